@@ -0,0 +1,365 @@
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use crate::cardano::bech32;
+use crate::cardano::cbor::{Decoder, MAJOR_MAP};
+
+// Shelley address layout (CIP-19): a header byte holding the address type in
+// the high nibble and the network id in the low one, then 28-byte
+// credentials. Byron addresses are base58 CBOR with a CRC32.
+
+/// A payment or stake credential: the hash of a verification key or script.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", content = "hash", rename_all = "lowercase")]
+pub enum Credential {
+    Key(String),
+    Script(String),
+}
+
+/// Where a pointer address finds its stake registration certificate.
+#[derive(Debug, Clone, Serialize)]
+pub struct Pointer {
+    pub slot: u64,
+    pub tx_index: u64,
+    pub cert_index: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AddressKind {
+    Base,
+    Pointer,
+    Enterprise,
+    Reward,
+    Byron,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Address {
+    pub address: String,
+    pub kind: AddressKind,
+    /// 1 on mainnet, 0 on every test network. Byron addresses carry a
+    /// protocol magic instead.
+    pub network_id: Option<u8>,
+    pub protocol_magic: Option<u32>,
+    pub payment: Option<Credential>,
+    pub stake: Option<Credential>,
+    pub pointer: Option<Pointer>,
+    /// Byron address root: the hash of the spending data and attributes.
+    pub root: Option<String>,
+    /// The reward address of the stake credential, for base and reward addresses.
+    pub stake_address: Option<String>,
+}
+
+impl Address {
+    pub fn parse(text: &str) -> Result<Self> {
+        let text = text.trim();
+        if text.starts_with("addr") || text.starts_with("stake") {
+            Self::parse_shelley(text)
+        } else {
+            Self::parse_byron(text)
+        }
+    }
+
+    pub fn is_mainnet(&self) -> bool {
+        match self.kind {
+            AddressKind::Byron => self.protocol_magic.is_none(),
+            _ => self.network_id == Some(1),
+        }
+    }
+
+    fn parse_shelley(text: &str) -> Result<Self> {
+        let (hrp, payload) = bech32::decode(text)
+            .map_err(|e| anyhow::anyhow!("'{}' is not a valid bech32 address ({})", text, e))?;
+        let header = *payload.first().context("address has no payload")?;
+        let (address_type, network_id) = (header >> 4, header & 0x0f);
+        let body = &payload[1..];
+
+        let expected_hrp = match (address_type, network_id) {
+            (14 | 15, 1) => "stake",
+            (14 | 15, _) => "stake_test",
+            (_, 1) => "addr",
+            _ => "addr_test",
+        };
+        if hrp != expected_hrp {
+            bail!("prefix '{}' does not match the address header - expected {}", hrp, expected_hrp);
+        }
+
+        let credential = |bytes: &[u8], script: bool| -> Result<Credential> {
+            if bytes.len() != 28 {
+                bail!("credential is {} bytes - expected 28", bytes.len());
+            }
+            let hash = hex::encode(bytes);
+            Ok(if script { Credential::Script(hash) } else { Credential::Key(hash) })
+        };
+        let exact = |len: usize| -> Result<()> {
+            if body.len() != len {
+                bail!("address body is {} bytes - expected {}", body.len(), len);
+            }
+            Ok(())
+        };
+
+        let mut address = Address {
+            address: text.to_string(),
+            kind: AddressKind::Base,
+            network_id: Some(network_id),
+            protocol_magic: None,
+            payment: None,
+            stake: None,
+            pointer: None,
+            root: None,
+            stake_address: None,
+        };
+        match address_type {
+            0..=3 => {
+                exact(56)?;
+                address.payment = Some(credential(&body[..28], address_type & 0b01 != 0)?);
+                address.stake = Some(credential(&body[28..], address_type & 0b10 != 0)?);
+            }
+            4 | 5 => {
+                address.kind = AddressKind::Pointer;
+                address.payment = Some(credential(body.get(..28).context("address is too short")?, address_type == 5)?);
+                address.pointer = Some(read_pointer(&body[28..])?);
+            }
+            6 | 7 => {
+                exact(28)?;
+                address.kind = AddressKind::Enterprise;
+                address.payment = Some(credential(body, address_type == 7)?);
+            }
+            14 | 15 => {
+                exact(28)?;
+                address.kind = AddressKind::Reward;
+                address.stake = Some(credential(body, address_type == 15)?);
+            }
+            other => bail!("unknown Shelley address type {}", other),
+        }
+
+        if let Some(stake) = &address.stake {
+            address.stake_address = Some(stake_address(stake, network_id));
+        }
+        Ok(address)
+    }
+
+    fn parse_byron(text: &str) -> Result<Self> {
+        let raw = base58_decode(text)
+            .with_context(|| format!("'{}' is neither a bech32 nor a base58 Byron address", text))?;
+
+        // [tag 24 (bytes payload), crc32 of payload]
+        let mut outer = Decoder::new(&raw);
+        if outer.array()? != 2 {
+            bail!("Byron address is not a two-element array");
+        }
+        if outer.read_header()? != (6, 24) {
+            bail!("Byron address payload is not CBOR-tagged");
+        }
+        let payload = outer.bytes()?;
+        let checksum = outer.unsigned()?;
+        if crc32(&payload) as u64 != checksum {
+            bail!("Byron address checksum mismatch");
+        }
+
+        // [root hash, attributes, address type]
+        let mut inner = Decoder::new(&payload);
+        if inner.array()? != 3 {
+            bail!("Byron address body is not a three-element array");
+        }
+        let root = inner.bytes()?;
+        let (major, attributes) = inner.read_header()?;
+        if major != MAJOR_MAP {
+            bail!("Byron address attributes are not a map");
+        }
+        let mut protocol_magic = None;
+        for _ in 0..attributes {
+            let key = inner.unsigned()?;
+            let value = inner.bytes()
+                .with_context(|| format!("Byron address attribute {} is not a byte string", key))?;
+            // Attribute 1 is the encrypted HD derivation path; 2 is the network magic
+            if key == 2 {
+                protocol_magic = Some(Decoder::new(&value).unsigned()? as u32);
+            }
+        }
+
+        Ok(Address {
+            address: text.to_string(),
+            kind: AddressKind::Byron,
+            network_id: None,
+            protocol_magic,
+            payment: None,
+            stake: None,
+            pointer: None,
+            root: Some(hex::encode(root)),
+            stake_address: None,
+        })
+    }
+}
+
+impl std::str::FromStr for Address {
+    type Err = anyhow::Error;
+
+    fn from_str(text: &str) -> Result<Self> {
+        Address::parse(text)
+    }
+}
+
+/// The reward address for a stake credential.
+pub fn stake_address(stake: &Credential, network_id: u8) -> String {
+    let (script, hash) = match stake {
+        Credential::Key(hash) => (false, hash),
+        Credential::Script(hash) => (true, hash),
+    };
+    let mut payload = vec![0xe0 | (u8::from(script) << 4) | network_id];
+    payload.extend(hex::decode(hash).expect("credential hashes are hex"));
+    bech32::encode(if network_id == 1 { "stake" } else { "stake_test" }, &payload)
+}
+
+fn read_pointer(bytes: &[u8]) -> Result<Pointer> {
+    let mut pos = 0;
+    let mut next = || -> Result<u64> {
+        let mut value: u64 = 0;
+        loop {
+            let byte = *bytes.get(pos).context("pointer is truncated")?;
+            pos += 1;
+            value = value.checked_mul(128).context("pointer value overflows")? | (byte & 0x7f) as u64;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+    };
+    Ok(Pointer { slot: next()?, tx_index: next()?, cert_index: next()? })
+}
+
+fn base58_decode(text: &str) -> Result<Vec<u8>> {
+    const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+    let mut bytes: Vec<u8> = Vec::new();
+    for c in text.bytes() {
+        let mut carry = ALPHABET.iter().position(|&a| a == c).context("invalid base58 character")? as u32;
+        for byte in bytes.iter_mut().rev() {
+            carry += *byte as u32 * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.insert(0, carry as u8);
+            carry >>= 8;
+        }
+    }
+    let zeros = text.bytes().take_while(|&c| c == b'1').count();
+    let mut out = vec![0u8; zeros];
+    out.extend(bytes);
+    Ok(out)
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xedb8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // CIP-19 test vectors
+    const PAYMENT_KEY: &str = "9493315cd92eb5d8c4304e67b7e16ae36d61d34502694657811a2c8e";
+    const SCRIPT: &str = "c37b1b5dc0669f1d3c61a6fddb2e8fde96be87b881c60bce8e8d542f";
+    const STAKE_KEY: &str = "337b62cfff6403a06a3acbc34f8c46003c69fe79a3628cefa9c47251";
+    const STAKE_ADDRESS: &str = "stake1uyehkck0lajq8gr28t9uxnuvgcqrc6070x3k9r8048z8y5gh6ffgw";
+
+    fn key(hash: &str) -> Option<Credential> {
+        Some(Credential::Key(hash.to_string()))
+    }
+
+    fn script(hash: &str) -> Option<Credential> {
+        Some(Credential::Script(hash.to_string()))
+    }
+
+    #[test]
+    fn base_addresses_carry_both_credentials() {
+        let vectors = [
+            ("addr1qx2fxv2umyhttkxyxp8x0dlpdt3k6cwng5pxj3jhsydzer3n0d3vllmyqwsx5wktcd8cc3sq835lu7drv2xwl2wywfgse35a3x", key(PAYMENT_KEY), key(STAKE_KEY)),
+            ("addr1z8phkx6acpnf78fuvxn0mkew3l0fd058hzquvz7w36x4gten0d3vllmyqwsx5wktcd8cc3sq835lu7drv2xwl2wywfgs9yc0hh", script(SCRIPT), key(STAKE_KEY)),
+            ("addr1yx2fxv2umyhttkxyxp8x0dlpdt3k6cwng5pxj3jhsydzerkr0vd4msrxnuwnccdxlhdjar77j6lg0wypcc9uar5d2shs2z78ve", key(PAYMENT_KEY), script(SCRIPT)),
+            ("addr1x8phkx6acpnf78fuvxn0mkew3l0fd058hzquvz7w36x4gt7r0vd4msrxnuwnccdxlhdjar77j6lg0wypcc9uar5d2shskhj42g", script(SCRIPT), script(SCRIPT)),
+        ];
+        for (text, payment, stake) in vectors {
+            let address = Address::parse(text).unwrap();
+            assert_eq!(address.kind, AddressKind::Base, "{}", text);
+            assert_eq!(address.network_id, Some(1));
+            assert_eq!((address.payment, address.stake), (payment, stake), "{}", text);
+        }
+
+        let address = Address::parse("addr1qx2fxv2umyhttkxyxp8x0dlpdt3k6cwng5pxj3jhsydzer3n0d3vllmyqwsx5wktcd8cc3sq835lu7drv2xwl2wywfgse35a3x").unwrap();
+        assert_eq!(address.stake_address.as_deref(), Some(STAKE_ADDRESS));
+    }
+
+    #[test]
+    fn pointer_addresses_decode_the_certificate_pointer() {
+        for text in [
+            "addr1gx2fxv2umyhttkxyxp8x0dlpdt3k6cwng5pxj3jhsydzer5pnz75xxcrzqf96k",
+            "addr128phkx6acpnf78fuvxn0mkew3l0fd058hzquvz7w36x4gtupnz75xxcrtw79hu",
+        ] {
+            let address = Address::parse(text).unwrap();
+            assert_eq!(address.kind, AddressKind::Pointer);
+            let pointer = address.pointer.unwrap();
+            assert_eq!((pointer.slot, pointer.tx_index, pointer.cert_index), (2498243, 27, 3));
+            assert_eq!(address.stake_address, None);
+        }
+    }
+
+    #[test]
+    fn enterprise_and_reward_addresses() {
+        let enterprise = Address::parse("addr1vx2fxv2umyhttkxyxp8x0dlpdt3k6cwng5pxj3jhsydzers66hrl8").unwrap();
+        assert_eq!((enterprise.kind, enterprise.payment, enterprise.stake), (AddressKind::Enterprise, key(PAYMENT_KEY), None));
+
+        let enterprise = Address::parse("addr1w8phkx6acpnf78fuvxn0mkew3l0fd058hzquvz7w36x4gtcyjy7wx").unwrap();
+        assert_eq!(enterprise.payment, script(SCRIPT));
+
+        let reward = Address::parse(STAKE_ADDRESS).unwrap();
+        assert_eq!((reward.kind, reward.stake), (AddressKind::Reward, key(STAKE_KEY)));
+        assert_eq!(reward.stake_address.as_deref(), Some(STAKE_ADDRESS));
+
+        let reward = Address::parse("stake178phkx6acpnf78fuvxn0mkew3l0fd058hzquvz7w36x4gtcccycj5").unwrap();
+        assert_eq!(reward.stake, script(SCRIPT));
+    }
+
+    #[test]
+    fn testnet_addresses_use_network_zero() {
+        let address = Address::parse("addr_test1qz2fxv2umyhttkxyxp8x0dlpdt3k6cwng5pxj3jhsydzer3n0d3vllmyqwsx5wktcd8cc3sq835lu7drv2xwl2wywfgs68faae").unwrap();
+        assert_eq!(address.network_id, Some(0));
+        assert!(!address.is_mainnet());
+        assert_eq!(address.stake_address.as_deref(), Some("stake_test1uqehkck0lajq8gr28t9uxnuvgcqrc6070x3k9r8048z8y5gssrtvn"));
+    }
+
+    #[test]
+    fn mismatched_prefix_is_rejected() {
+        // A mainnet payload under the testnet prefix
+        let payload = bech32::decode(STAKE_ADDRESS).unwrap().1;
+        let error = Address::parse(&bech32::encode("stake_test", &payload)).unwrap_err().to_string();
+        assert!(error.contains("does not match the address header"), "{}", error);
+    }
+
+    #[test]
+    fn byron_addresses_carry_the_protocol_magic() {
+        let testnet = Address::parse("37btjrVyb4KDXBNC4haBVPCrro8AQPHwvCMp3RFhhSVWwfFmZ6wwzSK6JK1hY6wHNmtrpTf1kdbva8TCneM2YsiXT7mrzT21EacHnPpz5YyUdj64na").unwrap();
+        assert_eq!(testnet.kind, AddressKind::Byron);
+        assert_eq!(testnet.protocol_magic, Some(1097911063));
+        assert!(!testnet.is_mainnet());
+        assert_eq!(testnet.root.map(|r| r.len()), Some(56));
+
+        let mainnet = Address::parse("Ae2tdPwUPEZFRbyhz3cpfC2CumGzNkFBN2L42rcUc2yjQpEkxDbkPodpMAi").unwrap();
+        assert_eq!(mainnet.protocol_magic, None);
+        assert!(mainnet.is_mainnet());
+    }
+
+    #[test]
+    fn byron_checksum_is_verified() {
+        // One character changed in the payload
+        let error = Address::parse("Ae2tdPwUPEZFRbyhz3cpfC2CumGzNkFBN2L42rcUc2yjQpEkxDbkPodpMAj").unwrap_err();
+        assert!(format!("{:#}", error).contains("checksum"), "{:#}", error);
+    }
+}
@@ -0,0 +1,161 @@
+use anyhow::{bail, Result};
+
+// BIP-173 bech32 as used by Cardano for pool IDs, addresses and keys. Cardano
+// strings exceed the 90 character limit of the BIP, so no length limit applies.
+
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const GENERATOR: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+
+fn polymod(values: impl Iterator<Item = u8>) -> u32 {
+    let mut checksum: u32 = 1;
+    for value in values {
+        let top = checksum >> 25;
+        checksum = ((checksum & 0x1ff_ffff) << 5) ^ value as u32;
+        for (i, generator) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                checksum ^= generator;
+            }
+        }
+    }
+    checksum
+}
+
+fn hrp_expand(hrp: &str) -> Vec<u8> {
+    let bytes = hrp.as_bytes();
+    bytes.iter().map(|b| b >> 5)
+        .chain(std::iter::once(0))
+        .chain(bytes.iter().map(|b| b & 0x1f))
+        .collect()
+}
+
+/// Decode a bech32 string into its human-readable part and payload bytes.
+pub fn decode(text: &str) -> Result<(String, Vec<u8>)> {
+    if text.chars().any(|c| c.is_ascii_lowercase()) && text.chars().any(|c| c.is_ascii_uppercase()) {
+        bail!("mixed case");
+    }
+    let text = text.to_ascii_lowercase();
+
+    let separator = match text.rfind('1') {
+        Some(pos) if pos > 0 => pos,
+        _ => bail!("missing human-readable part"),
+    };
+    let (hrp, data) = (&text[..separator], &text[separator + 1..]);
+    if data.len() < 6 {
+        bail!("too short");
+    }
+    if hrp.bytes().any(|b| !(33..=126).contains(&b)) {
+        bail!("invalid character in prefix");
+    }
+
+    let values = data.bytes()
+        .map(|b| CHARSET.iter().position(|&c| c == b).map(|v| v as u8))
+        .collect::<Option<Vec<u8>>>();
+    let values = match values {
+        Some(values) => values,
+        None => bail!("invalid character"),
+    };
+
+    if polymod(hrp_expand(hrp).into_iter().chain(values.iter().copied())) != 1 {
+        bail!("checksum mismatch");
+    }
+
+    let payload = convert_bits(&values[..values.len() - 6], 5, 8, false)?;
+    Ok((hrp.to_string(), payload))
+}
+
+/// Encode payload bytes under the given human-readable part.
+pub fn encode(hrp: &str, payload: &[u8]) -> String {
+    let values = convert_bits(payload, 8, 5, true).expect("padding never fails");
+    let checksum_input = hrp_expand(hrp).into_iter()
+        .chain(values.iter().copied())
+        .chain([0u8; 6]);
+    let checksum = polymod(checksum_input) ^ 1;
+
+    let mut text = String::with_capacity(hrp.len() + 1 + values.len() + 6);
+    text.push_str(hrp);
+    text.push('1');
+    for value in values.iter().copied().chain((0..6).map(|i| ((checksum >> (5 * (5 - i))) & 0x1f) as u8)) {
+        text.push(CHARSET[value as usize] as char);
+    }
+    text
+}
+
+fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Result<Vec<u8>> {
+    let mut acc: u32 = 0;
+    let mut bits: u32 = 0;
+    let max = (1u32 << to) - 1;
+    let mut out = Vec::with_capacity(data.len() * from as usize / to as usize + 1);
+
+    for &value in data {
+        acc = (acc << from) | value as u32;
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            out.push(((acc >> bits) & max) as u8);
+        }
+    }
+
+    if pad {
+        if bits > 0 {
+            out.push(((acc << (to - bits)) & max) as u8);
+        }
+    } else if bits >= from || ((acc << (to - bits)) & max) != 0 {
+        bail!("invalid padding");
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bip173_valid_strings_decode() {
+        let long_hrp = format!("11{}c8247j", "q".repeat(82));
+        for text in [
+            "A12UEL5L",
+            "a12uel5l",
+            "an83characterlonghumanreadablepartthatcontainsthenumber1andtheexcludedcharactersbio1tt5tgs",
+            "abcdef1qpzry9x8gf2tvdw0s3jn54khce6mua7lmqqqxw",
+            long_hrp.as_str(),
+            "split1checkupstagehandshakeupstreamerranterredcaperred2y9e3w",
+        ] {
+            assert!(decode(text).is_ok(), "{}: {:?}", text, decode(text));
+        }
+        assert_eq!(decode("A12UEL5L").unwrap(), ("a".to_string(), Vec::new()));
+    }
+
+    #[test]
+    fn bip173_invalid_strings_are_rejected() {
+        for (text, reason) in [
+            ("pzry9x0s0muk", "missing human-readable part"),
+            ("1pzry9x0s0muk", "missing human-readable part"),
+            ("x1b4n0q5v", "invalid character"),
+            ("li1dgmt3", "too short"),
+            ("A1G7SGD8", "checksum mismatch"),
+            ("a12uEL5L", "mixed case"),
+            ("\u{7f}1axkwrx", "invalid character in prefix"),
+        ] {
+            let error = decode(text).unwrap_err().to_string();
+            assert_eq!(error, reason, "{}", text);
+        }
+    }
+
+    #[test]
+    fn encode_round_trips() {
+        let payload: Vec<u8> = (0..57).collect();
+        let text = encode("addr_test", &payload);
+        assert!(text.starts_with("addr_test1"));
+        assert_eq!(decode(&text).unwrap(), ("addr_test".to_string(), payload));
+        assert_eq!(decode(&text.to_uppercase()).unwrap().0, "addr_test");
+    }
+
+    #[test]
+    fn a_single_changed_character_breaks_the_checksum() {
+        let text = encode("pool", &[7u8; 28]);
+        let mut corrupted = text.clone().into_bytes();
+        let last = corrupted.len() - 10;
+        corrupted[last] = if corrupted[last] == b'q' { b'p' } else { b'q' };
+        assert_eq!(decode(std::str::from_utf8(&corrupted).unwrap()).unwrap_err().to_string(), "checksum mismatch");
+    }
+}
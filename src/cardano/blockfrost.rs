@@ -1,125 +1,119 @@
 use anyhow::{Result, Context};
 use reqwest::Client;
 use serde_json::Value;
+use std::sync::Arc;
+use crate::cardano::cache::{QueryCache, Ttl};
 use crate::utils::config::Config;
+use crate::utils::error::CliError;
+use crate::utils::secret::Secret;
 
 pub struct BlockfrostClient {
     client: Client,
     base_url: String,
-    api_key: String,
+    api_key: Secret,
+    cache: Arc<QueryCache>,
 }
 
 impl BlockfrostClient {
     pub fn new(config: &Config) -> Option<Self> {
         match &config.blockfrost {
-            Some(blockfrost_config) => {
+            // A section without a resolvable project ID cannot be used
+            Some(blockfrost_config) if !blockfrost_config.api_key.is_empty() => {
                 Some(Self {
                     client: Client::new(),
                     base_url: blockfrost_config.base_url.clone(),
                     api_key: blockfrost_config.api_key.clone(),
+                    cache: QueryCache::shared(config),
                 })
             }
-            None => None,
+            _ => None,
         }
     }
 
     pub async fn get_network_info(&self) -> Result<Value> {
-        let url = format!("{}/network", self.base_url);
-
-        let response = self.client
-            .get(&url)
-            .header("project_id", &self.api_key)
-            .send()
-            .await
-            .context("Failed to send request to Blockfrost API")?;
-
-        if !response.status().is_success() {
-            anyhow::bail!("Blockfrost API returned status: {}", response.status());
-        }
-
-        let json: Value = response.json()
-            .await
-            .context("Failed to parse JSON response from Blockfrost")?;
-
-        Ok(json)
+        self.get("network", &[], Ttl::Default).await
     }
 
     pub async fn get_latest_epoch(&self) -> Result<Value> {
-        let url = format!("{}/epochs/latest", self.base_url);
-
-        let response = self.client
-            .get(&url)
-            .header("project_id", &self.api_key)
-            .send()
-            .await
-            .context("Failed to send request to Blockfrost API")?;
-
-        if !response.status().is_success() {
-            anyhow::bail!("Blockfrost API returned status: {}", response.status());
-        }
-
-        let json: Value = response.json()
-            .await
-            .context("Failed to parse JSON response from Blockfrost")?;
-
-        Ok(json)
+        // Block count and stake grow during the epoch
+        self.get("epochs/latest", &[], Ttl::Short).await
     }
 
     pub async fn get_all_pools(&self) -> Result<Value> {
-        let url = format!("{}/pools", self.base_url);
+        self.get("pools", &[("count", "100".to_string())], Ttl::Epoch).await
+    }
 
-        let response = self.client
-            .get(&url)
-            .header("project_id", &self.api_key)
-            .query(&[("count", "100")])
-            .send()
-            .await
-            .context("Failed to send request to Blockfrost API")?;
+    pub async fn get_pool_info(&self, pool_id: &str) -> Result<Value> {
+        self.get(&format!("pools/{}", pool_id), &[], Ttl::Default).await
+    }
 
-        if !response.status().is_success() {
-            anyhow::bail!("Blockfrost API returned status: {}", response.status());
-        }
+    pub async fn get_pool_metadata(&self, pool_id: &str) -> Result<Value> {
+        self.get(&format!("pools/{}/metadata", pool_id), &[], Ttl::Epoch).await
+    }
 
-        let json: Value = response.json()
-            .await
-            .context("Failed to parse JSON response from Blockfrost")?;
+    pub async fn get_pool_relays(&self, pool_id: &str) -> Result<Value> {
+        self.get(&format!("pools/{}/relays", pool_id), &[], Ttl::Epoch).await
+    }
 
-        Ok(json)
+    pub async fn get_pool_history(&self, pool_id: &str, page: u32) -> Result<Value> {
+        let query = [
+            ("count", "100".to_string()),
+            ("order", "desc".to_string()),
+            ("page", page.to_string()),
+        ];
+        self.get(&format!("pools/{}/history", pool_id), &query, Ttl::Epoch).await
     }
 
-    pub async fn get_pool_info(&self, pool_id: &str) -> Result<Value> {
-        let url = format!("{}/pools/{}", self.base_url, pool_id);
+    /// The newest page of pool history, bypassing the cache: its latest
+    /// entries gain their rewards after the page was cached for the epoch.
+    pub async fn get_latest_pool_history(&self, pool_id: &str) -> Result<Value> {
+        let query = [
+            ("count", "100".to_string()),
+            ("order", "desc".to_string()),
+            ("page", "1".to_string()),
+        ];
+        self.fetch(&format!("{}/pools/{}/history", self.base_url, pool_id), &query).await
+    }
 
-        let response = self.client
-            .get(&url)
-            .header("project_id", &self.api_key)
-            .send()
-            .await
-            .context("Failed to send request to Blockfrost API")?;
+    pub async fn get_latest_block(&self) -> Result<Value> {
+        self.get("blocks/latest", &[], Ttl::Short).await
+    }
 
-        if !response.status().is_success() {
-            anyhow::bail!("Blockfrost API returned status: {}", response.status());
-        }
+    pub async fn get_pool_delegators(&self, pool_id: &str, page: u32) -> Result<Value> {
+        let query = [("count", "100".to_string()), ("page", page.to_string())];
+        self.get(&format!("pools/{}/delegators", pool_id), &query, Ttl::Default).await
+    }
 
-        let json: Value = response.json()
-            .await
-            .context("Failed to parse JSON response from Blockfrost")?;
+    pub async fn get_account(&self, stake_address: &str) -> Result<Value> {
+        self.get(&format!("accounts/{}", stake_address), &[], Ttl::Short).await
+    }
 
-        Ok(json)
+    pub async fn get_account_rewards(&self, stake_address: &str) -> Result<Value> {
+        let query = [("count", "100".to_string()), ("order", "desc".to_string())];
+        self.get(&format!("accounts/{}/rewards", stake_address), &query, Ttl::Epoch).await
     }
 
-    pub async fn get_pool_metadata(&self, pool_id: &str) -> Result<Value> {
-        let url = format!("{}/pools/{}/metadata", self.base_url, pool_id);
+    async fn get(&self, path: &str, query: &[(&str, String)], ttl: Ttl) -> Result<Value> {
+        let url = format!("{}/{}", self.base_url, path);
+        let key = format!("blockfrost:{}?{}", url, query.iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<_>>()
+            .join("&"));
 
+        self.cache.get_or_fetch(&key, ttl, || self.fetch(&url, query)).await
+    }
+
+    async fn fetch(&self, url: &str, query: &[(&str, String)]) -> Result<Value> {
         let response = self.client
-            .get(&url)
-            .header("project_id", &self.api_key)
+            .get(url)
+            .header("project_id", self.api_key.expose())
+            .query(query)
             .send()
             .await
-            .context("Failed to send request to Blockfrost API")?;
+            .map_err(|e| CliError::Network(format!("Failed to send request to Blockfrost API: {}", e)))?;
 
         if !response.status().is_success() {
-            anyhow::bail!("Blockfrost API returned status: {}", response.status());
+            return Err(CliError::from_status("Blockfrost", response.status()).into());
         }
 
         let json: Value = response.json()
@@ -128,4 +122,4 @@ impl BlockfrostClient {
 
         Ok(json)
     }
-}
\ No newline at end of file
+}
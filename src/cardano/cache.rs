@@ -0,0 +1,305 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use crate::cardano::chain_time::ChainTime;
+use crate::cardano::metadata::blake2b_256_hex;
+use crate::utils::config::{CacheConfig, Config};
+
+static SHARED: OnceLock<Mutex<HashMap<String, Arc<QueryCache>>>> = OnceLock::new();
+
+/// How long a cached answer stays valid.
+#[derive(Debug, Clone, Copy)]
+pub enum Ttl {
+    /// Fast-moving data such as the chain tip.
+    Short,
+    /// Data that drifts slowly within an epoch, such as live stake.
+    Default,
+    /// Data fixed for the whole epoch: stake snapshots, parameters, history.
+    Epoch,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct CacheCounters {
+    pub hits: u64,
+    pub misses: u64,
+    pub coalesced: u64,
+    pub disk_hits: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Entry {
+    expires_at: DateTime<Utc>,
+    value: Value,
+}
+
+/// Memory (and optionally disk) cache in front of chain queries. Concurrent
+/// callers asking for the same key wait for a single fetch.
+pub struct QueryCache {
+    /// Prefix for every key, so profiles never read each other's answers.
+    scope: String,
+    config: CacheConfig,
+    chain_time: Option<ChainTime>,
+    entries: Mutex<HashMap<String, Entry>>,
+    inflight: Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    coalesced: AtomicU64,
+    disk_hits: AtomicU64,
+}
+
+impl QueryCache {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            scope: Self::scope(config),
+            config: config.cache.clone(),
+            chain_time: ChainTime::load(&config.cardano).ok(),
+            entries: Mutex::new(HashMap::new()),
+            inflight: Mutex::new(HashMap::new()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            coalesced: AtomicU64::new(0),
+            disk_hits: AtomicU64::new(0),
+        }
+    }
+
+    /// The cache for this config's profile, shared by every client instance
+    /// built from it. Each profile gets its own TTLs, directory and chain time.
+    pub fn shared(config: &Config) -> Arc<Self> {
+        SHARED.get_or_init(Default::default)
+            .lock().unwrap()
+            .entry(Self::scope(config))
+            .or_insert_with(|| Arc::new(Self::new(config)))
+            .clone()
+    }
+
+    fn scope(config: &Config) -> String {
+        format!("{}:{}", config.profile.as_deref().unwrap_or("default"), config.cardano.network)
+    }
+
+    pub fn counters(&self) -> CacheCounters {
+        CacheCounters {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            coalesced: self.coalesced.load(Ordering::Relaxed),
+            disk_hits: self.disk_hits.load(Ordering::Relaxed),
+        }
+    }
+
+    pub async fn get_or_fetch<F, Fut>(&self, key: &str, ttl: Ttl, fetch: F) -> Result<Value>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Value>>,
+    {
+        if !self.config.enabled {
+            return fetch().await;
+        }
+        let key = &format!("{}:{}", self.scope, key);
+
+        if let Some(value) = self.lookup(key) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(value);
+        }
+
+        let gate = self.inflight.lock().unwrap()
+            .entry(key.to_string())
+            .or_default()
+            .clone();
+        let result = {
+            let _guard = gate.lock().await;
+            self.fetch_gated(key, ttl, fetch).await
+        };
+
+        // Only the map and this caller hold the gate: nobody else is waiting
+        let mut inflight = self.inflight.lock().unwrap();
+        if Arc::strong_count(&gate) == 2 {
+            inflight.remove(key);
+        }
+        result
+    }
+
+    async fn fetch_gated<F, Fut>(&self, key: &str, ttl: Ttl, fetch: F) -> Result<Value>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Value>>,
+    {
+        // Someone else may have fetched it while we waited
+        if let Some(value) = self.lookup(key) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            self.coalesced.fetch_add(1, Ordering::Relaxed);
+            return Ok(value);
+        }
+
+        if let Some(entry) = self.read_disk(key, ttl) {
+            self.disk_hits.fetch_add(1, Ordering::Relaxed);
+            let value = entry.value.clone();
+            self.entries.lock().unwrap().insert(key.to_string(), entry);
+            return Ok(value);
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let result = fetch().await;
+
+        if let Ok(value) = &result {
+            let entry = Entry {
+                expires_at: self.expiry(ttl),
+                value: value.clone(),
+            };
+            self.write_disk(key, ttl, &entry);
+            self.entries.lock().unwrap().insert(key.to_string(), entry);
+        }
+        result
+    }
+
+    fn lookup(&self, key: &str) -> Option<Value> {
+        let entries = self.entries.lock().unwrap();
+        entries.get(key)
+            .filter(|entry| entry.expires_at > Utc::now())
+            .map(|entry| entry.value.clone())
+    }
+
+    fn expiry(&self, ttl: Ttl) -> DateTime<Utc> {
+        let now = Utc::now();
+        let seconds = |s: u64| chrono::Duration::seconds(s as i64);
+
+        match ttl {
+            Ttl::Short => now + seconds(self.config.tip_ttl_seconds),
+            Ttl::Default => now + seconds(self.config.default_ttl_seconds),
+            Ttl::Epoch => match &self.chain_time {
+                Some(chain_time) => now + chain_time.time_until_next_epoch(now),
+                None => now + seconds(self.config.default_ttl_seconds),
+            },
+        }
+    }
+
+    /// Only slow-moving data goes to disk: a tip or UTxO set read back by a
+    /// later run would already be stale.
+    fn disk_path(&self, key: &str, ttl: Ttl) -> Option<PathBuf> {
+        if !self.config.disk_enabled || matches!(ttl, Ttl::Short) {
+            return None;
+        }
+        Some(PathBuf::from(&self.config.directory).join(format!("{}.json", blake2b_256_hex(key.as_bytes()))))
+    }
+
+    fn read_disk(&self, key: &str, ttl: Ttl) -> Option<Entry> {
+        let content = std::fs::read_to_string(self.disk_path(key, ttl)?).ok()?;
+        let entry: Entry = serde_json::from_str(&content).ok()?;
+        (entry.expires_at > Utc::now()).then_some(entry)
+    }
+
+    fn write_disk(&self, key: &str, ttl: Ttl, entry: &Entry) {
+        let path = match self.disk_path(key, ttl) {
+            Some(path) => path,
+            None => return,
+        };
+
+        let result = std::fs::create_dir_all(&self.config.directory)
+            .and_then(|_| std::fs::write(&path, serde_json::to_vec(entry).unwrap_or_default()));
+        if let Err(e) = result {
+            log::warn!("Failed to write cache entry {}: {}", path.display(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    fn profile(name: &str) -> Config {
+        Config { profile: Some(name.to_string()), ..Config::default() }
+    }
+
+    fn with_tip_ttl(seconds: u64) -> QueryCache {
+        let mut config = Config::default();
+        config.cache.tip_ttl_seconds = seconds;
+        QueryCache::new(&config)
+    }
+
+    #[tokio::test]
+    async fn entries_are_served_until_they_expire() {
+        let calls = AtomicUsize::new(0);
+        let fetch = || async { Ok(Value::from(calls.fetch_add(1, Ordering::SeqCst))) };
+
+        let cache = with_tip_ttl(60);
+        assert_eq!(cache.get_or_fetch("tip", Ttl::Short, fetch).await.unwrap(), 0);
+        assert_eq!(cache.get_or_fetch("tip", Ttl::Short, fetch).await.unwrap(), 0);
+        assert_eq!(cache.counters().hits, 1);
+
+        let expired = with_tip_ttl(0);
+        assert_eq!(expired.get_or_fetch("tip", Ttl::Short, fetch).await.unwrap(), 1);
+        assert_eq!(expired.get_or_fetch("tip", Ttl::Short, fetch).await.unwrap(), 2);
+        assert_eq!(expired.counters().misses, 2);
+    }
+
+    #[tokio::test]
+    async fn concurrent_callers_share_one_fetch() {
+        let cache = with_tip_ttl(60);
+        let calls = AtomicUsize::new(0);
+        let fetch = || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            Ok(Value::from("tip"))
+        };
+
+        let (first, second, third) = tokio::join!(
+            cache.get_or_fetch("tip", Ttl::Short, fetch),
+            cache.get_or_fetch("tip", Ttl::Short, fetch),
+            cache.get_or_fetch("tip", Ttl::Short, fetch),
+        );
+        assert_eq!((first.unwrap(), second.unwrap(), third.unwrap()), ("tip".into(), "tip".into(), "tip".into()));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(cache.counters().coalesced, 2);
+        assert!(cache.inflight.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn failed_fetches_are_not_cached() {
+        let cache = with_tip_ttl(60);
+        let failed = cache.get_or_fetch("tip", Ttl::Short, || async { anyhow::bail!("node down") }).await;
+        assert!(failed.is_err());
+
+        let value = cache.get_or_fetch("tip", Ttl::Short, || async { Ok(Value::from(1)) }).await.unwrap();
+        assert_eq!(value, 1);
+        assert!(cache.inflight.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn profiles_do_not_share_entries() {
+        let (relay, producer) = (QueryCache::shared(&profile("relay")), QueryCache::shared(&profile("producer")));
+        assert!(!Arc::ptr_eq(&relay, &producer));
+        assert!(Arc::ptr_eq(&relay, &QueryCache::shared(&profile("relay"))));
+
+        relay.get_or_fetch("tip", Ttl::Short, || async { Ok(Value::from(1)) }).await.unwrap();
+        let value = producer.get_or_fetch("tip", Ttl::Short, || async { Ok(Value::from(2)) }).await.unwrap();
+        assert_eq!(value, 2);
+    }
+
+    #[tokio::test]
+    async fn short_lived_entries_stay_off_disk() {
+        let dir = std::env::temp_dir().join(format!("cvc-cache-{}", std::process::id()));
+        let mut config = Config::default();
+        config.cache.disk_enabled = true;
+        config.cache.directory = dir.to_string_lossy().to_string();
+
+        let cache = QueryCache::new(&config);
+        cache.get_or_fetch("tip", Ttl::Short, || async { Ok(Value::from(1)) }).await.unwrap();
+        cache.get_or_fetch("history", Ttl::Epoch, || async { Ok(Value::from(2)) }).await.unwrap();
+        let files = std::fs::read_dir(&dir).map_or(0, |entries| entries.count());
+
+        // A later run reads the epoch entry back but fetches the tip again
+        let later = QueryCache::new(&config);
+        let tip = later.get_or_fetch("tip", Ttl::Short, || async { Ok(Value::from(3)) }).await.unwrap();
+        let history = later.get_or_fetch("history", Ttl::Epoch, || async { Ok(Value::from(4)) }).await.unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(files, 1);
+        assert_eq!((tip, history), (Value::from(3), Value::from(2)));
+        assert_eq!(later.counters().disk_hits, 1);
+    }
+}
@@ -0,0 +1,146 @@
+use anyhow::{Result, Context};
+
+// Minimal CBOR reader/writer covering the subset of types found in node key
+// and certificate envelopes and node-to-node handshake messages.
+
+pub const MAJOR_UNSIGNED: u8 = 0;
+pub const MAJOR_BYTES: u8 = 2;
+pub const MAJOR_TEXT: u8 = 3;
+pub const MAJOR_ARRAY: u8 = 4;
+pub const MAJOR_MAP: u8 = 5;
+pub const MAJOR_SIMPLE: u8 = 7;
+
+pub struct Decoder<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Decoder<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn read_byte(&mut self) -> Result<u8> {
+        let byte = *self.data.get(self.pos).context("Unexpected end of CBOR data")?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    fn read_slice(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self.pos.checked_add(len).context("CBOR length overflow")?;
+        let slice = self.data.get(self.pos..end).context("Unexpected end of CBOR data")?;
+        self.pos = end;
+        Ok(slice)
+    }
+
+    pub fn read_header(&mut self) -> Result<(u8, u64)> {
+        let initial = self.read_byte()?;
+        let major = initial >> 5;
+        let info = initial & 0x1f;
+
+        let value = match info {
+            0..=23 => info as u64,
+            24 => self.read_byte()? as u64,
+            25 => u16::from_be_bytes(self.read_slice(2)?.try_into()?) as u64,
+            26 => u32::from_be_bytes(self.read_slice(4)?.try_into()?) as u64,
+            27 => u64::from_be_bytes(self.read_slice(8)?.try_into()?),
+            _ => anyhow::bail!("Unsupported CBOR additional info: {}", info),
+        };
+
+        Ok((major, value))
+    }
+
+    fn expect(&mut self, expected: u8) -> Result<u64> {
+        let (major, value) = self.read_header()?;
+        if major != expected {
+            anyhow::bail!("Expected CBOR major type {}, found {}", expected, major);
+        }
+        Ok(value)
+    }
+
+    pub fn array(&mut self) -> Result<u64> {
+        self.expect(MAJOR_ARRAY)
+    }
+
+    pub fn unsigned(&mut self) -> Result<u64> {
+        self.expect(MAJOR_UNSIGNED)
+    }
+
+    pub fn bytes(&mut self) -> Result<Vec<u8>> {
+        let len = self.expect(MAJOR_BYTES)? as usize;
+        Ok(self.read_slice(len)?.to_vec())
+    }
+
+    pub fn text(&mut self) -> Result<String> {
+        let len = self.expect(MAJOR_TEXT)? as usize;
+        String::from_utf8(self.read_slice(len)?.to_vec())
+            .context("Invalid UTF-8 in CBOR text string")
+    }
+}
+
+#[derive(Default)]
+pub struct Encoder {
+    buf: Vec<u8>,
+}
+
+impl Encoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn write_header(&mut self, major: u8, value: u64) -> &mut Self {
+        let major = major << 5;
+        if value < 24 {
+            self.buf.push(major | value as u8);
+        } else if value <= u8::MAX as u64 {
+            self.buf.push(major | 24);
+            self.buf.push(value as u8);
+        } else if value <= u16::MAX as u64 {
+            self.buf.push(major | 25);
+            self.buf.extend_from_slice(&(value as u16).to_be_bytes());
+        } else if value <= u32::MAX as u64 {
+            self.buf.push(major | 26);
+            self.buf.extend_from_slice(&(value as u32).to_be_bytes());
+        } else {
+            self.buf.push(major | 27);
+            self.buf.extend_from_slice(&value.to_be_bytes());
+        }
+        self
+    }
+
+    pub fn unsigned(&mut self, value: u64) -> &mut Self {
+        self.write_header(MAJOR_UNSIGNED, value)
+    }
+
+    pub fn array(&mut self, len: u64) -> &mut Self {
+        self.write_header(MAJOR_ARRAY, len)
+    }
+
+    pub fn map(&mut self, len: u64) -> &mut Self {
+        self.write_header(MAJOR_MAP, len)
+    }
+
+    pub fn bool(&mut self, value: bool) -> &mut Self {
+        self.write_header(MAJOR_SIMPLE, if value { 21 } else { 20 })
+    }
+
+    pub fn finish(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.buf)
+    }
+}
+
+/// Decode the `cborHex` field of a cardano-cli text envelope file.
+pub fn read_text_envelope(path: &str) -> Result<(String, Vec<u8>)> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path))?;
+    let envelope: serde_json::Value = serde_json::from_str(&content)
+        .with_context(|| format!("{} is not a valid text envelope", path))?;
+
+    let envelope_type = envelope["type"].as_str().unwrap_or("").to_string();
+    let cbor_hex = envelope["cborHex"]
+        .as_str()
+        .with_context(|| format!("{} has no cborHex field", path))?;
+    let bytes = hex::decode(cbor_hex).context("Invalid cborHex in text envelope")?;
+
+    Ok((envelope_type, bytes))
+}
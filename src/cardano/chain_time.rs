@@ -0,0 +1,302 @@
+use anyhow::{Result, Context};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use crate::cardano::cli::CardanoCli;
+use crate::utils::config::CardanoConfig;
+
+/// Slot/time/epoch arithmetic across the Byron and Shelley-based eras.
+#[derive(Debug, Clone)]
+pub struct ChainTime {
+    pub system_start: DateTime<Utc>,
+    pub byron_slot_length_secs: f64,
+    pub byron_epochs: u64,
+    pub security_param: u64,
+    pub epoch_length: u64,
+    pub slot_length_secs: f64,
+    pub active_slots_coeff: f64,
+}
+
+/// Notable points within one epoch.
+#[derive(Debug, Clone, Serialize)]
+pub struct EpochBoundaries {
+    pub epoch: u64,
+    pub start_slot: u64,
+    pub end_slot: u64,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    pub stability_window_slot: u64,
+    pub stability_window_time: DateTime<Utc>,
+    pub reward_calculation_slot: u64,
+    pub reward_calculation_time: DateTime<Utc>,
+}
+
+impl ChainTime {
+    /// Build from the configured genesis files, falling back to known network parameters.
+    pub fn load(config: &CardanoConfig) -> Result<Self> {
+        let mut chain_time = Self::for_network(&config.network);
+
+        if let Some(path) = &config.byron_genesis_file {
+            chain_time.apply_byron_genesis(&read_genesis(path)?)?;
+        }
+        if let Some(path) = &config.shelley_genesis_file {
+            chain_time.apply_shelley_genesis(&read_genesis(path)?)?;
+        }
+
+        Ok(chain_time)
+    }
+
+    pub fn for_network(network: &str) -> Self {
+        // (system start, Byron epochs before the hard fork, k, Shelley epoch length)
+        let (system_start, byron_epochs, security_param, epoch_length) = match network {
+            "preprod" => (1_654_041_600, 4, 2160, 432_000),
+            "preview" => (1_666_656_000, 0, 432, 86_400),
+            "testnet" => (1_563_999_616, 74, 2160, 432_000),
+            _ => (1_506_203_091, 208, 2160, 432_000),
+        };
+
+        Self {
+            system_start: Utc.timestamp_opt(system_start, 0).unwrap(),
+            byron_slot_length_secs: 20.0,
+            byron_epochs,
+            security_param,
+            epoch_length,
+            slot_length_secs: 1.0,
+            active_slots_coeff: 0.05,
+        }
+    }
+
+    fn apply_byron_genesis(&mut self, genesis: &Value) -> Result<()> {
+        let start = genesis["startTime"].as_i64().context("startTime missing from Byron genesis")?;
+        self.system_start = Utc.timestamp_opt(start, 0).single()
+            .context("Invalid startTime in Byron genesis")?;
+
+        if let Some(k) = genesis["protocolConsts"]["k"].as_u64() {
+            self.security_param = k;
+        }
+        // slotDuration is given in milliseconds, as a string
+        if let Some(ms) = genesis["blockVersionData"]["slotDuration"].as_str().and_then(|s| s.parse::<f64>().ok()) {
+            self.byron_slot_length_secs = ms / 1000.0;
+        }
+        Ok(())
+    }
+
+    fn apply_shelley_genesis(&mut self, genesis: &Value) -> Result<()> {
+        if let Some(start) = genesis["systemStart"].as_str() {
+            self.system_start = DateTime::parse_from_rfc3339(start)
+                .context("Invalid systemStart in Shelley genesis")?
+                .with_timezone(&Utc);
+        }
+
+        self.epoch_length = genesis["epochLength"].as_u64().context("epochLength missing from Shelley genesis")?;
+        self.slot_length_secs = genesis["slotLength"].as_f64().unwrap_or(self.slot_length_secs);
+        self.security_param = genesis["securityParam"].as_u64().unwrap_or(self.security_param);
+        self.active_slots_coeff = genesis["activeSlotsCoeff"].as_f64().unwrap_or(self.active_slots_coeff);
+        Ok(())
+    }
+
+    fn byron_epoch_length(&self) -> u64 {
+        self.security_param * 10
+    }
+
+    fn shelley_start_slot(&self) -> u64 {
+        self.byron_epochs * self.byron_epoch_length()
+    }
+
+    fn shelley_start_time(&self) -> DateTime<Utc> {
+        self.system_start + seconds(self.shelley_start_slot() as f64 * self.byron_slot_length_secs)
+    }
+
+    pub fn slot_to_time(&self, slot: u64) -> DateTime<Utc> {
+        let shelley_start = self.shelley_start_slot();
+        if slot < shelley_start {
+            self.system_start + seconds(slot as f64 * self.byron_slot_length_secs)
+        } else {
+            self.shelley_start_time() + seconds((slot - shelley_start) as f64 * self.slot_length_secs)
+        }
+    }
+
+    /// Slot in progress at `time`, or `None` before the system start.
+    pub fn time_to_slot(&self, time: DateTime<Utc>) -> Option<u64> {
+        if time < self.system_start {
+            return None;
+        }
+
+        let shelley_start_time = self.shelley_start_time();
+        if time < shelley_start_time {
+            let elapsed = seconds_between(self.system_start, time);
+            Some((elapsed / self.byron_slot_length_secs) as u64)
+        } else {
+            let elapsed = seconds_between(shelley_start_time, time);
+            Some(self.shelley_start_slot() + (elapsed / self.slot_length_secs) as u64)
+        }
+    }
+
+    /// (epoch, slot in epoch) for an absolute slot.
+    pub fn epoch_of_slot(&self, slot: u64) -> (u64, u64) {
+        let shelley_start = self.shelley_start_slot();
+        if slot < shelley_start {
+            (slot / self.byron_epoch_length(), slot % self.byron_epoch_length())
+        } else {
+            let since = slot - shelley_start;
+            (self.byron_epochs + since / self.epoch_length, since % self.epoch_length)
+        }
+    }
+
+    pub fn epoch_start_slot(&self, epoch: u64) -> u64 {
+        if epoch < self.byron_epochs {
+            epoch * self.byron_epoch_length()
+        } else {
+            self.shelley_start_slot() + (epoch - self.byron_epochs) * self.epoch_length
+        }
+    }
+
+    pub fn epoch_length_of(&self, epoch: u64) -> u64 {
+        if epoch < self.byron_epochs {
+            self.byron_epoch_length()
+        } else {
+            self.epoch_length
+        }
+    }
+
+    /// Blocks the whole network is expected to make in an epoch: one per
+    /// slot won, at the active slot coefficient.
+    pub fn expected_blocks(&self, epoch: u64) -> f64 {
+        self.epoch_length_of(epoch) as f64 * self.active_slots_coeff
+    }
+
+    pub fn current_epoch(&self, now: DateTime<Utc>) -> u64 {
+        self.time_to_slot(now).map_or(0, |slot| self.epoch_of_slot(slot).0)
+    }
+
+    /// Epoch from the node tip, or from the wall clock when the node is unreachable.
+    pub async fn tip_epoch(&self, cli: &CardanoCli) -> u64 {
+        match cli.query_tip().await.ok().and_then(|tip| tip["epoch"].as_u64()) {
+            Some(epoch) => epoch,
+            None => self.current_epoch(Utc::now()),
+        }
+    }
+
+    /// Time left until the epoch following the one in progress at `now`.
+    pub fn time_until_next_epoch(&self, now: DateTime<Utc>) -> Duration {
+        let next = self.epoch_boundaries(self.current_epoch(now)).end_time;
+        (next - now).max(Duration::zero())
+    }
+
+    /// 3k/f slots: blocks older than this are final.
+    pub fn stability_window(&self) -> u64 {
+        (3.0 * self.security_param as f64 / self.active_slots_coeff).ceil() as u64
+    }
+
+    /// 4k/f slots into an epoch: the reward update for the previous epoch is known.
+    pub fn reward_calculation_slot(&self) -> u64 {
+        (4.0 * self.security_param as f64 / self.active_slots_coeff).ceil() as u64
+    }
+
+    pub fn epoch_boundaries(&self, epoch: u64) -> EpochBoundaries {
+        let start_slot = self.epoch_start_slot(epoch);
+        let end_slot = start_slot + self.epoch_length_of(epoch);
+        let stability_window_slot = start_slot + self.stability_window();
+        let reward_calculation_slot = start_slot + self.reward_calculation_slot();
+
+        EpochBoundaries {
+            epoch,
+            start_slot,
+            end_slot,
+            start_time: self.slot_to_time(start_slot),
+            end_time: self.slot_to_time(end_slot),
+            stability_window_slot,
+            stability_window_time: self.slot_to_time(stability_window_slot),
+            reward_calculation_slot,
+            reward_calculation_time: self.slot_to_time(reward_calculation_slot),
+        }
+    }
+}
+
+fn read_genesis(path: &str) -> Result<Value> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read genesis file {}", path))?;
+    serde_json::from_str(&content).with_context(|| format!("Failed to parse genesis file {}", path))
+}
+
+fn seconds(secs: f64) -> Duration {
+    Duration::milliseconds((secs * 1000.0).round() as i64)
+}
+
+fn seconds_between(from: DateTime<Utc>, to: DateTime<Utc>) -> f64 {
+    (to - from).num_milliseconds() as f64 / 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(timestamp: i64) -> DateTime<Utc> {
+        Utc.timestamp_opt(timestamp, 0).unwrap()
+    }
+
+    #[test]
+    fn mainnet_shelley_starts_at_epoch_208() {
+        let mainnet = ChainTime::for_network("mainnet");
+
+        assert_eq!(mainnet.epoch_of_slot(4_492_799), (207, 21_599));
+        assert_eq!(mainnet.epoch_of_slot(4_492_800), (208, 0));
+        assert_eq!(mainnet.epoch_start_slot(208), 4_492_800);
+        assert_eq!(mainnet.epoch_start_slot(209), 4_924_800);
+        // 2020-07-29T21:44:51Z
+        assert_eq!(mainnet.slot_to_time(4_492_800), at(1_596_059_091));
+        assert_eq!(mainnet.slot_to_time(4_492_801), at(1_596_059_092));
+    }
+
+    #[test]
+    fn preprod_has_four_byron_epochs() {
+        let preprod = ChainTime::for_network("preprod");
+
+        assert_eq!(preprod.epoch_length_of(3), 21_600);
+        assert_eq!(preprod.epoch_length_of(4), 432_000);
+        assert_eq!(preprod.epoch_of_slot(86_399), (3, 21_599));
+        assert_eq!(preprod.epoch_of_slot(86_400), (4, 0));
+        // 2022-06-21T00:00:00Z
+        assert_eq!(preprod.slot_to_time(86_400), at(1_655_769_600));
+    }
+
+    #[test]
+    fn preview_epochs_are_one_day() {
+        let preview = ChainTime::for_network("preview");
+
+        assert_eq!(preview.epoch_of_slot(0), (0, 0));
+        assert_eq!(preview.epoch_of_slot(86_399), (0, 86_399));
+        assert_eq!(preview.epoch_of_slot(86_400), (1, 0));
+        assert_eq!(preview.epoch_start_slot(10), 864_000);
+        assert_eq!(preview.slot_to_time(86_400), at(1_666_656_000 + 86_400));
+        assert_eq!(preview.expected_blocks(10), 4_320.0);
+    }
+
+    #[test]
+    fn slots_round_trip_through_time() {
+        for network in ["mainnet", "preprod", "preview"] {
+            let chain_time = ChainTime::for_network(network);
+            let fork = chain_time.epoch_start_slot(chain_time.byron_epochs);
+            for slot in [0, 1, fork.saturating_sub(1), fork, fork + 1, fork + 1_000_000] {
+                assert_eq!(chain_time.time_to_slot(chain_time.slot_to_time(slot)), Some(slot), "{} slot {}", network, slot);
+            }
+        }
+
+        let mainnet = ChainTime::for_network("mainnet");
+        // Mid-way through a 20 second Byron slot
+        assert_eq!(mainnet.time_to_slot(at(1_506_203_091 + 30)), Some(1));
+        assert_eq!(mainnet.time_to_slot(at(1_506_203_090)), None);
+    }
+
+    #[test]
+    fn stability_window_and_reward_calculation_follow_k_and_f() {
+        let mainnet = ChainTime::for_network("mainnet");
+        assert_eq!(mainnet.stability_window(), 129_600);
+        assert_eq!(mainnet.reward_calculation_slot(), 172_800);
+
+        let boundaries = mainnet.epoch_boundaries(208);
+        assert_eq!(boundaries.end_slot, 4_924_800);
+        assert_eq!(boundaries.stability_window_slot, 4_492_800 + 129_600);
+        assert_eq!(boundaries.reward_calculation_time, at(1_596_059_091 + 172_800));
+    }
+}
@@ -1,12 +1,17 @@
 use anyhow::{Result, Context};
 use serde_json::Value;
 use std::process::Command;
+use std::sync::Arc;
+use crate::cardano::cache::{QueryCache, Ttl};
+use crate::cardano::pool_id::PoolId;
 use crate::utils::config::Config;
+use crate::utils::error::CliError;
 
 pub struct CardanoCli {
     cli_path: String,
     socket_path: String,
     network: String,
+    cache: Arc<QueryCache>,
 }
 
 impl CardanoCli {
@@ -15,168 +20,270 @@ impl CardanoCli {
             cli_path: config.cardano.cli_path.clone(),
             socket_path: config.cardano.node_socket_path.clone(),
             network: config.cardano.network.clone(),
+            cache: QueryCache::shared(config),
         }
     }
 
     pub async fn query_tip(&self) -> Result<Value> {
+        self.cache.get_or_fetch(&self.cache_key("tip"), Ttl::Short, || async move {
+            let output = Command::new(&self.cli_path)
+                .args([
+                    "query", "tip",
+                    "--socket-path", &self.socket_path,
+                    &format!("--{}", self.network),
+                ])
+                .output()
+                .map_err(|e| CliError::NodeUnreachable(format!("Failed to execute cardano-cli query tip: {}", e)))?;
+
+            if !output.status.success() {
+                return Err(query_failed("query tip", &output.stderr));
+            }
+
+            let result = String::from_utf8(output.stdout)
+                .context("Invalid UTF-8 in cardano-cli output")?;
+
+            serde_json::from_str(&result)
+                .context("Failed to parse JSON response from cardano-cli")
+        }).await
+    }
+
+    pub async fn query_stake_distribution(&self) -> Result<Value> {
+        self.cache.get_or_fetch(&self.cache_key("stake-distribution"), Ttl::Epoch, || async move {
+            let output = Command::new(&self.cli_path)
+                .args([
+                    "query", "stake-distribution",
+                    "--socket-path", &self.socket_path,
+                    &format!("--{}", self.network),
+                ])
+                .output()
+                .map_err(|e| CliError::NodeUnreachable(format!("Failed to execute cardano-cli query stake-distribution: {}", e)))?;
+
+            if !output.status.success() {
+                return Err(query_failed("query stake-distribution", &output.stderr));
+            }
+
+            let result = String::from_utf8(output.stdout)
+                .context("Invalid UTF-8 in cardano-cli output")?;
+
+            serde_json::from_str(&result)
+                .context("Failed to parse JSON response from cardano-cli")
+        }).await
+    }
+
+    pub async fn query_pool_params(&self, pool_id: &str) -> Result<Value> {
+        self.cache.get_or_fetch(&self.cache_key(&format!("pool-params/{}", pool_id)), Ttl::Epoch, || async move {
+            let output = Command::new(&self.cli_path)
+                .args([
+                    "query", "pool-params",
+                    "--stake-pool-id", pool_id,
+                    "--socket-path", &self.socket_path,
+                    &format!("--{}", self.network),
+                ])
+                .output()
+                .map_err(|e| CliError::NodeUnreachable(format!("Failed to execute cardano-cli query pool-params: {}", e)))?;
+
+            if !output.status.success() {
+                return Err(query_failed("query pool-params", &output.stderr));
+            }
+
+            let result = String::from_utf8(output.stdout)
+                .context("Invalid UTF-8 in cardano-cli output")?;
+
+            serde_json::from_str(&result)
+                .context("Failed to parse JSON response from cardano-cli")
+        }).await
+    }
+
+    pub async fn query_stake_address_info(&self, stake_address: &str) -> Result<Value> {
+        self.cache.get_or_fetch(&self.cache_key(&format!("stake-address-info:{}", stake_address)), Ttl::Short, || {
+            self.fetch_stake_address_info(stake_address)
+        }).await
+    }
+
+    /// `query_stake_address_info` straight from the node. Withdrawals must
+    /// match the current balance exactly, so they never use a cached answer.
+    pub async fn fetch_stake_address_info(&self, stake_address: &str) -> Result<Value> {
         let output = Command::new(&self.cli_path)
             .args([
-                "query", "tip",
+                "query", "stake-address-info",
+                "--address", stake_address,
                 "--socket-path", &self.socket_path,
                 &format!("--{}", self.network),
             ])
             .output()
-            .context("Failed to execute cardano-cli query tip")?;
+            .map_err(|e| CliError::NodeUnreachable(format!("Failed to execute cardano-cli query stake-address-info: {}", e)))?;
 
         if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            anyhow::bail!("cardano-cli query tip failed: {}", error);
+            return Err(query_failed("query stake-address-info", &output.stderr));
         }
 
-        let result = String::from_utf8(output.stdout)
-            .context("Invalid UTF-8 in cardano-cli output")?;
-
-        serde_json::from_str(&result)
+        serde_json::from_slice(&output.stdout)
             .context("Failed to parse JSON response from cardano-cli")
     }
 
-    pub async fn query_stake_distribution(&self) -> Result<Value> {
+    /// UTxOs at an address, keyed by `txhash#index`.
+    pub async fn query_utxo(&self, address: &str) -> Result<Value> {
+        self.cache.get_or_fetch(&self.cache_key(&format!("utxo:{}", address)), Ttl::Short, || async move {
+            let output = Command::new(&self.cli_path)
+                .args([
+                    "query", "utxo",
+                    "--address", address,
+                    "--out-file", "/dev/stdout",
+                    "--socket-path", &self.socket_path,
+                    &format!("--{}", self.network),
+                ])
+                .output()
+                .map_err(|e| CliError::NodeUnreachable(format!("Failed to execute cardano-cli query utxo: {}", e)))?;
+
+            if !output.status.success() {
+                return Err(query_failed("query utxo", &output.stderr));
+            }
+
+            serde_json::from_slice(&output.stdout)
+                .context("Failed to parse JSON response from cardano-cli")
+        }).await
+    }
+
+    pub fn is_mainnet(&self) -> bool {
+        self.network == "mainnet"
+    }
+
+    fn cache_key(&self, query: &str) -> String {
+        // Profiles on the same network may still talk to different nodes
+        format!("cardano-cli:{}:{}:{}", self.network, self.socket_path, query)
+    }
+
+    // Helper method to check if cardano-cli is available
+    pub async fn is_available(&self) -> bool {
+        let output = Command::new(&self.cli_path)
+            .args(["version"])
+            .output();
+
+        match output {
+            Ok(result) => result.status.success(),
+            Err(_) => false,
+        }
+    }
+
+    pub async fn query_stake_pools(&self) -> Result<Vec<String>> {
         let output = Command::new(&self.cli_path)
             .args([
-                "query", "stake-distribution",
+                "query", "stake-pools",
                 "--socket-path", &self.socket_path,
                 &format!("--{}", self.network),
             ])
             .output()
-            .context("Failed to execute cardano-cli query stake-distribution")?;
+            .map_err(|e| CliError::NodeUnreachable(format!("Failed to execute cardano-cli query stake-pools: {}", e)))?;
 
         if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            anyhow::bail!("cardano-cli query stake-distribution failed: {}", error);
+            return Err(query_failed("query stake-pools", &output.stderr));
         }
 
-        let result = String::from_utf8(output.stdout)
-            .context("Invalid UTF-8 in cardano-cli output")?;
-
-        serde_json::from_str(&result)
-            .context("Failed to parse JSON response from cardano-cli")
+        let result = String::from_utf8(output.stdout)?;
+        let pools: Vec<String> = serde_json::from_str(&result)?;
+        Ok(pools)
     }
 
-    pub async fn query_pool_params(&self, pool_id: &str) -> Result<Value> {
+    pub async fn query_kes_period_info(&self, opcert_file: &str) -> Result<Value> {
         let output = Command::new(&self.cli_path)
             .args([
-                "query", "pool-params",
-                "--stake-pool-id", pool_id,
+                "query", "kes-period-info",
+                "--op-cert-file", opcert_file,
                 "--socket-path", &self.socket_path,
                 &format!("--{}", self.network),
             ])
             .output()
-            .context("Failed to execute cardano-cli query pool-params")?;
+            .map_err(|e| CliError::NodeUnreachable(format!("Failed to execute cardano-cli query kes-period-info: {}", e)))?;
 
         if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            anyhow::bail!("cardano-cli query pool-params failed: {}", error);
+            return Err(query_failed("query kes-period-info", &output.stderr));
         }
 
         let result = String::from_utf8(output.stdout)
             .context("Invalid UTF-8 in cardano-cli output")?;
 
-        serde_json::from_str(&result)
-            .context("Failed to parse JSON response from cardano-cli")
-    }
+        // The JSON document is preceded by human-readable check results
+        let json_start = result.find('{')
+            .context("No JSON found in kes-period-info output")?;
 
-    pub async fn query_pool_info(&self, pool_id: &str) -> Result<Value> {
-        // First try to get pool params to see if pool exists and is active
-        match self.query_pool_params(pool_id).await {
-            Ok(params) => {
-                // If we get params, the pool is active
-                Ok(serde_json::json!({
-                    "active": true,
-                    "params": params
-                }))
-            }
-            Err(_) => {
-                // Pool might be retired or doesn't exist
-                Ok(serde_json::json!({
-                    "active": false,
-                    "params": null
-                }))
-            }
-        }
+        serde_json::from_str(&result[json_start..])
+            .context("Failed to parse kes-period-info output")
     }
 
-    pub async fn query_pool_blocks(&self, _pool_id: &str, _epoch: u64) -> Result<Value> {
-        //TODO This is a simplified approach. In reality, you'd need to:
-        // 1. Query the ledger state for the epoch
-        // 2. Parse block producer information
-        // For now, we'll return a placeholder that indicates "not implemented"
+    pub async fn key_gen_kes(&self, vkey_file: &str, skey_file: &str) -> Result<()> {
+        let output = Command::new(&self.cli_path)
+            .args([
+                "node", "key-gen-KES",
+                "--verification-key-file", vkey_file,
+                "--signing-key-file", skey_file,
+            ])
+            .output()
+            .context("Failed to execute cardano-cli node key-gen-KES")?;
 
-        // Try to get ledger state (this is expensive and might fail)
-        match self.query_ledger_state().await {
-            Ok(_ledger) => {
-                // TODO: Parse actual block information from ledger state
-                // This would require complex parsing of the ledger state JSON
-                Ok(serde_json::json!([]))  // Return empty array for now
-            }
-            Err(_) => {
-                // Return empty array if we can't query ledger state
-                Ok(serde_json::json!([]))
-            }
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("cardano-cli node key-gen-KES failed: {}", error);
         }
+
+        Ok(())
     }
 
-    pub async fn query_ledger_state(&self) -> Result<Value> {
+    /// Run `transaction build` with the given inputs and withdrawal, returning
+    /// the fee it reports.
+    pub async fn transaction_build(&self, args: &[String], out_file: &str) -> Result<String> {
         let output = Command::new(&self.cli_path)
+            .args(["latest", "transaction", "build"])
+            .args(args)
             .args([
-                "query", "ledger-state",
+                "--out-file", out_file,
                 "--socket-path", &self.socket_path,
                 &format!("--{}", self.network),
             ])
             .output()
-            .context("Failed to execute cardano-cli query ledger-state")?;
+            .map_err(|e| CliError::NodeUnreachable(format!("Failed to execute cardano-cli transaction build: {}", e)))?;
 
         if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            anyhow::bail!("cardano-cli query ledger-state failed: {}", error);
+            return Err(query_failed("transaction build", &output.stderr));
         }
 
-        let result = String::from_utf8(output.stdout)
-            .context("Invalid UTF-8 in cardano-cli output")?;
-
-        serde_json::from_str(&result)
-            .context("Failed to parse JSON response from cardano-cli")
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
     }
 
-    // Helper method to check if cardano-cli is available
-    pub async fn is_available(&self) -> bool {
+    pub async fn transaction_txid(&self, tx_file: &str) -> Result<String> {
         let output = Command::new(&self.cli_path)
-            .args(&["version"])
-            .output();
+            .args(["latest", "transaction", "txid", "--tx-file", tx_file])
+            .output()
+            .context("Failed to execute cardano-cli transaction txid")?;
 
-        match output {
-            Ok(result) => result.status.success(),
-            Err(_) => false,
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("cardano-cli transaction txid failed: {}", error);
+        }
+
+        // Newer versions print a JSON object, older ones the bare hash
+        let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        match serde_json::from_str::<Value>(&stdout) {
+            Ok(json) => json["txhash"].as_str().map(String::from).context("No txhash in cardano-cli output"),
+            Err(_) => Ok(stdout),
         }
     }
 
-    pub async fn query_stake_pools(&self) -> Result<Vec<String>> {
+    pub async fn transaction_submit(&self, tx_file: &str) -> Result<()> {
         let output = Command::new(&self.cli_path)
             .args([
-                "query", "stake-pools",
+                "latest", "transaction", "submit",
+                "--tx-file", tx_file,
                 "--socket-path", &self.socket_path,
                 &format!("--{}", self.network),
             ])
             .output()
-            .context("Failed to execute cardano-cli query stake-pools")?;
+            .map_err(|e| CliError::NodeUnreachable(format!("Failed to execute cardano-cli transaction submit: {}", e)))?;
 
         if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            anyhow::bail!("cardano-cli query stake-pools failed: {}", error);
+            return Err(query_failed("transaction submit", &output.stderr));
         }
 
-        let result = String::from_utf8(output.stdout)?;
-        let pools: Vec<String> = serde_json::from_str(&result)?;
-        Ok(pools)
+        Ok(())
     }
 
     pub async fn query_leadership_schedule(&self, pool_id: &str, vrf_key_file: &str) -> Result<Value> {
@@ -189,14 +296,78 @@ impl CardanoCli {
                 &format!("--{}", self.network),
             ])
             .output()
-            .context("Failed to execute cardano-cli query leadership-schedule")?;
+            .map_err(|e| CliError::NodeUnreachable(format!("Failed to execute cardano-cli query leadership-schedule: {}", e)))?;
 
         if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            anyhow::bail!("cardano-cli query leadership-schedule failed: {}", error);
+            return Err(query_failed("query leadership-schedule", &output.stderr));
         }
 
         let result = String::from_utf8(output.stdout)?;
         serde_json::from_str(&result).context("Failed to parse leadership schedule")
     }
-}
\ No newline at end of file
+}
+
+/// Node connection problems and missing objects get their own categories;
+/// anything else stays generic.
+fn query_failed(query: &str, stderr: &[u8]) -> anyhow::Error {
+    let error = String::from_utf8_lossy(stderr);
+    let message = format!("cardano-cli {} failed: {}", query, error);
+    let lower = error.to_lowercase();
+
+    // e.g. "Network.Socket.connect: <socket: 11>: does not exist (No such file or directory)"
+    if lower.contains("socket") || lower.contains("connect") {
+        CliError::NodeUnreachable(message).into()
+    } else if lower.contains("not found") || lower.contains("does not exist") {
+        CliError::NotFound(message).into()
+    } else {
+        anyhow::anyhow!(message)
+    }
+}
+
+/// Locate the current pool parameters in `query pool-params` output, which
+/// newer cardano-cli versions key by pool ID.
+pub fn extract_pool_params<'a>(params: &'a Value, pool_id: &str) -> Option<&'a Value> {
+    let keyed = pool_id.parse::<PoolId>().ok().and_then(|id| id.lookup(params));
+    let pool_params = if params.get("poolParams").is_some() {
+        &params["poolParams"]
+    } else if let Some(entry) = keyed {
+        &entry["poolParams"]
+    } else {
+        &params.as_object()?.values().next()?["poolParams"]
+    };
+
+    if pool_params.is_null() {
+        None
+    } else {
+        Some(pool_params)
+    }
+}
+
+/// Total lovelace across `query utxo` output.
+pub fn utxo_lovelace(utxos: &Value) -> u64 {
+    utxos.as_object().into_iter().flatten()
+        .filter_map(|(_, utxo)| utxo["value"]["lovelace"].as_u64())
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::error::classify;
+
+    fn category(stderr: &str) -> Option<CliError> {
+        classify(&query_failed("query tip", stderr.as_bytes())).cloned()
+    }
+
+    #[test]
+    fn only_socket_errors_mean_the_node_is_unreachable() {
+        let missing_socket = "Network.Socket.connect: <socket: 11>: does not exist (No such file or directory)";
+        assert!(matches!(category(missing_socket), Some(CliError::NodeUnreachable(_))));
+        let refused = "Network.Socket.connect: <socket: 11>: connection refused";
+        assert!(matches!(category(refused), Some(CliError::NodeUnreachable(_))));
+
+        assert!(matches!(category("Stake pool pool1abc does not exist"), Some(CliError::NotFound(_))));
+        assert!(matches!(category("Address not found in the UTxO"), Some(CliError::NotFound(_))));
+        assert!(category("Failed reading: Invalid stake pool id").is_none());
+    }
+}
@@ -0,0 +1,223 @@
+use anyhow::{Result, Context};
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use crate::cardano::cbor::{self, Decoder};
+use crate::utils::config::CardanoConfig;
+
+#[derive(Debug, Clone, Copy)]
+pub struct KesParams {
+    pub slots_per_kes_period: u64,
+    pub max_kes_evolutions: u64,
+    pub slot_length_secs: f64,
+}
+
+impl KesParams {
+    pub fn load(config: &CardanoConfig) -> Result<Self> {
+        match &config.shelley_genesis_file {
+            Some(path) => Self::from_genesis_file(path),
+            None => Ok(Self::for_network(&config.network)),
+        }
+    }
+
+    pub fn from_genesis_file(path: &str) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read Shelley genesis file {}", path))?;
+        let genesis: serde_json::Value = serde_json::from_str(&content)
+            .context("Failed to parse Shelley genesis file")?;
+
+        Ok(Self {
+            slots_per_kes_period: genesis["slotsPerKESPeriod"]
+                .as_u64()
+                .context("slotsPerKESPeriod missing from genesis")?,
+            max_kes_evolutions: genesis["maxKESEvolutions"]
+                .as_u64()
+                .context("maxKESEvolutions missing from genesis")?,
+            slot_length_secs: genesis["slotLength"].as_f64().unwrap_or(1.0),
+        })
+    }
+
+    pub fn for_network(_network: &str) -> Self {
+        // Mainnet, preprod and preview all share the same KES parameters
+        Self {
+            slots_per_kes_period: 129_600,
+            max_kes_evolutions: 62,
+            slot_length_secs: 1.0,
+        }
+    }
+
+    pub fn period_for_slot(&self, slot: u64) -> u64 {
+        slot / self.slots_per_kes_period
+    }
+
+    pub fn period_duration_days(&self) -> f64 {
+        self.slots_per_kes_period as f64 * self.slot_length_secs / 86_400.0
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OperationalCertificate {
+    pub hot_vkey: Vec<u8>,
+    pub counter: u64,
+    pub kes_period: u64,
+    pub cold_vkey: Vec<u8>,
+}
+
+impl OperationalCertificate {
+    pub fn from_file(path: &str) -> Result<Self> {
+        let (envelope_type, bytes) = cbor::read_text_envelope(path)?;
+        if envelope_type != "NodeOperationalCertificate" {
+            anyhow::bail!("{} is a '{}', not an operational certificate", path, envelope_type);
+        }
+        Self::from_cbor(&bytes)
+    }
+
+    // Layout: [[hot_vkey, counter, kes_period, sigma], cold_vkey]
+    pub fn from_cbor(bytes: &[u8]) -> Result<Self> {
+        let mut decoder = Decoder::new(bytes);
+
+        if decoder.array()? != 2 {
+            anyhow::bail!("Unexpected operational certificate structure");
+        }
+        if decoder.array()? != 4 {
+            anyhow::bail!("Unexpected operational certificate body");
+        }
+
+        let hot_vkey = decoder.bytes()?;
+        let counter = decoder.unsigned()?;
+        let kes_period = decoder.unsigned()?;
+        let _sigma = decoder.bytes()?;
+        let cold_vkey = decoder.bytes()?;
+
+        Ok(Self { hot_vkey, counter, kes_period, cold_vkey })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KesStatus {
+    pub current_period: u64,
+    pub start_period: u64,
+    pub end_period: u64,
+    pub remaining_periods: u64,
+    pub remaining_days: f64,
+    pub expiry: DateTime<Utc>,
+    pub local_counter: u64,
+    pub on_chain_counter: Option<u64>,
+    pub counter_valid: bool,
+}
+
+impl KesStatus {
+    pub fn evaluate(
+        params: &KesParams,
+        opcert: &OperationalCertificate,
+        tip_slot: u64,
+        on_chain_counter: Option<u64>,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let current_period = params.period_for_slot(tip_slot);
+        let end_period = opcert.kes_period + params.max_kes_evolutions;
+        let remaining_periods = end_period.saturating_sub(current_period);
+
+        let expiry_slot = end_period * params.slots_per_kes_period;
+        let remaining_secs = expiry_slot.saturating_sub(tip_slot) as f64 * params.slot_length_secs;
+        let expiry = now + Duration::seconds(remaining_secs as i64);
+
+        // The node accepts the certificate it has already seen or the next one
+        let counter_valid = match on_chain_counter {
+            Some(chain) => opcert.counter == chain || opcert.counter == chain + 1,
+            None => true,
+        };
+
+        Self {
+            current_period,
+            start_period: opcert.kes_period,
+            end_period,
+            remaining_periods,
+            remaining_days: remaining_secs / 86_400.0,
+            expiry,
+            local_counter: opcert.counter,
+            on_chain_counter,
+            counter_valid,
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.remaining_periods == 0
+    }
+}
+
+/// Read the raw key bytes from a KES verification key envelope.
+pub fn read_kes_vkey(path: &str) -> Result<Vec<u8>> {
+    let (envelope_type, bytes) = cbor::read_text_envelope(path)?;
+    if !envelope_type.starts_with("KesVerificationKey") {
+        anyhow::bail!("{} is a '{}', not a KES verification key", path, envelope_type);
+    }
+    Decoder::new(&bytes).bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOT_VKEY: &str = "8f4b2e33c2a6bd0bb63e0aa9b8e7c0bf4b4ab3dcf2a5ad6e1e3c6fd90c5c9b71";
+    const COLD_VKEY: &str = "3a7d9f5e2c1b0a8d6f4e3c2b1a0f9e8d7c6b5a4f3e2d1c0b9a8f7e6d5c4b3a29";
+
+    /// cborHex of an opcert with counter 5 issued at KES period 838.
+    fn opcert_cbor() -> String {
+        format!("82845820{}051903465840{}5820{}", HOT_VKEY, "ab".repeat(64), COLD_VKEY)
+    }
+
+    fn opcert(counter: u64) -> OperationalCertificate {
+        OperationalCertificate { counter, ..OperationalCertificate::from_cbor(&hex::decode(opcert_cbor()).unwrap()).unwrap() }
+    }
+
+    fn evaluate(tip_slot: u64, local: u64, chain: Option<u64>) -> KesStatus {
+        KesStatus::evaluate(&KesParams::for_network("mainnet"), &opcert(local), tip_slot, chain, Utc::now())
+    }
+
+    #[test]
+    fn opcert_fields_are_decoded() {
+        let cert = OperationalCertificate::from_cbor(&hex::decode(opcert_cbor()).unwrap()).unwrap();
+
+        assert_eq!(hex::encode(&cert.hot_vkey), HOT_VKEY);
+        assert_eq!(cert.counter, 5);
+        assert_eq!(cert.kes_period, 838);
+        assert_eq!(hex::encode(&cert.cold_vkey), COLD_VKEY);
+
+        // A bare body, without the cold key wrapper
+        let body = hex::decode(&opcert_cbor()[2..]).unwrap();
+        assert!(OperationalCertificate::from_cbor(&body).is_err());
+    }
+
+    #[test]
+    fn counter_may_match_or_be_one_ahead_of_the_chain() {
+        let tip = 850 * 129_600;
+        assert!(evaluate(tip, 5, Some(5)).counter_valid);
+        assert!(evaluate(tip, 6, Some(5)).counter_valid);
+        assert!(!evaluate(tip, 7, Some(5)).counter_valid);
+        assert!(!evaluate(tip, 4, Some(5)).counter_valid);
+        // A pool that has never forged has no on-chain counter yet
+        assert!(evaluate(tip, 0, None).counter_valid);
+    }
+
+    #[test]
+    fn key_expires_after_max_evolutions() {
+        // Issued at period 838, so valid until the start of period 900
+        let expiry_slot = 900 * 129_600;
+
+        let last_slot = evaluate(expiry_slot - 1, 5, Some(5));
+        assert_eq!(last_slot.current_period, 899);
+        assert_eq!(last_slot.end_period, 900);
+        assert_eq!(last_slot.remaining_periods, 1);
+        assert!(!last_slot.is_expired());
+        assert_eq!(last_slot.remaining_days, 1.0 / 86_400.0);
+
+        let expired = evaluate(expiry_slot, 5, Some(5));
+        assert_eq!(expired.remaining_periods, 0);
+        assert!(expired.is_expired());
+        assert_eq!(expired.remaining_days, 0.0);
+
+        let last_period = evaluate(899 * 129_600, 5, Some(5));
+        assert_eq!(last_period.remaining_days, 1.5);
+        assert!(evaluate(expiry_slot + 129_600, 5, Some(5)).is_expired());
+    }
+}
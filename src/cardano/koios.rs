@@ -0,0 +1,179 @@
+use anyhow::{Result, Context};
+use reqwest::{Client, RequestBuilder};
+use serde_json::Value;
+use crate::utils::config::Config;
+use crate::utils::error::CliError;
+use crate::utils::secret::Secret;
+
+// Koios caps every response at 1000 rows
+const PAGE_SIZE: usize = 1000;
+
+pub struct KoiosClient {
+    client: Client,
+    base_url: String,
+    api_token: Option<Secret>,
+}
+
+/// PostgREST query parameters: RPC arguments plus horizontal filters.
+#[derive(Debug, Clone, Default)]
+pub struct KoiosQuery {
+    params: Vec<(String, String)>,
+}
+
+impl KoiosQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// An endpoint argument such as `_pool_bech32`.
+    pub fn arg(mut self, name: &str, value: impl ToString) -> Self {
+        self.params.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// A horizontal filter, e.g. `filter("pool_status", "eq", "registered")`.
+    pub fn filter(mut self, column: &str, operator: &str, value: impl ToString) -> Self {
+        self.params.push((column.to_string(), format!("{}.{}", operator, value.to_string())));
+        self
+    }
+
+    pub fn select(mut self, columns: &[&str]) -> Self {
+        self.params.push(("select".to_string(), columns.join(",")));
+        self
+    }
+
+    pub fn order(mut self, column: &str, descending: bool) -> Self {
+        let direction = if descending { "desc" } else { "asc" };
+        self.params.push(("order".to_string(), format!("{}.{}", column, direction)));
+        self
+    }
+}
+
+impl KoiosClient {
+    pub fn new(config: &Config) -> Option<Self> {
+        config.koios.as_ref().map(|koios_config| Self {
+            client: Client::new(),
+            base_url: koios_config.base_url.trim_end_matches('/').to_string(),
+            api_token: koios_config.api_token.clone(),
+        })
+    }
+
+    pub async fn get_tip(&self) -> Result<Value> {
+        self.get("tip", &KoiosQuery::new()).await
+    }
+
+    pub async fn get_epoch_info(&self, epoch: Option<u64>) -> Result<Value> {
+        let mut query = KoiosQuery::new();
+        if let Some(epoch) = epoch {
+            query = query.arg("_epoch_no", epoch);
+        }
+        self.get("epoch_info", &query).await
+    }
+
+    /// Every pool matching the filters, across all pages.
+    pub async fn get_pool_list(&self, query: &KoiosQuery) -> Result<Value> {
+        self.get_all("pool_list", query).await
+    }
+
+    pub async fn get_pool_info(&self, pool_ids: &[&str]) -> Result<Value> {
+        self.post("pool_info", serde_json::json!({ "_pool_bech32_ids": pool_ids })).await
+    }
+
+    pub async fn get_pool_metadata(&self, pool_ids: &[&str]) -> Result<Value> {
+        self.post("pool_metadata", serde_json::json!({ "_pool_bech32_ids": pool_ids })).await
+    }
+
+    pub async fn get_pool_history(&self, pool_id: &str, epoch: Option<u64>) -> Result<Value> {
+        let query = KoiosQuery::new().arg("_pool_bech32", pool_id);
+        let query = match epoch {
+            Some(epoch) => query.arg("_epoch_no", epoch),
+            None => query.order("epoch_no", true),
+        };
+        self.get("pool_history", &query).await
+    }
+
+    pub async fn get_pool_delegators(&self, pool_id: &str) -> Result<Value> {
+        self.get_all("pool_delegators", &KoiosQuery::new().arg("_pool_bech32", pool_id)).await
+    }
+
+    pub async fn get_pool_blocks(&self, pool_id: &str, epoch: Option<u64>) -> Result<Value> {
+        let mut query = KoiosQuery::new().arg("_pool_bech32", pool_id);
+        if let Some(epoch) = epoch {
+            query = query.arg("_epoch_no", epoch);
+        }
+        self.get_all("pool_blocks", &query.select(&["epoch_no", "abs_slot", "block_height", "block_hash"])).await
+    }
+
+    pub async fn get_account_info(&self, stake_addresses: &[&str]) -> Result<Value> {
+        self.post("account_info", serde_json::json!({ "_stake_addresses": stake_addresses })).await
+    }
+
+    pub async fn get_account_rewards(&self, stake_addresses: &[&str], epoch: Option<u64>) -> Result<Value> {
+        let mut body = serde_json::json!({ "_stake_addresses": stake_addresses });
+        if let Some(epoch) = epoch {
+            body["_epoch_no"] = epoch.into();
+        }
+        self.post("account_rewards", body).await
+    }
+
+    async fn get(&self, endpoint: &str, query: &KoiosQuery) -> Result<Value> {
+        let request = self.client
+            .get(format!("{}/{}", self.base_url, endpoint))
+            .query(&query.params);
+        self.send(request).await
+    }
+
+    /// Follow Range-header pagination until a short page comes back.
+    async fn get_all(&self, endpoint: &str, query: &KoiosQuery) -> Result<Value> {
+        let mut rows = Vec::new();
+
+        loop {
+            let start = rows.len();
+            let request = self.client
+                .get(format!("{}/{}", self.base_url, endpoint))
+                .query(&query.params)
+                .header("Range", format!("{}-{}", start, start + PAGE_SIZE - 1));
+
+            let page = match self.send(request).await? {
+                Value::Array(page) => page,
+                other => anyhow::bail!("Expected a list from Koios {}, got {}", endpoint, other),
+            };
+
+            let count = page.len();
+            rows.extend(page);
+            if count < PAGE_SIZE {
+                break;
+            }
+        }
+
+        Ok(Value::Array(rows))
+    }
+
+    async fn post(&self, endpoint: &str, body: Value) -> Result<Value> {
+        let request = self.client
+            .post(format!("{}/{}", self.base_url, endpoint))
+            .json(&body);
+        self.send(request).await
+    }
+
+    async fn send(&self, request: RequestBuilder) -> Result<Value> {
+        let request = match &self.api_token {
+            Some(token) => request.bearer_auth(token.expose()),
+            None => request,
+        };
+
+        let response = request
+            .header("Accept", "application/json")
+            .send()
+            .await
+            .map_err(|e| CliError::Network(format!("Failed to send request to Koios API: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(CliError::from_status("Koios", response.status()).into());
+        }
+
+        response.json()
+            .await
+            .context("Failed to parse JSON response from Koios")
+    }
+}
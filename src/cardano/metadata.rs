@@ -0,0 +1,220 @@
+use anyhow::{Result, Context};
+use blake2::digest::consts::U32;
+use blake2::{Blake2b, Digest};
+use serde::Serialize;
+use serde_json::Value;
+use crate::cardano::blockfrost::BlockfrostClient;
+use crate::cardano::cli::{extract_pool_params, CardanoCli};
+use crate::cardano::koios::KoiosClient;
+
+type Blake2b256 = Blake2b<U32>;
+
+// CIP-6 limits
+pub const MAX_METADATA_BYTES: usize = 512;
+pub const MAX_URL_BYTES: usize = 64;
+pub const MAX_NAME_CHARS: usize = 50;
+pub const MAX_DESCRIPTION_BYTES: usize = 255;
+pub const MAX_HOMEPAGE_BYTES: usize = 64;
+
+/// Metadata URL and hash as registered in the pool certificate.
+#[derive(Debug, Clone, Serialize)]
+pub struct MetadataAnchor {
+    pub url: String,
+    pub hash: String,
+}
+
+pub fn blake2b_256_hex(data: &[u8]) -> String {
+    hex::encode(Blake2b256::digest(data))
+}
+
+pub async fn fetch_anchor(
+    cli: &CardanoCli,
+    blockfrost: Option<&BlockfrostClient>,
+    koios: Option<&KoiosClient>,
+    pool_id: &str,
+) -> Result<MetadataAnchor> {
+    if let Ok(params) = cli.query_pool_params(pool_id).await {
+        if let Some(anchor) = anchor_from_pool_params(&params, pool_id) {
+            return Ok(anchor);
+        }
+    }
+
+    if let Some(blockfrost) = blockfrost {
+        if let Ok(metadata) = blockfrost.get_pool_metadata(pool_id).await {
+            return Ok(MetadataAnchor {
+                url: metadata["url"].as_str().context("Pool has no metadata URL registered")?.to_string(),
+                hash: metadata["hash"].as_str().context("Pool has no metadata hash registered")?.to_string(),
+            });
+        }
+    }
+
+    let koios = koios
+        .context("Pool metadata unavailable from cardano-cli and Blockfrost, and no Koios configuration found")?;
+    let metadata = koios.get_pool_metadata(&[pool_id]).await?;
+    let metadata = metadata.get(0).context("Koios has no metadata for this pool")?;
+
+    Ok(MetadataAnchor {
+        url: metadata["meta_url"].as_str().context("Pool has no metadata URL registered")?.to_string(),
+        hash: metadata["meta_hash"].as_str().context("Pool has no metadata hash registered")?.to_string(),
+    })
+}
+
+fn anchor_from_pool_params(params: &Value, pool_id: &str) -> Option<MetadataAnchor> {
+    let metadata = extract_pool_params(params, pool_id)?.get("metadata")?;
+    Some(MetadataAnchor {
+        url: metadata["url"].as_str()?.to_string(),
+        hash: metadata["hash"].as_str()?.to_string(),
+    })
+}
+
+pub async fn download(location: &str) -> Result<Vec<u8>> {
+    if location.starts_with("http://") || location.starts_with("https://") {
+        let response = reqwest::get(location)
+            .await
+            .with_context(|| format!("Failed to download metadata from {}", location))?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("Metadata server returned status: {}", response.status());
+        }
+
+        Ok(response.bytes().await?.to_vec())
+    } else {
+        std::fs::read(location).with_context(|| format!("Failed to read metadata file {}", location))
+    }
+}
+
+/// Check a metadata document against the CIP-6 schema and size limits.
+pub fn validate_cip6(raw: &[u8]) -> Vec<String> {
+    let mut issues = Vec::new();
+
+    if raw.len() > MAX_METADATA_BYTES {
+        issues.push(format!("Metadata is {} bytes, maximum is {}", raw.len(), MAX_METADATA_BYTES));
+    }
+
+    let json: Value = match serde_json::from_slice(raw) {
+        Ok(json) => json,
+        Err(e) => {
+            issues.push(format!("Metadata is not valid JSON: {}", e));
+            return issues;
+        }
+    };
+
+    let object = match json.as_object() {
+        Some(object) => object,
+        None => {
+            issues.push("Metadata must be a JSON object".to_string());
+            return issues;
+        }
+    };
+
+    for field in ["name", "description", "ticker", "homepage"] {
+        match object.get(field) {
+            Some(Value::String(_)) => {}
+            Some(_) => issues.push(format!("Field '{}' must be a string", field)),
+            None => issues.push(format!("Required field '{}' is missing", field)),
+        }
+    }
+
+    for field in object.keys() {
+        if !["name", "description", "ticker", "homepage", "extended"].contains(&field.as_str()) {
+            issues.push(format!("Unknown field '{}'", field));
+        }
+    }
+
+    if let Some(name) = json["name"].as_str() {
+        if name.chars().count() > MAX_NAME_CHARS {
+            issues.push(format!("Name is {} characters, maximum is {}", name.chars().count(), MAX_NAME_CHARS));
+        }
+    }
+
+    if let Some(description) = json["description"].as_str() {
+        if description.len() > MAX_DESCRIPTION_BYTES {
+            issues.push(format!("Description is {} bytes, maximum is {}", description.len(), MAX_DESCRIPTION_BYTES));
+        }
+    }
+
+    if let Some(ticker) = json["ticker"].as_str() {
+        if !(3..=5).contains(&ticker.len()) {
+            issues.push(format!("Ticker '{}' must be 3-5 characters", ticker));
+        }
+        if !ticker.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit()) {
+            issues.push(format!("Ticker '{}' must only contain A-Z and 0-9", ticker));
+        }
+    }
+
+    for field in ["homepage", "extended"] {
+        if let Some(url) = json[field].as_str() {
+            if url.len() > MAX_HOMEPAGE_BYTES {
+                issues.push(format!("Field '{}' is {} bytes, maximum is {}", field, url.len(), MAX_HOMEPAGE_BYTES));
+            }
+        }
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const DOCUMENT: &str = r#"{"name":"Sample Stake Pool","description":"A pool used in tests.","ticker":"SMPL","homepage":"https://example.com"}"#;
+
+    fn issues(document: Value) -> Vec<String> {
+        validate_cip6(document.to_string().as_bytes())
+    }
+
+    fn pool(name: &str, description: &str, ticker: &str) -> Value {
+        json!({ "name": name, "description": description, "ticker": ticker, "homepage": "https://example.com" })
+    }
+
+    #[test]
+    fn hash_is_blake2b_256_of_the_raw_bytes() {
+        assert_eq!(blake2b_256_hex(b""), "0e5751c026e543b2e8ab2eb06099daa1d1e5df47778f7787faab45cdf12fe3a8");
+        assert_eq!(blake2b_256_hex(b"abc"), "bddd813c634239723171ef3fee98579b94964e3bb1cb3e427262c8c068d52319");
+        assert_eq!(blake2b_256_hex(DOCUMENT.as_bytes()), "c3db6f1df503670d089cc8f0d339c86c9ac4c8a252d201de7b9bdafac888b744");
+        assert!(validate_cip6(DOCUMENT.as_bytes()).is_empty());
+    }
+
+    #[test]
+    fn size_limits_are_enforced() {
+        assert!(issues(pool(&"é".repeat(50), &"d".repeat(255), "SMPL")).is_empty());
+
+        let long_name = issues(pool(&"n".repeat(51), "", "SMPL"));
+        assert_eq!(long_name, vec!["Name is 51 characters, maximum is 50"]);
+        // 128 characters but 256 bytes
+        let long_description = issues(pool("Pool", &"é".repeat(128), "SMPL"));
+        assert_eq!(long_description, vec!["Description is 256 bytes, maximum is 255"]);
+
+        let padded = format!("{}{}", DOCUMENT, " ".repeat(MAX_METADATA_BYTES + 1 - DOCUMENT.len()));
+        assert_eq!(validate_cip6(padded.as_bytes()), vec!["Metadata is 513 bytes, maximum is 512"]);
+        assert!(validate_cip6(&padded.as_bytes()[..MAX_METADATA_BYTES]).is_empty());
+    }
+
+    #[test]
+    fn ticker_length_and_charset() {
+        for ticker in ["ABC", "ABCDE", "P00L"] {
+            assert!(issues(pool("Pool", "", ticker)).is_empty(), "{}", ticker);
+        }
+        assert_eq!(issues(pool("Pool", "", "AB")), vec!["Ticker 'AB' must be 3-5 characters"]);
+        assert_eq!(issues(pool("Pool", "", "ABCDEF")), vec!["Ticker 'ABCDEF' must be 3-5 characters"]);
+        assert_eq!(issues(pool("Pool", "", "abc")), vec!["Ticker 'abc' must only contain A-Z and 0-9"]);
+        assert_eq!(issues(pool("Pool", "", "AB-C")), vec!["Ticker 'AB-C' must only contain A-Z and 0-9"]);
+    }
+
+    #[test]
+    fn unknown_and_missing_fields_are_reported() {
+        let mut document = pool("Pool", "", "SMPL");
+        document["extended"] = json!("https://example.com/x.json");
+        assert!(issues(document.clone()).is_empty());
+
+        document["twitter"] = json!("@pool");
+        document.as_object_mut().unwrap().remove("homepage");
+        let found = issues(document);
+        assert!(found.contains(&"Required field 'homepage' is missing".to_string()), "{:?}", found);
+        assert!(found.contains(&"Unknown field 'twitter'".to_string()), "{:?}", found);
+        assert_eq!(found.len(), 2);
+
+        assert_eq!(validate_cip6(b"[]"), vec!["Metadata must be a JSON object"]);
+    }
+}
@@ -1,3 +1,15 @@
+pub mod chain_time;
 pub mod cli;
 pub mod node;
-pub mod blockfrost;
\ No newline at end of file
+pub mod address;
+pub mod bech32;
+pub mod blockfrost;
+pub mod cache;
+pub mod cbor;
+pub mod kes;
+pub mod koios;
+pub mod metadata;
+pub mod ogmios;
+pub mod pool_id;
+pub mod provider;
+pub mod relay;
\ No newline at end of file
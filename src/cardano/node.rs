@@ -1,20 +1,199 @@
-use anyhow::Result;
+use anyhow::{Result, Context};
+use serde::Serialize;
 use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+use crate::utils::config::Config;
+
+const DEFAULT_PROMETHEUS_URL: &str = "http://127.0.0.1:12798/metrics";
+const METRIC_PREFIX: &str = "cardano_node_metrics_";
 
 pub struct CardanoNode {
     socket_path: String,
+    prometheus_url: String,
+    ekg_url: Option<String>,
+}
+
+/// Selected metrics exposed by cardano-node's Prometheus or EKG endpoint.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct NodeMetrics {
+    pub connected_peers: Option<u64>,
+    pub hot_peers: Option<u64>,
+    pub warm_peers: Option<u64>,
+    pub cold_peers: Option<u64>,
+    pub mempool_txs: Option<u64>,
+    pub mempool_bytes: Option<u64>,
+    pub current_kes_period: Option<u64>,
+    pub remaining_kes_periods: Option<u64>,
+    pub blocks_forged: Option<u64>,
+    pub blocks_adopted: Option<u64>,
+    pub blocks_not_adopted: Option<u64>,
+    pub leader_slots: Option<u64>,
+    pub missed_slots: Option<u64>,
+    pub density: Option<f64>,
+    pub block_number: Option<u64>,
+    pub slot_number: Option<u64>,
+    pub epoch: Option<u64>,
+    pub slot_in_epoch: Option<u64>,
+    pub uptime_seconds: Option<u64>,
+}
+
+impl NodeMetrics {
+    fn from_samples(samples: &HashMap<String, f64>) -> Self {
+        let get = |names: &[&str]| names.iter().find_map(|name| samples.get(*name).copied());
+        let int = |names: &[&str]| get(names).map(|v| v as u64);
+
+        let uptime_seconds = get(&["nodeStartTime"]).and_then(|start| {
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .ok()?
+                .as_secs_f64();
+            Some((now - start).max(0.0) as u64)
+        });
+
+        Self {
+            connected_peers: int(&["connectedPeers", "peersFromNodeKernel"]),
+            hot_peers: int(&["peerSelection_hot", "peerSelection_ActivePeers"]),
+            warm_peers: int(&["peerSelection_warm", "peerSelection_EstablishedPeers"]),
+            cold_peers: int(&["peerSelection_cold", "peerSelection_KnownPeers"]),
+            mempool_txs: int(&["txsInMempool"]),
+            mempool_bytes: int(&["mempoolBytes"]),
+            current_kes_period: int(&["currentKESPeriod"]),
+            remaining_kes_periods: int(&["remainingKESPeriods"]),
+            blocks_forged: int(&["Forge_forged"]),
+            blocks_adopted: int(&["Forge_adopted"]),
+            blocks_not_adopted: int(&["Forge_didnt_adopt"]),
+            leader_slots: int(&["Forge_node_is_leader"]),
+            missed_slots: int(&["slotsMissedNum", "slotsMissed"]),
+            density: get(&["density"]),
+            block_number: int(&["blockNum"]),
+            slot_number: int(&["slotNum"]),
+            epoch: int(&["epoch"]),
+            slot_in_epoch: int(&["slotInEpoch"]),
+            uptime_seconds,
+        }
+    }
 }
 
 impl CardanoNode {
-    pub fn new(socket_path: String) -> Self {
-        Self { socket_path }
+    pub fn new(config: &Config) -> Self {
+        Self {
+            socket_path: config.cardano.node_socket_path.clone(),
+            prometheus_url: config.cardano.node_prometheus_url
+                .clone()
+                .unwrap_or_else(|| DEFAULT_PROMETHEUS_URL.to_string()),
+            ekg_url: config.cardano.node_ekg_url.clone(),
+        }
     }
 
     pub async fn get_node_info(&self) -> Result<Value> {
-        // TODO: Implement node info retrieval
+        let socket_exists = Path::new(&self.socket_path).exists();
+        let metrics = self.get_metrics().await;
+
+        if !socket_exists && metrics.is_err() {
+            anyhow::bail!("Node socket {} not found and metrics endpoint unreachable", self.socket_path);
+        }
+
         Ok(serde_json::json!({
-            "status": "placeholder",
-            "socket_path": self.socket_path
+            "status": if metrics.is_ok() { "running" } else { "socket only" },
+            "socket_path": self.socket_path,
+            "socket_exists": socket_exists,
+            "metrics": metrics.ok(),
         }))
     }
-}
\ No newline at end of file
+
+    /// Scrape node metrics, preferring Prometheus and falling back to EKG.
+    pub async fn get_metrics(&self) -> Result<NodeMetrics> {
+        match self.scrape_prometheus().await {
+            Ok(metrics) => Ok(metrics),
+            Err(prometheus_error) => match &self.ekg_url {
+                Some(url) => self.scrape_ekg(url).await,
+                None => Err(prometheus_error),
+            },
+        }
+    }
+
+    pub async fn scrape_prometheus(&self) -> Result<NodeMetrics> {
+        let response = reqwest::get(&self.prometheus_url)
+            .await
+            .with_context(|| format!("Failed to reach node metrics at {}", self.prometheus_url))?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("Node metrics endpoint returned status: {}", response.status());
+        }
+
+        let body = response.text().await?;
+        Ok(NodeMetrics::from_samples(&parse_prometheus(&body)))
+    }
+
+    pub async fn scrape_ekg(&self, url: &str) -> Result<NodeMetrics> {
+        let response = reqwest::Client::new()
+            .get(url)
+            .header("Accept", "application/json")
+            .send()
+            .await
+            .with_context(|| format!("Failed to reach node EKG at {}", url))?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("Node EKG endpoint returned status: {}", response.status());
+        }
+
+        let json: Value = response.json()
+            .await
+            .context("Failed to parse JSON response from node EKG")?;
+
+        let mut samples = HashMap::new();
+        flatten_ekg(&json, String::new(), &mut samples);
+        Ok(NodeMetrics::from_samples(&samples))
+    }
+}
+
+/// Parse Prometheus text exposition into metric name → value, with the
+/// `cardano_node_metrics_` prefix and type suffixes stripped.
+fn parse_prometheus(body: &str) -> HashMap<String, f64> {
+    body.lines()
+        .filter(|line| !line.starts_with('#') && !line.trim().is_empty())
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let name = parts.next()?;
+            let value = parts.next()?.parse::<f64>().ok()?;
+            // Drop any labels; node metrics of interest are unlabelled
+            let name = name.split('{').next()?;
+            Some((normalize_name(name), value))
+        })
+        .collect()
+}
+
+fn flatten_ekg(value: &Value, path: String, samples: &mut HashMap<String, f64>) {
+    if let Some(object) = value.as_object() {
+        if let Some(val) = object.get("val") {
+            if let Some(number) = val.as_f64() {
+                samples.insert(normalize_name(&path), number);
+            }
+            return;
+        }
+
+        for (key, child) in object {
+            // EKG nests values under their type, e.g. connectedPeers.int.val
+            let child_path = if ["int", "real", "counter", "gauge"].contains(&key.as_str()) {
+                path.clone()
+            } else if path.is_empty() {
+                key.clone()
+            } else {
+                format!("{}_{}", path, key)
+            };
+            flatten_ekg(child, child_path, samples);
+        }
+    }
+}
+
+fn normalize_name(name: &str) -> String {
+    let name = name.replace('.', "_");
+    let name = name.strip_prefix(METRIC_PREFIX).unwrap_or(&name);
+    let name = name.strip_prefix("cardano_node_").unwrap_or(name);
+    ["_int", "_real", "_counter"]
+        .iter()
+        .find_map(|suffix| name.strip_suffix(suffix))
+        .unwrap_or(name)
+        .to_string()
+}
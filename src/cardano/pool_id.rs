@@ -0,0 +1,140 @@
+use anyhow::{bail, Result};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::fmt;
+use std::str::FromStr;
+use crate::cardano::bech32;
+
+const HRP: &str = "pool";
+
+/// A stake pool ID: the 28-byte hash of the pool's cold key. cardano-cli
+/// prints it as hex, Blockfrost and Koios as bech32 `pool1...`; both parse to
+/// the same value. Displays and serializes as bech32.
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PoolId {
+    hash: [u8; 28],
+    bech32: String,
+}
+
+impl PoolId {
+    pub fn from_hash(hash: [u8; 28]) -> Self {
+        PoolId { hash, bech32: bech32::encode(HRP, &hash) }
+    }
+
+    /// The bech32 form, accepted by every provider.
+    pub fn as_str(&self) -> &str {
+        &self.bech32
+    }
+
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.hash)
+    }
+
+    /// The entry for this pool in a JSON object keyed by pool ID, whichever
+    /// form the keys are in.
+    pub fn lookup<'a>(&self, object: &'a Value) -> Option<&'a Value> {
+        object.get(&self.bech32).or_else(|| object.get(self.to_hex()))
+    }
+}
+
+impl FromStr for PoolId {
+    type Err = anyhow::Error;
+
+    fn from_str(text: &str) -> Result<Self> {
+        let text = text.trim();
+        let bytes = if text.len() == 56 && text.chars().all(|c| c.is_ascii_hexdigit()) {
+            hex::decode(text)?
+        } else {
+            let (hrp, payload) = bech32::decode(text)
+                .map_err(|e| anyhow::anyhow!("'{}' is not a bech32 or hex pool ID ({})", text, e))?;
+            if hrp != HRP {
+                bail!("'{}' has prefix '{}' - expected a pool ID (pool1...)", text, hrp);
+            }
+            payload
+        };
+
+        match <[u8; 28]>::try_from(bytes.as_slice()) {
+            Ok(hash) => Ok(PoolId::from_hash(hash)),
+            Err(_) => bail!("'{}' is {} bytes - a pool ID is 28", text, bytes.len()),
+        }
+    }
+}
+
+impl fmt::Display for PoolId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.bech32)
+    }
+}
+
+impl fmt::Debug for PoolId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PoolId({})", self.bech32)
+    }
+}
+
+impl Serialize for PoolId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.bech32)
+    }
+}
+
+impl<'de> Deserialize<'de> for PoolId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(|e: anyhow::Error| serde::de::Error::custom(e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BECH32: &str = "pool1pu5jlj4q9w9jlxeu370a3c9myx47md5j5m2str0naunn2q3lkdy";
+    const HEX: &str = "0f292fcaa02b8b2f9b3c8f9fd8e0bb21abedb692a6d5058df3ef2735";
+
+    #[test]
+    fn bech32_and_hex_parse_to_the_same_id() {
+        let from_bech32: PoolId = BECH32.parse().unwrap();
+        let from_hex: PoolId = HEX.parse().unwrap();
+        assert_eq!(from_bech32, from_hex);
+        assert_eq!(from_hex.as_str(), BECH32);
+        assert_eq!(from_bech32.to_hex(), HEX);
+        assert_eq!(HEX.to_uppercase().parse::<PoolId>().unwrap(), from_bech32);
+    }
+
+    #[test]
+    fn serializes_as_bech32() {
+        let id: PoolId = HEX.parse().unwrap();
+        assert_eq!(serde_json::to_value(&id).unwrap(), Value::from(BECH32));
+        assert_eq!(serde_json::from_value::<PoolId>(Value::from(HEX)).unwrap(), id);
+    }
+
+    #[test]
+    fn bad_checksum_is_rejected() {
+        let corrupted = format!("{}q", &BECH32[..BECH32.len() - 1]);
+        let error = corrupted.parse::<PoolId>().unwrap_err().to_string();
+        assert!(error.contains("is not a bech32 or hex pool ID"), "{}", error);
+    }
+
+    #[test]
+    fn other_prefixes_are_rejected() {
+        let payload = hex::decode(HEX).unwrap();
+        let error = bech32::encode("stake", &payload).parse::<PoolId>().unwrap_err().to_string();
+        assert!(error.contains("has prefix 'stake'"), "{}", error);
+    }
+
+    #[test]
+    fn wrong_lengths_are_rejected() {
+        let error = bech32::encode(HRP, &[0u8; 32]).parse::<PoolId>().unwrap_err().to_string();
+        assert!(error.contains("is 32 bytes"), "{}", error);
+        // 54 hex digits is neither hex form nor bech32
+        assert!(HEX[..54].parse::<PoolId>().is_err());
+    }
+
+    #[test]
+    fn lookup_finds_either_key_form() {
+        let id: PoolId = BECH32.parse().unwrap();
+        assert_eq!(id.lookup(&serde_json::json!({ HEX: 1 })), Some(&Value::from(1)));
+        assert_eq!(id.lookup(&serde_json::json!({ BECH32: 2 })), Some(&Value::from(2)));
+    }
+}
@@ -0,0 +1,957 @@
+use anyhow::{Result, Context};
+use async_trait::async_trait;
+use serde::Serialize;
+use serde_json::Value;
+use crate::cardano::address::{stake_address, Credential};
+use crate::cardano::blockfrost::BlockfrostClient;
+use crate::cardano::cli::{extract_pool_params, CardanoCli};
+use crate::cardano::koios::KoiosClient;
+use crate::cardano::ogmios::{self, OgmiosClient};
+use crate::cardano::pool_id::PoolId;
+use crate::utils::config::Config;
+use crate::utils::error::{classify, CliError};
+
+// Below this sync progress the node's ledger answers are stale
+const MIN_SYNC_PROGRESS: f64 = 99.9;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChainTip {
+    pub epoch: u64,
+    pub slot: u64,
+    pub slot_in_epoch: Option<u64>,
+    pub block: Option<u64>,
+    pub hash: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EpochInfo {
+    pub epoch: u64,
+    pub start_time: Option<i64>,
+    pub end_time: Option<i64>,
+    pub block_count: Option<u64>,
+    pub active_stake: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PoolInfo {
+    pub pool_id: String,
+    pub active: bool,
+    pub live_stake: Option<u64>,
+    pub active_stake: Option<u64>,
+    pub live_saturation: Option<f64>,
+    pub live_delegators: Option<u64>,
+    pub blocks_minted: Option<u64>,
+    pub blocks_epoch: Option<u64>,
+    pub pledge: Option<u64>,
+    /// Stake addresses of the pool owners, whose stake counts towards pledge.
+    pub owners: Vec<String>,
+    pub fixed_cost: Option<u64>,
+    pub margin: Option<f64>,
+    pub source: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PoolStake {
+    pub pool_id: String,
+    pub pool_stake: u64,
+    pub total_stake: u64,
+    pub source: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Delegator {
+    pub address: String,
+    pub live_stake: Option<u64>,
+}
+
+/// Per-epoch pool result, as reported once the epoch's rewards are known.
+#[derive(Debug, Clone, Serialize)]
+pub struct EpochRewards {
+    pub epoch: u64,
+    pub blocks: u64,
+    pub active_stake: Option<u64>,
+    pub delegator_count: Option<u64>,
+    pub rewards: u64,
+    pub fees: u64,
+}
+
+/// A reward paid to a stake address.
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountReward {
+    pub epoch: u64,
+    pub amount: u64,
+    pub kind: String,
+    pub pool_id: Option<String>,
+}
+
+/// Balance and delegation of a stake address.
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountInfo {
+    pub stake_address: String,
+    pub registered: bool,
+    /// Funds under the stake key, rewards included. cardano-cli cannot tell.
+    pub controlled_amount: Option<u64>,
+    pub rewards_available: Option<u64>,
+    pub delegated_pool: Option<String>,
+    pub source: String,
+}
+
+/// A source of on-chain data about the network and its pools.
+#[async_trait]
+pub trait ChainDataProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+    async fn tip(&self) -> Result<ChainTip>;
+    async fn epoch_info(&self) -> Result<EpochInfo>;
+    async fn pool_info(&self, pool_id: &str) -> Result<PoolInfo>;
+    async fn pool_stake(&self, pool_id: &str) -> Result<PoolStake>;
+    async fn pool_delegators(&self, pool_id: &str) -> Result<Vec<Delegator>>;
+    async fn pool_blocks(&self, pool_id: &str, epoch: u64) -> Result<u64>;
+    /// Most recent `epochs` epochs with known rewards, newest first.
+    async fn pool_rewards(&self, pool_id: &str, epochs: u64) -> Result<Vec<EpochRewards>>;
+    /// Rewards earned by a stake address, newest first.
+    async fn account_rewards(&self, stake_address: &str) -> Result<Vec<AccountReward>>;
+    async fn account_info(&self, stake_address: &str) -> Result<AccountInfo>;
+}
+
+/// Every provider name accepted in `[providers] order` and `--provider`.
+pub const PROVIDER_NAMES: &[&str] = &["cardano-cli", "ogmios", "blockfrost", "koios"];
+
+/// Tries each configured provider in order and returns the first answer.
+pub struct ProviderChain {
+    providers: Vec<Box<dyn ChainDataProvider>>,
+}
+
+impl ProviderChain {
+    /// Build the chain from `[providers] order`, or from a single provider when one is named.
+    pub fn from_config(config: &Config, only: Option<&str>) -> Result<Self> {
+        let names = match only {
+            Some(name) => vec![name.to_string()],
+            None => config.providers.order.clone(),
+        };
+
+        let mut providers: Vec<Box<dyn ChainDataProvider>> = Vec::new();
+        for name in &names {
+            match name.as_str() {
+                "cardano-cli" => providers.push(Box::new(CardanoCli::new(config))),
+                "blockfrost" => match BlockfrostClient::new(config) {
+                    Some(client) => providers.push(Box::new(client)),
+                    None if only.is_some() => return Err(CliError::Config("Blockfrost configuration not found".to_string()).into()),
+                    None => {}
+                },
+                "koios" => match KoiosClient::new(config) {
+                    Some(client) => providers.push(Box::new(client)),
+                    None if only.is_some() => return Err(CliError::Config("Koios configuration not found".to_string()).into()),
+                    None => {}
+                },
+                "ogmios" => match OgmiosClient::new(config) {
+                    Some(client) => providers.push(Box::new(client)),
+                    None if only.is_some() => return Err(CliError::Config("Ogmios configuration not found".to_string()).into()),
+                    None => {}
+                },
+                other => return Err(CliError::Config(format!(
+                    "Unknown chain data provider '{}' (expected cardano-cli, ogmios, blockfrost or koios)", other
+                )).into()),
+            }
+        }
+
+        if providers.is_empty() {
+            return Err(CliError::Config("No chain data providers available - check [providers] order in config.toml".to_string()).into());
+        }
+
+        Ok(Self { providers })
+    }
+
+    /// Every provider that has configuration, regardless of `[providers] order`.
+    pub fn all_configured(config: &Config) -> Self {
+        let providers = PROVIDER_NAMES
+            .iter()
+            .filter_map(|name| Self::from_config(config, Some(name)).ok())
+            .flat_map(|chain| chain.providers)
+            .collect();
+        Self { providers }
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.providers.iter().map(|p| p.name()).collect()
+    }
+
+    pub fn providers(&self) -> &[Box<dyn ChainDataProvider>] {
+        &self.providers
+    }
+}
+
+macro_rules! failover {
+    ($chain:expr, $what:expr, |$provider:ident| $call:expr) => {{
+        let mut errors = Vec::new();
+        let mut kinds = Vec::new();
+        for $provider in &$chain.providers {
+            match $call.await {
+                Ok(value) => return Ok(value),
+                Err(e) => {
+                    log::warn!("{} failed to provide {}: {}", $provider.name(), $what, e);
+                    errors.push(format!("{}: {}", $provider.name(), e));
+                    kinds.push(classify(&e).cloned());
+                }
+            }
+        }
+        let message = format!("No provider could supply {} ({})", $what, errors.join("; "));
+        match common_kind(&kinds) {
+            Some(kind) => Err(kind.with_message(message).into()),
+            None => Err(anyhow::anyhow!(message)),
+        }
+    }};
+}
+
+/// The error category shared by every failed provider, if they all agree.
+fn common_kind(kinds: &[Option<CliError>]) -> Option<CliError> {
+    let first = kinds.first()?.as_ref()?;
+    kinds.iter()
+        .all(|k| k.as_ref().is_some_and(|k| std::mem::discriminant(k) == std::mem::discriminant(first)))
+        .then(|| first.clone())
+}
+
+#[async_trait]
+impl ChainDataProvider for ProviderChain {
+    fn name(&self) -> &'static str {
+        "failover"
+    }
+
+    async fn tip(&self) -> Result<ChainTip> {
+        failover!(self, "chain tip", |provider| provider.tip())
+    }
+
+    async fn epoch_info(&self) -> Result<EpochInfo> {
+        failover!(self, "epoch info", |provider| provider.epoch_info())
+    }
+
+    async fn pool_info(&self, pool_id: &str) -> Result<PoolInfo> {
+        failover!(self, "pool info", |provider| provider.pool_info(pool_id))
+    }
+
+    async fn pool_stake(&self, pool_id: &str) -> Result<PoolStake> {
+        failover!(self, "pool stake", |provider| provider.pool_stake(pool_id))
+    }
+
+    async fn pool_delegators(&self, pool_id: &str) -> Result<Vec<Delegator>> {
+        failover!(self, "pool delegators", |provider| provider.pool_delegators(pool_id))
+    }
+
+    async fn pool_blocks(&self, pool_id: &str, epoch: u64) -> Result<u64> {
+        failover!(self, "pool blocks", |provider| provider.pool_blocks(pool_id, epoch))
+    }
+
+    async fn pool_rewards(&self, pool_id: &str, epochs: u64) -> Result<Vec<EpochRewards>> {
+        failover!(self, "pool rewards", |provider| provider.pool_rewards(pool_id, epochs))
+    }
+
+    async fn account_rewards(&self, stake_address: &str) -> Result<Vec<AccountReward>> {
+        failover!(self, "account rewards", |provider| provider.account_rewards(stake_address))
+    }
+
+    async fn account_info(&self, stake_address: &str) -> Result<AccountInfo> {
+        failover!(self, "account info", |provider| provider.account_info(stake_address))
+    }
+}
+
+#[async_trait]
+impl ChainDataProvider for CardanoCli {
+    fn name(&self) -> &'static str {
+        "cardano-cli"
+    }
+
+    async fn tip(&self) -> Result<ChainTip> {
+        let tip = synced_tip(self).await?;
+        Ok(ChainTip {
+            epoch: tip["epoch"].as_u64().context("Tip has no epoch")?,
+            slot: tip["slot"].as_u64().context("Tip has no slot")?,
+            slot_in_epoch: tip["slotInEpoch"].as_u64(),
+            block: tip["block"].as_u64(),
+            hash: tip["hash"].as_str().map(String::from),
+        })
+    }
+
+    async fn epoch_info(&self) -> Result<EpochInfo> {
+        let tip = self.tip().await?;
+        Ok(EpochInfo {
+            epoch: tip.epoch,
+            start_time: None,
+            end_time: None,
+            block_count: None,
+            active_stake: None,
+        })
+    }
+
+    async fn pool_info(&self, pool_id: &str) -> Result<PoolInfo> {
+        synced_tip(self).await?;
+        let params = self.query_pool_params(pool_id).await?;
+        let pool_params = extract_pool_params(&params, pool_id);
+        let stake = self.pool_stake(pool_id).await.ok();
+
+        Ok(PoolInfo {
+            pool_id: pool_id.to_string(),
+            active: pool_params.is_some(),
+            live_stake: stake.as_ref().map(|s| s.pool_stake),
+            active_stake: None,
+            live_saturation: None,
+            live_delegators: None,
+            blocks_minted: None,
+            blocks_epoch: None,
+            pledge: pool_params.and_then(|p| p["pledge"].as_u64()),
+            owners: pool_params.map_or(Vec::new(), |p| owner_addresses(&p["owners"], self.is_mainnet())),
+            fixed_cost: pool_params.and_then(|p| p["cost"].as_u64()),
+            margin: pool_params.and_then(|p| p["margin"].as_f64()),
+            source: self.name().to_string(),
+        })
+    }
+
+    async fn pool_stake(&self, pool_id: &str) -> Result<PoolStake> {
+        synced_tip(self).await?;
+        let distribution = self.query_stake_distribution().await?;
+
+        Ok(PoolStake {
+            pool_id: pool_id.to_string(),
+            pool_stake: stake_from_distribution(&distribution, pool_id)
+                .context("Pool not found in stake distribution")?,
+            total_stake: total_stake_from_distribution(&distribution),
+            source: self.name().to_string(),
+        })
+    }
+
+    async fn pool_delegators(&self, _pool_id: &str) -> Result<Vec<Delegator>> {
+        anyhow::bail!("cardano-cli cannot list pool delegators")
+    }
+
+    async fn pool_blocks(&self, _pool_id: &str, _epoch: u64) -> Result<u64> {
+        anyhow::bail!("cardano-cli cannot count blocks per pool")
+    }
+
+    async fn pool_rewards(&self, _pool_id: &str, _epochs: u64) -> Result<Vec<EpochRewards>> {
+        anyhow::bail!("cardano-cli cannot report pool reward history")
+    }
+
+    async fn account_rewards(&self, _stake_address: &str) -> Result<Vec<AccountReward>> {
+        anyhow::bail!("cardano-cli cannot report account reward history")
+    }
+
+    async fn account_info(&self, stake_address: &str) -> Result<AccountInfo> {
+        synced_tip(self).await?;
+        // An empty list means the stake key is not registered
+        let info = self.query_stake_address_info(stake_address).await?;
+        let entry = info.as_array().and_then(|a| a.first());
+
+        Ok(AccountInfo {
+            stake_address: stake_address.to_string(),
+            registered: entry.is_some(),
+            controlled_amount: None,
+            rewards_available: entry.map_or(Some(0), |e| e["rewardAccountBalance"].as_u64()),
+            delegated_pool: entry
+                .and_then(|e| e["stakeDelegation"].as_str().or_else(|| e["delegation"].as_str()))
+                .map(String::from),
+            source: self.name().to_string(),
+        })
+    }
+}
+
+async fn synced_tip(cli: &CardanoCli) -> Result<Value> {
+    let tip = cli.query_tip().await?;
+    let progress = tip["syncProgress"]
+        .as_str()
+        .and_then(|s| s.parse::<f64>().ok())
+        .unwrap_or(100.0);
+
+    if progress < MIN_SYNC_PROGRESS {
+        anyhow::bail!("node is still syncing ({:.2}%)", progress);
+    }
+    Ok(tip)
+}
+
+fn stake_from_distribution(distribution: &Value, pool_id: &str) -> Option<u64> {
+    // The stake distribution format varies between cardano-cli versions, and
+    // so does whether pools are keyed by bech32 or hex ID
+    let pool_id = pool_id.parse::<PoolId>().ok()?;
+    distribution.get("pools")
+        .and_then(|pools| pool_id.lookup(pools))
+        .or_else(|| pool_id.lookup(distribution))
+        .and_then(|v| v.as_str())
+        .and_then(|s| s.parse::<u64>().ok())
+}
+
+fn total_stake_from_distribution(distribution: &Value) -> u64 {
+    for key in ["total", "totalStake"] {
+        if let Some(total) = distribution.get(key).and_then(|v| v.as_str()).and_then(|s| s.parse().ok()) {
+            return total;
+        }
+    }
+
+    // If we can't find total, sum all pool stakes
+    distribution.get("pools")
+        .and_then(|v| v.as_object())
+        .map(|pools| pools.values()
+            .filter_map(|v| v.as_str())
+            .filter_map(|s| s.parse::<u64>().ok())
+            .sum())
+        .unwrap_or(0)
+}
+
+#[async_trait]
+impl ChainDataProvider for BlockfrostClient {
+    fn name(&self) -> &'static str {
+        "blockfrost"
+    }
+
+    async fn tip(&self) -> Result<ChainTip> {
+        let block = self.get_latest_block().await?;
+        Ok(ChainTip {
+            epoch: block["epoch"].as_u64().context("Latest block has no epoch")?,
+            slot: block["slot"].as_u64().context("Latest block has no slot")?,
+            slot_in_epoch: block["epoch_slot"].as_u64(),
+            block: block["height"].as_u64(),
+            hash: block["hash"].as_str().map(String::from),
+        })
+    }
+
+    async fn epoch_info(&self) -> Result<EpochInfo> {
+        let epoch = self.get_latest_epoch().await?;
+        Ok(EpochInfo {
+            epoch: epoch["epoch"].as_u64().context("Epoch info has no epoch number")?,
+            start_time: epoch["start_time"].as_i64(),
+            end_time: epoch["end_time"].as_i64(),
+            block_count: epoch["block_count"].as_u64(),
+            active_stake: lovelace(&epoch["active_stake"]),
+        })
+    }
+
+    async fn pool_info(&self, pool_id: &str) -> Result<PoolInfo> {
+        let pool = self.get_pool_info(pool_id).await?;
+        let retiring = pool["retirement"].as_array().is_some_and(|r| !r.is_empty());
+
+        Ok(PoolInfo {
+            pool_id: pool_id.to_string(),
+            active: !retiring,
+            live_stake: lovelace(&pool["live_stake"]),
+            active_stake: lovelace(&pool["active_stake"]),
+            live_saturation: pool["live_saturation"].as_f64(),
+            live_delegators: pool["live_delegators"].as_u64(),
+            blocks_minted: pool["blocks_minted"].as_u64(),
+            blocks_epoch: pool["blocks_epoch"].as_u64(),
+            pledge: lovelace(&pool["declared_pledge"]),
+            owners: owner_addresses(&pool["owners"], true),
+            fixed_cost: lovelace(&pool["fixed_cost"]),
+            margin: pool["margin_cost"].as_f64(),
+            source: self.name().to_string(),
+        })
+    }
+
+    async fn pool_stake(&self, pool_id: &str) -> Result<PoolStake> {
+        let pool = self.get_pool_info(pool_id).await?;
+        let network = self.get_network_info().await?;
+
+        Ok(PoolStake {
+            pool_id: pool_id.to_string(),
+            pool_stake: lovelace(&pool["live_stake"]).context("Pool has no live stake")?,
+            total_stake: lovelace(&network["stake"]["live"]).context("Network has no live stake")?,
+            source: self.name().to_string(),
+        })
+    }
+
+    async fn pool_delegators(&self, pool_id: &str) -> Result<Vec<Delegator>> {
+        let mut delegators = Vec::new();
+        let mut page = 1;
+
+        loop {
+            let batch = self.get_pool_delegators(pool_id, page).await?;
+            let entries = match batch.as_array() {
+                Some(entries) if !entries.is_empty() => entries,
+                _ => break,
+            };
+
+            delegators.extend(entries.iter().filter_map(|d| Some(Delegator {
+                address: d["address"].as_str()?.to_string(),
+                live_stake: lovelace(&d["live_stake"]),
+            })));
+
+            if entries.len() < 100 {
+                break;
+            }
+            page += 1;
+        }
+
+        Ok(delegators)
+    }
+
+    async fn pool_blocks(&self, pool_id: &str, epoch: u64) -> Result<u64> {
+        let history = self.get_pool_history(pool_id, 1).await?;
+        if let Some(entry) = history.as_array().into_iter().flatten().find(|e| e["epoch"].as_u64() == Some(epoch)) {
+            return entry["blocks"].as_u64().context("History entry has no block count");
+        }
+
+        // History only covers finished epochs; the current one comes from pool info
+        let latest = self.get_latest_epoch().await?;
+        if latest["epoch"].as_u64() == Some(epoch) {
+            let pool = self.get_pool_info(pool_id).await?;
+            return pool["blocks_epoch"].as_u64().context("Pool info has no epoch block count");
+        }
+
+        anyhow::bail!("No block count for epoch {}", epoch)
+    }
+
+    async fn pool_rewards(&self, pool_id: &str, epochs: u64) -> Result<Vec<EpochRewards>> {
+        let history = self.get_pool_history(pool_id, 1).await?;
+        Ok(history.as_array().into_iter().flatten()
+            .take(epochs as usize)
+            .filter_map(|entry| Some(EpochRewards {
+                epoch: entry["epoch"].as_u64()?,
+                blocks: entry["blocks"].as_u64().unwrap_or(0),
+                active_stake: lovelace(&entry["active_stake"]),
+                delegator_count: entry["delegators_count"].as_u64(),
+                rewards: lovelace(&entry["rewards"])?,
+                fees: lovelace(&entry["fees"])?,
+            }))
+            .collect())
+    }
+    async fn account_rewards(&self, stake_address: &str) -> Result<Vec<AccountReward>> {
+        let rewards = self.get_account_rewards(stake_address).await?;
+        Ok(rewards.as_array().into_iter().flatten()
+            .filter_map(|r| Some(AccountReward {
+                epoch: r["epoch"].as_u64()?,
+                amount: lovelace(&r["amount"])?,
+                kind: r["type"].as_str().unwrap_or("member").to_string(),
+                pool_id: r["pool_id"].as_str().map(String::from),
+            }))
+            .collect())
+    }
+    async fn account_info(&self, stake_address: &str) -> Result<AccountInfo> {
+        let account = match self.get_account(stake_address).await {
+            Ok(account) => account,
+            // Blockfrost only knows stake addresses that have appeared on chain
+            Err(e) if matches!(classify(&e), Some(CliError::NotFound(_))) => {
+                return Ok(unregistered_account(stake_address, self.name()));
+            }
+            Err(e) => return Err(e),
+        };
+        Ok(AccountInfo {
+            stake_address: stake_address.to_string(),
+            registered: account["active"].as_bool().unwrap_or(false),
+            controlled_amount: lovelace(&account["controlled_amount"]),
+            rewards_available: lovelace(&account["withdrawable_amount"]),
+            delegated_pool: account["pool_id"].as_str().map(String::from),
+            source: self.name().to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl ChainDataProvider for KoiosClient {
+    fn name(&self) -> &'static str {
+        "koios"
+    }
+
+    async fn tip(&self) -> Result<ChainTip> {
+        let tip = first(self.get_tip().await?)?;
+        Ok(ChainTip {
+            epoch: tip["epoch_no"].as_u64().context("Tip has no epoch")?,
+            slot: tip["abs_slot"].as_u64().context("Tip has no slot")?,
+            slot_in_epoch: tip["epoch_slot"].as_u64(),
+            block: tip["block_no"].as_u64(),
+            hash: tip["hash"].as_str().map(String::from),
+        })
+    }
+
+    async fn epoch_info(&self) -> Result<EpochInfo> {
+        let tip = self.tip().await?;
+        let epoch = first(self.get_epoch_info(Some(tip.epoch)).await?)?;
+        Ok(EpochInfo {
+            epoch: tip.epoch,
+            start_time: epoch["start_time"].as_i64(),
+            end_time: epoch["end_time"].as_i64(),
+            block_count: epoch["blk_count"].as_u64(),
+            active_stake: lovelace(&epoch["active_stake"]),
+        })
+    }
+
+    async fn pool_info(&self, pool_id: &str) -> Result<PoolInfo> {
+        let pool = first(self.get_pool_info(&[pool_id]).await?)?;
+        Ok(PoolInfo {
+            pool_id: pool_id.to_string(),
+            active: pool["pool_status"].as_str() == Some("registered"),
+            live_stake: lovelace(&pool["live_stake"]),
+            active_stake: lovelace(&pool["active_stake"]),
+            live_saturation: pool["live_saturation"].as_f64(),
+            live_delegators: pool["live_delegators"].as_u64(),
+            blocks_minted: pool["block_count"].as_u64(),
+            blocks_epoch: None,
+            pledge: lovelace(&pool["pledge"]),
+            owners: owner_addresses(&pool["owners"], true),
+            fixed_cost: lovelace(&pool["fixed_cost"]),
+            margin: pool["margin"].as_f64(),
+            source: self.name().to_string(),
+        })
+    }
+
+    async fn pool_stake(&self, pool_id: &str) -> Result<PoolStake> {
+        let pool = first(self.get_pool_info(&[pool_id]).await?)?;
+        let pool_stake = lovelace(&pool["live_stake"]).context("Pool has no live stake")?;
+
+        // Koios has no network-wide live stake; use the epoch's active stake instead
+        let total_stake = self.epoch_info().await?.active_stake
+            .context("Total stake unavailable from Koios")?;
+
+        Ok(PoolStake {
+            pool_id: pool_id.to_string(),
+            pool_stake,
+            total_stake,
+            source: self.name().to_string(),
+        })
+    }
+
+    async fn pool_delegators(&self, pool_id: &str) -> Result<Vec<Delegator>> {
+        let delegators = self.get_pool_delegators(pool_id).await?;
+        Ok(delegators.as_array().into_iter().flatten()
+            .filter_map(|d| Some(Delegator {
+                address: d["stake_address"].as_str()?.to_string(),
+                live_stake: lovelace(&d["amount"]),
+            }))
+            .collect())
+    }
+
+    async fn pool_blocks(&self, pool_id: &str, epoch: u64) -> Result<u64> {
+        let blocks = self.get_pool_blocks(pool_id, Some(epoch)).await?;
+        Ok(blocks.as_array().map_or(0, |b| b.len() as u64))
+    }
+
+    async fn pool_rewards(&self, pool_id: &str, epochs: u64) -> Result<Vec<EpochRewards>> {
+        let history = self.get_pool_history(pool_id, None).await?;
+        Ok(history.as_array().into_iter().flatten()
+            .filter_map(|entry| Some(EpochRewards {
+                epoch: entry["epoch_no"].as_u64()?,
+                blocks: entry["block_cnt"].as_u64().unwrap_or(0),
+                active_stake: lovelace(&entry["active_stake"]),
+                delegator_count: entry["delegator_cnt"].as_u64(),
+                rewards: lovelace(&entry["deleg_rewards"])? + lovelace(&entry["pool_fees"])?,
+                fees: lovelace(&entry["pool_fees"])?,
+            }))
+            .take(epochs as usize)
+            .collect())
+    }
+    async fn account_rewards(&self, stake_address: &str) -> Result<Vec<AccountReward>> {
+        let accounts = self.get_account_rewards(&[stake_address], None).await?;
+
+        // Older Koios versions nest rewards per account, newer ones return flat rows
+        let rows: Vec<&Value> = accounts.as_array().into_iter().flatten()
+            .flat_map(|account| match account["rewards"].as_array() {
+                Some(nested) => nested.iter().collect::<Vec<_>>(),
+                None => vec![account],
+            })
+            .collect();
+
+        let mut rewards: Vec<AccountReward> = rows.into_iter()
+            .filter_map(|r| Some(AccountReward {
+                epoch: r["earned_epoch"].as_u64()?,
+                amount: lovelace(&r["amount"])?,
+                kind: r["type"].as_str().unwrap_or("member").to_string(),
+                pool_id: r["pool_id_bech32"].as_str().or_else(|| r["pool_id"].as_str()).map(String::from),
+            }))
+            .collect();
+        rewards.sort_by_key(|r| std::cmp::Reverse(r.epoch));
+        Ok(rewards)
+    }
+
+    async fn account_info(&self, stake_address: &str) -> Result<AccountInfo> {
+        // Koios leaves stake addresses that never appeared on chain out of the list
+        let account = match self.get_account_info(&[stake_address]).await? {
+            Value::Array(accounts) if accounts.is_empty() => return Ok(unregistered_account(stake_address, self.name())),
+            accounts => first(accounts)?,
+        };
+        Ok(AccountInfo {
+            stake_address: stake_address.to_string(),
+            registered: account["status"].as_str() == Some("registered"),
+            controlled_amount: lovelace(&account["total_balance"]),
+            rewards_available: lovelace(&account["rewards_available"]),
+            delegated_pool: account["delegated_pool"].as_str().map(String::from),
+            source: self.name().to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl ChainDataProvider for OgmiosClient {
+    fn name(&self) -> &'static str {
+        "ogmios"
+    }
+
+    async fn tip(&self) -> Result<ChainTip> {
+        let tip = self.query_tip().await?;
+        Ok(ChainTip {
+            epoch: self.query_epoch().await?,
+            slot: tip["slot"].as_u64().context("Tip has no slot")?,
+            slot_in_epoch: None,
+            block: self.query_block_height().await.ok(),
+            hash: tip["id"].as_str().map(String::from),
+        })
+    }
+
+    async fn epoch_info(&self) -> Result<EpochInfo> {
+        let provenance = self.query_rewards_provenance().await.ok();
+        Ok(EpochInfo {
+            epoch: self.query_epoch().await?,
+            start_time: None,
+            end_time: None,
+            block_count: None,
+            active_stake: provenance.and_then(|p| ogmios::lovelace(&p["activeStakeInEpoch"])),
+        })
+    }
+
+    async fn pool_info(&self, pool_id: &str) -> Result<PoolInfo> {
+        let pools = self.query_stake_pools(&[pool_id]).await?;
+        let pool = pools.get(pool_id).context("Pool is not registered")?;
+
+        Ok(PoolInfo {
+            pool_id: pool_id.to_string(),
+            // A scheduled retirement shows up as `retirement: { epoch }`
+            active: pool.get("retirement").is_none_or(Value::is_null),
+            live_stake: None,
+            active_stake: None,
+            live_saturation: None,
+            live_delegators: None,
+            blocks_minted: None,
+            blocks_epoch: None,
+            pledge: ogmios::lovelace(&pool["pledge"]),
+            // Owners are key hashes; the reward account tells the network
+            owners: owner_addresses(&pool["owners"], pool["rewardAccount"].as_str().is_none_or(|a| !a.starts_with("stake_test"))),
+            fixed_cost: ogmios::lovelace(&pool["cost"]),
+            margin: ogmios::ratio(&pool["margin"]),
+            source: self.name().to_string(),
+        })
+    }
+
+    async fn pool_stake(&self, pool_id: &str) -> Result<PoolStake> {
+        let distribution = self.query_live_stake_distribution().await?;
+        let share = pool_id.parse::<PoolId>().ok()
+            .and_then(|id| id.lookup(&distribution))
+            .and_then(|entry| ogmios::ratio(&entry["stake"]))
+            .context("Pool not found in live stake distribution")?;
+
+        // The live distribution is relative and no query gives its lovelace
+        // total; scaling by the active snapshot would match no other source
+        anyhow::bail!("Ogmios only reports relative live stake ({:.6}%)", share * 100.0)
+    }
+
+    async fn pool_delegators(&self, _pool_id: &str) -> Result<Vec<Delegator>> {
+        anyhow::bail!("Ogmios cannot list pool delegators")
+    }
+
+    async fn pool_blocks(&self, _pool_id: &str, _epoch: u64) -> Result<u64> {
+        anyhow::bail!("Ogmios cannot count blocks per pool")
+    }
+
+    async fn pool_rewards(&self, _pool_id: &str, _epochs: u64) -> Result<Vec<EpochRewards>> {
+        anyhow::bail!("Ogmios cannot report pool reward history")
+    }
+
+    async fn account_rewards(&self, _stake_address: &str) -> Result<Vec<AccountReward>> {
+        anyhow::bail!("Ogmios cannot report account reward history")
+    }
+    async fn account_info(&self, _stake_address: &str) -> Result<AccountInfo> {
+        anyhow::bail!("Ogmios cannot report account balances")
+    }
+}
+
+/// A stake address the provider has never seen: unregistered and empty.
+fn unregistered_account(stake_address: &str, source: &str) -> AccountInfo {
+    AccountInfo {
+        stake_address: stake_address.to_string(),
+        registered: false,
+        controlled_amount: Some(0),
+        rewards_available: Some(0),
+        delegated_pool: None,
+        source: source.to_string(),
+    }
+}
+
+/// Lovelace amounts arrive as strings from the HTTP APIs, numbers from cardano-cli.
+fn lovelace(value: &Value) -> Option<u64> {
+    value.as_str().and_then(|s| s.parse().ok()).or_else(|| value.as_u64())
+}
+
+/// Owners as stake addresses. Blockfrost and Koios already return them that
+/// way; cardano-cli and Ogmios return stake key hashes.
+fn owner_addresses(owners: &Value, mainnet: bool) -> Vec<String> {
+    owners.as_array().into_iter().flatten()
+        .filter_map(|owner| owner.as_str())
+        .map(|owner| match hex::decode(owner) {
+            Ok(hash) if hash.len() == 28 => stake_address(&Credential::Key(owner.to_lowercase()), u8::from(mainnet)),
+            _ => owner.to_string(),
+        })
+        .collect()
+}
+
+fn first(value: Value) -> Result<Value> {
+    match value {
+        Value::Array(mut items) if !items.is_empty() => Ok(items.swap_remove(0)),
+        _ => anyhow::bail!("Empty response"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cardano::ogmios::stand_in;
+    use crate::utils::config::{BlockfrostConfig, KoiosConfig};
+    use crate::utils::secret::Secret;
+    use serde_json::json;
+    use std::sync::{Arc, Mutex};
+
+    const POOL: &str = "pool1pu5jlj4q9w9jlxeu370a3c9myx47md5j5m2str0naunn2q3lkdy";
+
+    #[tokio::test]
+    async fn ogmios_pool_info_reads_retirement_and_owners() {
+        let client = OgmiosClient::with_url(&stand_in::serve().await);
+
+        let info = client.pool_info(POOL).await.unwrap();
+        assert!(!info.active, "a scheduled retirement makes the pool inactive");
+        assert_eq!(info.pledge, Some(500_000_000_000));
+        assert_eq!(info.margin, Some(0.02));
+        assert_eq!(info.owners, vec!["stake1uyehkck0lajq8gr28t9uxnuvgcqrc6070x3k9r8048z8y5gh6ffgw"]);
+        assert_eq!(info.live_stake, None);
+    }
+
+    const OWNER: &str = "stake1uyehkck0lajq8gr28t9uxnuvgcqrc6070x3k9r8048z8y5gh6ffgw";
+
+    /// Answer every HTTP request with the same status and JSON body.
+    async fn serve_http(status: &'static str, body: &'static str) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = [0u8; 4096];
+                let _ = stream.read(&mut request).await;
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status, body.len(), body
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        url
+    }
+
+    fn blockfrost_at(base_url: &str) -> BlockfrostClient {
+        let config = Config {
+            blockfrost: Some(BlockfrostConfig {
+                api_key: Secret::new("mainnettest"),
+                api_key_file: None,
+                api_key_env: None,
+                base_url: base_url.to_string(),
+            }),
+            ..Config::default()
+        };
+        BlockfrostClient::new(&config).unwrap()
+    }
+
+    #[tokio::test]
+    async fn blockfrost_unknown_account_is_unregistered() {
+        let client = blockfrost_at(&serve_http("404 Not Found", r#"{"status_code":404,"error":"Not Found"}"#).await);
+
+        let account = client.account_info(OWNER).await.unwrap();
+        assert!(!account.registered);
+        assert_eq!(account.controlled_amount, Some(0));
+        assert_eq!(account.delegated_pool, None);
+    }
+
+    #[tokio::test]
+    async fn blockfrost_other_errors_still_fail() {
+        let client = blockfrost_at(&serve_http("403 Forbidden", r#"{"status_code":403}"#).await);
+
+        let error = client.account_info(OWNER).await.unwrap_err();
+        assert!(matches!(classify(&error), Some(CliError::ProviderAuth(_))), "{:#}", error);
+    }
+
+    fn koios_at(base_url: &str) -> KoiosClient {
+        let config = Config {
+            koios: Some(KoiosConfig {
+                base_url: base_url.to_string(),
+                api_token: None,
+                api_token_file: None,
+                api_token_env: None,
+            }),
+            ..Config::default()
+        };
+        KoiosClient::new(&config).unwrap()
+    }
+
+    /// Serve `total` rows the way Koios does, honouring the Range header,
+    /// and return the URL with the ranges that were asked for.
+    async fn serve_koios_rows(total: usize) -> (String, Arc<Mutex<Vec<String>>>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let ranges = Arc::new(Mutex::new(Vec::new()));
+        let seen = ranges.clone();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = [0u8; 4096];
+                let read = stream.read(&mut request).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&request[..read]).to_string();
+                let range = request.lines()
+                    .find_map(|line| line.to_ascii_lowercase().strip_prefix("range: ").map(str::to_string))
+                    .unwrap_or_default();
+                let (start, end) = range.split_once('-')
+                    .and_then(|(start, end)| Some((start.parse::<usize>().ok()?, end.parse::<usize>().ok()?)))
+                    .unwrap_or((0, total));
+                seen.lock().unwrap().push(range);
+
+                let rows: Vec<Value> = (start..(end + 1).min(total))
+                    .map(|n| json!({ "stake_address": format!("stake{}", n) }))
+                    .collect();
+                let body = Value::Array(rows).to_string();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(), body
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        (url, ranges)
+    }
+
+    #[tokio::test]
+    async fn koios_lists_are_paged_until_a_short_page() {
+        let (url, ranges) = serve_koios_rows(2_500).await;
+
+        let rows = koios_at(&url).get_pool_delegators(POOL).await.unwrap();
+        let rows = rows.as_array().unwrap();
+        assert_eq!(rows.len(), 2_500);
+        assert_eq!(rows[1_000]["stake_address"], "stake1000");
+        assert_eq!(rows[2_499]["stake_address"], "stake2499");
+        assert_eq!(*ranges.lock().unwrap(), vec!["0-999", "1000-1999", "2000-2999"]);
+    }
+
+    #[tokio::test]
+    async fn koios_full_last_page_ends_on_an_empty_one() {
+        let (url, ranges) = serve_koios_rows(1_000).await;
+
+        let rows = koios_at(&url).get_pool_delegators(POOL).await.unwrap();
+        assert_eq!(rows.as_array().unwrap().len(), 1_000);
+        assert_eq!(*ranges.lock().unwrap(), vec!["0-999", "1000-1999"]);
+    }
+
+    #[tokio::test]
+    async fn koios_empty_account_list_is_unregistered() {
+        let client = koios_at(&serve_http("200 OK", "[]").await);
+
+        let account = client.account_info(OWNER).await.unwrap();
+        assert!(!account.registered);
+        assert_eq!(account.rewards_available, Some(0));
+    }
+
+    #[tokio::test]
+    async fn ogmios_pool_stake_has_no_lovelace_total() {
+        let client = OgmiosClient::with_url(&stand_in::serve().await);
+
+        let error = client.pool_stake(POOL).await.unwrap_err().to_string();
+        assert!(error.contains("relative live stake (0.050000%)"), "{}", error);
+    }
+}
@@ -0,0 +1,334 @@
+use anyhow::{Result, Context};
+use hickory_resolver::TokioAsyncResolver;
+use serde::Serialize;
+use serde_json::Value;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Instant;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time::{timeout, Duration};
+use crate::cardano::cbor::{Decoder, Encoder, MAJOR_ARRAY, MAJOR_UNSIGNED};
+use crate::cardano::cli::extract_pool_params;
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+const DEFAULT_RELAY_PORT: u16 = 3001;
+
+// Node-to-node versions that share the [magic, initiatorOnly, peerSharing, query] parameters
+const NODE_TO_NODE_VERSIONS: [u64; 4] = [11, 12, 13, 14];
+const HANDSHAKE_PROTOCOL_ID: u16 = 0;
+
+#[derive(Debug, Clone, Serialize)]
+pub enum RelayKind {
+    SingleAddress,
+    SingleHostName,
+    MultiHostName,
+}
+
+/// A relay as registered in the pool certificate.
+#[derive(Debug, Clone, Serialize)]
+pub struct Relay {
+    pub kind: RelayKind,
+    pub host: String,
+    pub port: Option<u16>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub enum Handshake {
+    Accepted { version: u64, network_magic: u64 },
+    Refused(String),
+    Failed(String),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RelayProbe {
+    pub relay: String,
+    pub endpoint: Option<SocketAddr>,
+    pub reachable: bool,
+    pub latency_ms: Option<f64>,
+    pub handshake: Option<Handshake>,
+    pub network_ok: bool,
+    pub error: Option<String>,
+}
+
+impl RelayProbe {
+    pub fn is_healthy(&self) -> bool {
+        self.reachable && self.network_ok
+    }
+}
+
+/// Parse relays from `cardano-cli query pool-params` output.
+pub fn relays_from_pool_params(params: &Value, pool_id: &str) -> Vec<Relay> {
+    let relays = match extract_pool_params(params, pool_id).and_then(|p| p["relays"].as_array()) {
+        Some(relays) => relays,
+        None => return Vec::new(),
+    };
+
+    relays.iter().filter_map(|relay| {
+        if let Some(single) = relay.get("single host address") {
+            let host = single["IPv4"].as_str().or_else(|| single["IPv6"].as_str())?;
+            Some(Relay {
+                kind: RelayKind::SingleAddress,
+                host: host.to_string(),
+                port: single["port"].as_u64().map(|p| p as u16),
+            })
+        } else if let Some(single) = relay.get("single host name") {
+            Some(Relay {
+                kind: RelayKind::SingleHostName,
+                host: single["dnsName"].as_str()?.to_string(),
+                port: single["port"].as_u64().map(|p| p as u16),
+            })
+        } else {
+            relay.get("multi host name").and_then(|multi| {
+                Some(Relay {
+                    kind: RelayKind::MultiHostName,
+                    host: multi["dnsName"].as_str()?.to_string(),
+                    port: None,
+                })
+            })
+        }
+    }).collect()
+}
+
+/// Parse relays from Blockfrost `/pools/{id}/relays` output.
+pub fn relays_from_blockfrost(relays: &Value) -> Vec<Relay> {
+    relays.as_array().map(|relays| relays.iter().filter_map(|relay| {
+        let port = relay["port"].as_u64().map(|p| p as u16);
+
+        if let Some(srv) = relay["dns_srv"].as_str() {
+            Some(Relay { kind: RelayKind::MultiHostName, host: srv.to_string(), port: None })
+        } else if let Some(dns) = relay["dns"].as_str() {
+            Some(Relay { kind: RelayKind::SingleHostName, host: dns.to_string(), port })
+        } else {
+            let ip = relay["ipv4"].as_str().or_else(|| relay["ipv6"].as_str())?;
+            Some(Relay { kind: RelayKind::SingleAddress, host: ip.to_string(), port })
+        }
+    }).collect()).unwrap_or_default()
+}
+
+/// Resolve a relay to the socket addresses it advertises.
+pub async fn resolve(resolver: &TokioAsyncResolver, relay: &Relay) -> Result<Vec<SocketAddr>> {
+    let port = relay.port.unwrap_or(DEFAULT_RELAY_PORT);
+
+    match relay.kind {
+        RelayKind::SingleAddress => {
+            let ip: IpAddr = relay.host.parse()
+                .with_context(|| format!("Invalid relay IP address {}", relay.host))?;
+            Ok(vec![SocketAddr::new(ip, port)])
+        }
+        RelayKind::SingleHostName => {
+            let lookup = resolver.lookup_ip(relay.host.as_str()).await
+                .with_context(|| format!("DNS lookup failed for {}", relay.host))?;
+            Ok(lookup.iter().map(|ip| SocketAddr::new(ip, port)).collect())
+        }
+        RelayKind::MultiHostName => {
+            let srv = resolver.srv_lookup(relay.host.as_str()).await
+                .with_context(|| format!("SRV lookup failed for {}", relay.host))?;
+
+            let mut addrs = Vec::new();
+            for record in srv.iter() {
+                let target = record.target().to_utf8();
+                if let Ok(lookup) = resolver.lookup_ip(target.as_str()).await {
+                    addrs.extend(lookup.iter().map(|ip| SocketAddr::new(ip, record.port())));
+                }
+            }
+            Ok(addrs)
+        }
+    }
+}
+
+/// Resolve every relay and probe each endpoint it resolves to.
+pub async fn probe_relays(relays: &[Relay], network_magic: u32) -> Result<Vec<RelayProbe>> {
+    let resolver = TokioAsyncResolver::tokio_from_system_conf()
+        .context("Failed to create DNS resolver")?;
+
+    let mut probes = Vec::new();
+
+    for relay in relays {
+        let label = match relay.port {
+            Some(port) => format!("{}:{}", relay.host, port),
+            None => relay.host.clone(),
+        };
+
+        match resolve(&resolver, relay).await {
+            Ok(addrs) if !addrs.is_empty() => {
+                for addr in addrs {
+                    probes.push(probe_endpoint(&label, addr, network_magic).await);
+                }
+            }
+            Ok(_) => probes.push(unresolved(&label, "No addresses found".to_string())),
+            Err(e) => probes.push(unresolved(&label, e.to_string())),
+        }
+    }
+
+    Ok(probes)
+}
+
+fn unresolved(label: &str, error: String) -> RelayProbe {
+    RelayProbe {
+        relay: label.to_string(),
+        endpoint: None,
+        reachable: false,
+        latency_ms: None,
+        handshake: None,
+        network_ok: false,
+        error: Some(error),
+    }
+}
+
+pub async fn probe_endpoint(label: &str, addr: SocketAddr, network_magic: u32) -> RelayProbe {
+    let mut probe = RelayProbe {
+        relay: label.to_string(),
+        endpoint: Some(addr),
+        reachable: false,
+        latency_ms: None,
+        handshake: None,
+        network_ok: false,
+        error: None,
+    };
+
+    let started = Instant::now();
+    let mut stream = match timeout(PROBE_TIMEOUT, TcpStream::connect(addr)).await {
+        Ok(Ok(stream)) => stream,
+        Ok(Err(e)) => {
+            probe.error = Some(e.to_string());
+            return probe;
+        }
+        Err(_) => {
+            probe.error = Some("Connection timed out".to_string());
+            return probe;
+        }
+    };
+
+    probe.reachable = true;
+    probe.latency_ms = Some(started.elapsed().as_secs_f64() * 1000.0);
+
+    let handshake = match timeout(PROBE_TIMEOUT, handshake(&mut stream, network_magic)).await {
+        Ok(Ok(handshake)) => handshake,
+        Ok(Err(e)) => Handshake::Failed(e.to_string()),
+        Err(_) => Handshake::Failed("Handshake timed out".to_string()),
+    };
+
+    probe.network_ok = matches!(
+        handshake,
+        Handshake::Accepted { network_magic: magic, .. } if magic == network_magic as u64
+    );
+    probe.handshake = Some(handshake);
+    probe
+}
+
+async fn handshake(stream: &mut TcpStream, network_magic: u32) -> Result<Handshake> {
+    let payload = propose_versions(network_magic);
+
+    // Mux SDU header: timestamp, mode bit + protocol id, payload length
+    let mut sdu = Vec::with_capacity(8 + payload.len());
+    sdu.extend_from_slice(&0u32.to_be_bytes());
+    sdu.extend_from_slice(&HANDSHAKE_PROTOCOL_ID.to_be_bytes());
+    sdu.extend_from_slice(&(payload.len() as u16).to_be_bytes());
+    sdu.extend_from_slice(&payload);
+    stream.write_all(&sdu).await?;
+
+    let mut header = [0u8; 8];
+    stream.read_exact(&mut header).await?;
+    let length = u16::from_be_bytes([header[6], header[7]]) as usize;
+    let mut response = vec![0u8; length];
+    stream.read_exact(&mut response).await?;
+
+    decode_reply(&response)
+}
+
+/// MsgProposeVersions: [0, { version => [magic, initiatorOnly, peerSharing, query] }]
+fn propose_versions(network_magic: u32) -> Vec<u8> {
+    let mut encoder = Encoder::new();
+    encoder.array(2).unsigned(0).map(NODE_TO_NODE_VERSIONS.len() as u64);
+    for version in NODE_TO_NODE_VERSIONS {
+        encoder.unsigned(version)
+            .array(4)
+            .unsigned(network_magic as u64)
+            .bool(true)
+            .unsigned(0)
+            .bool(false);
+    }
+    encoder.finish()
+}
+
+fn decode_reply(response: &[u8]) -> Result<Handshake> {
+    let mut decoder = Decoder::new(response);
+    decoder.array()?;
+    match decoder.unsigned()? {
+        1 => {
+            // MsgAcceptVersion: [1, version, params]
+            let version = decoder.unsigned()?;
+            let (major, value) = decoder.read_header()?;
+            let network_magic = match major {
+                MAJOR_ARRAY => decoder.unsigned()?,
+                MAJOR_UNSIGNED => value,
+                _ => anyhow::bail!("Unexpected version parameters in handshake reply"),
+            };
+            Ok(Handshake::Accepted { version, network_magic })
+        }
+        2 => {
+            // MsgRefuse: [2, [reason, ...]]
+            decoder.array()?;
+            let reason = match decoder.unsigned()? {
+                0 => "version mismatch".to_string(),
+                1 => "handshake decode error".to_string(),
+                _ => {
+                    decoder.unsigned()?;
+                    decoder.text().unwrap_or_else(|_| "refused".to_string())
+                }
+            };
+            Ok(Handshake::Refused(reason))
+        }
+        tag => anyhow::bail!("Unexpected handshake message {}", tag),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAINNET: u32 = 764_824_073;
+
+    fn decode(reply: &str) -> Handshake {
+        decode_reply(&hex::decode(reply).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn proposal_offers_versions_11_to_14() {
+        // [magic, initiatorOnly = true, peerSharing = 0, query = false]
+        let params = "841a2d964a09f500f4";
+        let expected = format!("8200a40b{p}0c{p}0d{p}0e{p}", p = params);
+        assert_eq!(hex::encode(propose_versions(MAINNET)), expected);
+
+        assert!(hex::encode(propose_versions(1)).starts_with("8200a40b8401f500f4"));
+    }
+
+    #[test]
+    fn accepted_version_and_magic_are_decoded() {
+        match decode("83010e841a2d964a09f500f4") {
+            Handshake::Accepted { version, network_magic } => assert_eq!((version, network_magic), (14, MAINNET as u64)),
+            other => panic!("{:?}", other),
+        }
+        // Versions before 11 reply with the bare magic
+        match decode("83010a1a2d964a09") {
+            Handshake::Accepted { version, network_magic } => assert_eq!((version, network_magic), (10, MAINNET as u64)),
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn refusals_carry_their_reason() {
+        let reason = |reply: &str| match decode(reply) {
+            Handshake::Refused(reason) => reason,
+            other => panic!("{:?}", other),
+        };
+        // [2, [0, [7, 8]]]
+        assert_eq!(reason("82028200820708"), "version mismatch");
+        // [2, [1, 14, "bad"]]
+        assert_eq!(reason("820283010e63626164"), "handshake decode error");
+        // [2, [2, 14, "busy"]]
+        assert_eq!(reason("820283020e6462757379"), "busy");
+
+        assert!(decode_reply(&hex::decode("8203").unwrap()).is_err());
+    }
+}
@@ -0,0 +1,196 @@
+use anyhow::Result;
+use colored::*;
+use serde::Serialize;
+use serde_json::Value;
+use crate::cardano::address::{Address, AddressKind, Credential};
+use crate::cardano::pool_id::PoolId;
+use crate::cardano::provider::{AccountInfo, ChainDataProvider, ProviderChain};
+use crate::utils::config::Config;
+use crate::utils::error::CliError;
+use crate::utils::output::{format_ada, render, OutputFormat, Render};
+use crate::progress;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct InspectedAddress {
+    /// Which configured pool field the address came from, e.g. `ADLT pledge`.
+    pub label: Option<String>,
+    #[serde(flatten)]
+    pub decoded: Address,
+    pub account: Option<AccountInfo>,
+    /// For configured addresses: whether the stake key delegates to that pool.
+    pub delegated_to_pool: Option<bool>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AddressReport {
+    pub network: String,
+    pub addresses: Vec<InspectedAddress>,
+}
+
+impl Render for AddressReport {
+    const SCHEMA: &'static str = "address-inspect";
+
+    fn render_table(&self) {
+        for entry in &self.addresses {
+            let address = &entry.decoded;
+            match &entry.label {
+                Some(label) => println!("\n🏷️  {} {}", label.cyan().bold(), address.address.dimmed()),
+                None => println!("\n🏷️  {}", address.address.cyan()),
+            }
+
+            println!("  Type:           {}", describe_kind(address));
+            println!("  Network:        {}", describe_network(address));
+            if let Some(payment) = &address.payment {
+                println!("  Payment:        {}", describe_credential(payment));
+            }
+            if let Some(stake) = &address.stake {
+                println!("  Stake:          {}", describe_credential(stake));
+            }
+            if let Some(pointer) = &address.pointer {
+                println!("  Pointer:        slot {}, tx {}, cert {}", pointer.slot, pointer.tx_index, pointer.cert_index);
+            }
+            if let Some(root) = &address.root {
+                println!("  Address root:   {}", root);
+            }
+            if let Some(stake_address) = &address.stake_address {
+                println!("  Stake address:  {}", stake_address);
+            }
+
+            if let Some(account) = &entry.account {
+                if !account.registered {
+                    println!("  Stake key:      {}", "not registered".yellow());
+                }
+                println!("  Balance:        {}", account.controlled_amount.map_or("-".to_string(), format_ada));
+                println!("  Rewards:        {}", account.rewards_available.map_or("-".to_string(), format_ada));
+                let delegation = account.delegated_pool.as_deref().unwrap_or("not delegated");
+                match entry.delegated_to_pool {
+                    Some(true) => println!("  Delegated to:   {} {}", delegation, "✅ this pool".green()),
+                    Some(false) => println!("  Delegated to:   {} {}", delegation, "⚠️  not this pool".yellow()),
+                    None => println!("  Delegated to:   {}", delegation),
+                }
+                println!("  Source:         {}", account.source.dimmed());
+            }
+            for warning in &entry.warnings {
+                println!("  ⚠️  {}", warning.yellow());
+            }
+        }
+    }
+
+    fn records(&self) -> Result<Vec<Value>> {
+        self.addresses.iter().map(|a| Ok(serde_json::to_value(a)?)).collect()
+    }
+}
+
+/// Decode the given addresses, or every configured pledge and reward address
+/// when none are given, and look up their stake accounts.
+pub async fn inspect(
+    addresses: Vec<Address>,
+    offline: bool,
+    provider: Option<String>,
+    output: OutputFormat,
+    config: &Config,
+) -> Result<()> {
+    let mut entries: Vec<(Option<String>, Address, Option<&PoolId>)> = addresses.into_iter()
+        .map(|address| (None, address, None))
+        .collect();
+
+    if entries.is_empty() {
+        for pool in &config.pools {
+            for (role, text) in [("pledge", &pool.pledge_address), ("reward", &pool.reward_address)] {
+                if text.is_empty() {
+                    continue;
+                }
+                let address = Address::parse(text).map_err(|e| CliError::Config(format!(
+                    "{} {} address: {:#}", pool.ticker, role, e
+                )))?;
+                entries.push((Some(format!("{} {}", pool.ticker, role)), address, Some(&pool.pool_id)));
+            }
+        }
+        if entries.is_empty() {
+            return Err(CliError::Config("No addresses given and no pledge or reward addresses configured".to_string()).into());
+        }
+    }
+
+    let chain = if offline {
+        None
+    } else {
+        let chain = ProviderChain::from_config(config, provider.as_deref())?;
+        progress!("{}", format!("Providers: {}", chain.names().join(" → ")).dimmed());
+        Some(chain)
+    };
+
+    let mainnet = config.cardano.network == "mainnet";
+    let mut failures = 0;
+    let mut inspected = Vec::new();
+    for (label, decoded, pool_id) in entries {
+        let mut warnings = Vec::new();
+        if decoded.is_mainnet() != mainnet {
+            warnings.push(format!("address is not for the configured network ({})", config.cardano.network));
+        }
+
+        let mut account = None;
+        if let (Some(chain), Some(stake_address)) = (&chain, &decoded.stake_address) {
+            match chain.account_info(stake_address).await {
+                Ok(info) => account = Some(info),
+                Err(e) => {
+                    failures += 1;
+                    warnings.push(format!("account lookup failed: {}", e));
+                }
+            }
+        } else if decoded.kind == AddressKind::Pointer {
+            warnings.push("pointer addresses are deprecated - the stake credential cannot be looked up".to_string());
+        }
+
+        let delegated_to_pool = match (&account, pool_id) {
+            (Some(account), Some(pool_id)) => Some(
+                account.delegated_pool.as_deref().and_then(|d| d.parse::<PoolId>().ok()).as_ref() == Some(pool_id)
+            ),
+            _ => None,
+        };
+
+        inspected.push(InspectedAddress { label, decoded, account, delegated_to_pool, warnings });
+    }
+
+    let report = AddressReport {
+        network: config.cardano.network.clone(),
+        addresses: inspected,
+    };
+    render(&report, output)?;
+
+    if failures > 0 {
+        return Err(CliError::CheckFailed(format!("{} account lookup(s) failed", failures)).into());
+    }
+    Ok(())
+}
+
+fn describe_kind(address: &Address) -> String {
+    let credential = |c: &Option<Credential>| match c {
+        Some(Credential::Key(_)) => "key",
+        Some(Credential::Script(_)) => "script",
+        None => "none",
+    };
+    match address.kind {
+        AddressKind::Base => format!("base (payment {}, stake {})", credential(&address.payment), credential(&address.stake)),
+        AddressKind::Pointer => format!("pointer (payment {})", credential(&address.payment)),
+        AddressKind::Enterprise => format!("enterprise (payment {}, no stake rights)", credential(&address.payment)),
+        AddressKind::Reward => format!("reward (stake {})", credential(&address.stake)),
+        AddressKind::Byron => "byron (bootstrap, no stake rights)".to_string(),
+    }
+}
+
+fn describe_network(address: &Address) -> String {
+    match (address.network_id, address.protocol_magic) {
+        (Some(1), _) => "mainnet (id 1)".to_string(),
+        (Some(id), _) => format!("testnet (id {})", id),
+        (None, Some(magic)) => format!("testnet (protocol magic {})", magic),
+        (None, None) => "mainnet".to_string(),
+    }
+}
+
+fn describe_credential(credential: &Credential) -> String {
+    match credential {
+        Credential::Key(hash) => format!("key {}", hash),
+        Credential::Script(hash) => format!("script {}", hash),
+    }
+}
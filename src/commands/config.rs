@@ -0,0 +1,93 @@
+use anyhow::Result;
+use colored::*;
+use std::path::{Path, PathBuf};
+use crate::utils::config::{self, Config};
+use crate::utils::error::CliError;
+use crate::utils::output::{self, render, OutputFormat};
+use crate::utils::validation;
+
+/// Write a starter config. Defaults to the user config path so the tool
+/// works from any directory, including cron.
+pub fn init(path: Option<PathBuf>, force: bool) -> Result<()> {
+    let target = match path {
+        Some(path) => path,
+        None => config::user_config_path().ok_or_else(|| {
+            CliError::Config("Cannot determine the user config directory - set $HOME or pass --path".to_string())
+        })?,
+    };
+
+    if target.exists() && !force {
+        return Err(CliError::Config(format!(
+            "{} already exists - pass --force to overwrite it", target.display()
+        )).into());
+    }
+
+    if let Some(parent) = target.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let content = toml::to_string_pretty(&Config::default())?;
+    std::fs::write(&target, content)?;
+
+    println!("{} Created config file: {}", "✅".green(), target.display());
+    println!("Edit the pool IDs, node paths and API keys before running other commands.");
+    Ok(())
+}
+
+/// Show which files make up the effective configuration.
+pub fn show_paths(explicit: Option<&Path>) -> Result<()> {
+    println!("{}", "📂 Config files (lowest precedence first)".blue().bold());
+
+    let layers = Config::layers(explicit)?;
+    if explicit.is_none() {
+        let mut candidates = vec![config::system_config_path()];
+        candidates.extend(config::user_config_path());
+        candidates.push(config::project_config_path());
+        for path in candidates {
+            let state = if layers.contains(&path) { "loaded".green() } else { "not found".dimmed() };
+            println!("  • {} ({})", path.display(), state);
+        }
+    } else {
+        for path in &layers {
+            println!("  • {} ({})", path.display(), "loaded".green());
+        }
+    }
+
+    let overrides: Vec<String> = std::env::vars()
+        .map(|(name, _)| name)
+        .filter(|name| name.starts_with(config::ENV_PREFIX) && !config::RESERVED_ENV.contains(&name.as_str()))
+        .collect();
+    if !overrides.is_empty() {
+        println!("\n{}", "🌱 Environment overrides".blue().bold());
+        for name in overrides {
+            println!("  • {}", name);
+        }
+    }
+    Ok(())
+}
+
+/// Report every problem in the effective configuration, once per requested
+/// profile. Warnings fail the command only with `--strict`.
+pub fn validate(explicit: Option<&Path>, profiles: &[Option<String>], strict: bool, output: OutputFormat) -> Result<()> {
+    let mut errors = 0;
+    let mut warnings = 0;
+
+    for profile in profiles {
+        let (config, layers) = Config::load_unvalidated(explicit, profile.as_deref())?;
+        let report = validation::validate_layers(&config, &layers);
+
+        output::set_profile(config.profile.as_deref(), &config.cardano.network);
+        render(&report, output)?;
+
+        errors += report.errors();
+        warnings += report.warnings();
+    }
+
+    let failing = if strict { errors + warnings } else { errors };
+    if failing > 0 {
+        return Err(CliError::Config(format!(
+            "Configuration has {} error(s) and {} warning(s)", errors, warnings
+        )).into());
+    }
+    Ok(())
+}
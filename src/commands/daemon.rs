@@ -0,0 +1,415 @@
+use anyhow::{Result, Context};
+use colored::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+use tokio::time::{interval, Duration, MissedTickBehavior};
+use crate::cardano::blockfrost::BlockfrostClient;
+use crate::cardano::chain_time::ChainTime;
+use crate::cardano::provider::{ChainDataProvider, ProviderChain};
+use crate::commands::health_check::collect_health_sample;
+use crate::commands::monitoring::record_pool_snapshot;
+use crate::commands::pledge::{check_pool_pledge, PledgeWatch};
+use crate::storage::history::{HistoryStore, PoolSnapshot, RewardsRecord};
+use crate::utils::alerts::send_webhook;
+use crate::utils::config::Config;
+use crate::utils::error::CliError;
+use crate::utils::output::{render, OutputFormat, Render};
+use crate::progress;
+
+const JOB_TIP: &str = "tip";
+const JOB_SNAPSHOT: &str = "pool_snapshot";
+const JOB_REWARDS: &str = "rewards";
+const JOB_HEALTH: &str = "health";
+const JOB_PLEDGE: &str = "pledge";
+const JOB_BACKFILL: &str = "backfill";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DaemonStatus {
+    pub pid: u32,
+    pub state: String,
+    pub started_at: String,
+    pub updated_at: String,
+    pub epoch: Option<u64>,
+    pub slot: Option<u64>,
+    pub slot_in_epoch: Option<u64>,
+    pub jobs: BTreeMap<String, JobStatus>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct JobStatus {
+    pub runs: u64,
+    pub last_run: Option<String>,
+    pub last_success: Option<String>,
+    pub last_error: Option<String>,
+}
+
+impl DaemonStatus {
+    pub fn read(path: &str) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| CliError::NotFound(format!("No daemon status file at {}: {}", path, e)))?;
+        serde_json::from_str(&content).context("Failed to parse daemon status file")
+    }
+
+    /// Write the status file, logging failures: a full disk or a permission
+    /// problem must not stop collection.
+    fn save(&mut self, path: &str) {
+        if let Err(e) = self.write(path) {
+            log::error!("Failed to write daemon status file {}: {:#}", path, e);
+        }
+    }
+
+    fn write(&mut self, path: &str) -> Result<()> {
+        self.updated_at = chrono::Utc::now().to_rfc3339();
+
+        if let Some(parent) = Path::new(path).parent() {
+            if !parent.as_os_str().is_empty() {
+                std::fs::create_dir_all(parent)?;
+            }
+        }
+
+        // Write then rename so readers never see a partial file
+        let tmp = format!("{}.tmp", path);
+        std::fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    fn record(&mut self, job: &str, result: &Result<()>) {
+        let now = chrono::Utc::now().to_rfc3339();
+        let entry = self.jobs.entry(job.to_string()).or_default();
+        entry.runs += 1;
+        entry.last_run = Some(now.clone());
+
+        match result {
+            Ok(()) => {
+                entry.last_success = Some(now);
+                entry.last_error = None;
+            }
+            Err(e) => {
+                log::error!("Job {} failed: {:#}", job, e);
+                entry.last_error = Some(e.to_string());
+            }
+        }
+    }
+}
+
+pub async fn execute(config: &Config) -> Result<()> {
+    println!("{}", "🛰️  Starting Collector Daemon".blue().bold());
+    println!("{}", "=".repeat(50).blue());
+
+    let store = HistoryStore::from_config(config)?
+        .context("The daemon needs history storage - set [storage] enabled = true")?;
+    let chain = ProviderChain::from_config(config, None)?;
+    let blockfrost = BlockfrostClient::new(config);
+    let chain_time = ChainTime::load(&config.cardano)?;
+    let status_file = config.daemon.status_file.clone();
+
+    let mut status = DaemonStatus {
+        pid: std::process::id(),
+        state: "running".to_string(),
+        started_at: chrono::Utc::now().to_rfc3339(),
+        ..Default::default()
+    };
+    status.save(&status_file);
+    println!("📄 Status file: {}", status_file);
+
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
+
+    // Backfill before entering the schedule so analytics has history right
+    // away; it can take a while, so a shutdown request interrupts it
+    if let Some(blockfrost) = &blockfrost {
+        tokio::select! {
+            _ = &mut shutdown => {
+                println!("\n{}", "🛑 Shutdown requested during backfill, stopping daemon...".yellow());
+                status.state = "stopped".to_string();
+                status.save(&status_file);
+                return Ok(());
+            }
+            result = backfill(&store, blockfrost, config) => {
+                status.record(JOB_BACKFILL, &result);
+                status.save(&status_file);
+            }
+        }
+    } else {
+        println!("⚠️  No Blockfrost configuration - skipping backfill");
+    }
+
+    let mut tip_timer = interval(Duration::from_secs(config.daemon.tip_interval_seconds.max(1)));
+    let mut health_timer = interval(Duration::from_secs(config.monitoring.check_interval_seconds.max(1)));
+    tip_timer.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    health_timer.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+    let mut snapshot_epoch: Option<u64> = None;
+    let mut rewards_epoch: Option<u64> = None;
+    let mut pledge_watch = PledgeWatch::default();
+
+    println!("{}", "✅ Daemon running (SIGTERM or Ctrl+C to stop)".green());
+
+    loop {
+        tokio::select! {
+            _ = &mut shutdown => {
+                println!("\n{}", "🛑 Shutdown requested, stopping daemon...".yellow());
+                break;
+            }
+            _ = tip_timer.tick() => {
+                let result = match chain.tip().await {
+                    Ok(tip) => {
+                        status.epoch = Some(tip.epoch);
+                        status.slot = Some(tip.slot);
+                        status.slot_in_epoch = tip.slot_in_epoch;
+                        Ok(())
+                    }
+                    Err(e) => Err(e),
+                };
+                status.record(JOB_TIP, &result);
+
+                if let Some(epoch) = status.epoch {
+                    if snapshot_epoch != Some(epoch) {
+                        let result = snapshot_pools(&store, &chain, config).await;
+                        if result.is_ok() {
+                            snapshot_epoch = Some(epoch);
+                        }
+                        status.record(JOB_SNAPSHOT, &result);
+                    }
+                }
+
+                if let (Some(epoch), Some(blockfrost)) = (status.epoch, &blockfrost) {
+                    let past_calculation = status.slot_in_epoch.is_some_and(|s| s >= chain_time.reward_calculation_slot());
+                    if past_calculation && rewards_epoch != Some(epoch) {
+                        let result = collect_rewards(&store, blockfrost, config).await;
+                        if result.is_ok() {
+                            rewards_epoch = Some(epoch);
+                        }
+                        status.record(JOB_REWARDS, &result);
+                    }
+                }
+
+                status.save(&status_file);
+            }
+            _ = health_timer.tick() => {
+                let result = match collect_health_sample(config).await {
+                    Ok(sample) => store.record_health_sample(&sample),
+                    Err(e) => Err(e),
+                };
+                status.record(JOB_HEALTH, &result);
+
+                let result = check_pledge(&chain, config, &mut pledge_watch).await;
+                status.record(JOB_PLEDGE, &result);
+                status.save(&status_file);
+            }
+        }
+    }
+
+    status.state = "stopped".to_string();
+    status.save(&status_file);
+    println!("{}", "✅ Daemon stopped cleanly".green());
+
+    Ok(())
+}
+
+impl Render for DaemonStatus {
+    const SCHEMA: &'static str = "daemon-status";
+
+    fn render_table(&self) {
+        println!("  • State: {}", if self.state == "running" { self.state.green() } else { self.state.yellow() });
+        println!("  • PID: {}", self.pid);
+        println!("  • Started: {}", self.started_at);
+        println!("  • Updated: {}", self.updated_at);
+        if let (Some(epoch), Some(slot)) = (self.epoch, self.slot) {
+            println!("  • Tip: epoch {} slot {}", epoch, slot);
+        }
+
+        println!("\n{}", "📋 Jobs".cyan().bold());
+        for (name, job) in &self.jobs {
+            let state = match &job.last_error {
+                Some(error) => format!("❌ {}", error).red().to_string(),
+                None => "✅".to_string(),
+            };
+            println!("  • {} ({} runs, last {}): {}",
+                     name, job.runs, job.last_run.as_deref().unwrap_or("-"), state);
+        }
+    }
+
+    /// One record per job.
+    fn records(&self) -> Result<Vec<Value>> {
+        self.jobs.iter().map(|(name, job)| {
+            let mut record = serde_json::to_value(job)?;
+            record["job"] = Value::from(name.as_str());
+            record["state"] = Value::from(self.state.as_str());
+            Ok(record)
+        }).collect()
+    }
+}
+
+/// Print the status file written by a running (or stopped) daemon.
+pub async fn show_status(output: OutputFormat, config: &Config) -> Result<()> {
+    let status = DaemonStatus::read(&config.daemon.status_file)?;
+
+    progress!("{}", "🛰️  Collector Daemon Status".blue().bold());
+    progress!("{}", "=".repeat(50).blue());
+    render(&status, output)
+}
+
+async fn snapshot_pools(store: &HistoryStore, chain: &ProviderChain, config: &Config) -> Result<()> {
+    let epoch_info = chain.epoch_info().await?;
+
+    for pool in &config.pools {
+        let pool_info = chain.pool_info(pool.pool_id.as_str()).await?;
+        record_pool_snapshot(store, &epoch_info, &pool_info)?;
+    }
+
+    println!("📸 Recorded pool snapshots for epoch {}", epoch_info.epoch);
+    Ok(())
+}
+
+/// Check every pool's pledge and alert the webhook when one falls below it
+/// or recovers.
+async fn check_pledge(chain: &ProviderChain, config: &Config, watch: &mut PledgeWatch) -> Result<()> {
+    let mut errors = Vec::new();
+    for pool in &config.pools {
+        let report = match check_pool_pledge(chain, config, pool).await {
+            Ok(report) => report,
+            Err(e) => {
+                errors.push(format!("{}: {:#}", pool.ticker, e));
+                continue;
+            }
+        };
+        if let Some(alert) = watch.alert_for(&report) {
+            println!("🔔 {}", alert.message);
+            if let Err(e) = send_webhook(&config.monitoring.alerts, &alert).await {
+                // Not recorded, so the alert is raised again next time
+                errors.push(format!("{}: {:#}", pool.ticker, e));
+                continue;
+            }
+        }
+        watch.record(&report);
+    }
+
+    if !errors.is_empty() {
+        anyhow::bail!("{}", errors.join("; "));
+    }
+    Ok(())
+}
+
+async fn collect_rewards(store: &HistoryStore, blockfrost: &BlockfrostClient, config: &Config) -> Result<()> {
+    for pool in &config.pools {
+        // The most recent entries carry the rewards that were just calculated
+        let history = blockfrost.get_latest_pool_history(pool.pool_id.as_str()).await?;
+        for entry in history.as_array().into_iter().flatten().take(3) {
+            record_history_entry(store, pool.pool_id.as_str(), entry)?;
+        }
+    }
+
+    println!("💰 Updated rewards history");
+    Ok(())
+}
+
+async fn backfill(store: &HistoryStore, blockfrost: &BlockfrostClient, config: &Config) -> Result<()> {
+    let wanted = config.daemon.backfill_epochs;
+    if wanted == 0 {
+        return Ok(());
+    }
+
+    for pool in &config.pools {
+        let existing: HashSet<u64> = store.snapshot_epochs(pool.pool_id.as_str())?.into_iter().collect();
+        let mut filled = 0;
+        let mut seen = 0;
+        let mut page = 1;
+
+        'pages: loop {
+            let history = blockfrost.get_pool_history(pool.pool_id.as_str(), page).await?;
+            let entries = match history.as_array() {
+                Some(entries) if !entries.is_empty() => entries,
+                _ => break,
+            };
+
+            for entry in entries {
+                if seen >= wanted {
+                    break 'pages;
+                }
+                seen += 1;
+
+                let epoch = entry["epoch"].as_u64().unwrap_or(0);
+                if !existing.contains(&epoch) {
+                    record_history_entry(store, pool.pool_id.as_str(), entry)?;
+                    filled += 1;
+                }
+            }
+
+            page += 1;
+        }
+
+        println!("⏪ Backfilled {} epoch(s) for {}", filled, pool.ticker);
+    }
+
+    Ok(())
+}
+
+/// Store one entry of Blockfrost `/pools/{id}/history`.
+fn record_history_entry(store: &HistoryStore, pool_id: &str, entry: &Value) -> Result<()> {
+    let epoch = entry["epoch"].as_u64().context("History entry has no epoch")?;
+    let lovelace = |value: &Value| value.as_str().and_then(|s| s.parse::<u64>().ok());
+
+    let active_stake = lovelace(&entry["active_stake"]);
+    let total_stake = match (active_stake, entry["active_size"].as_f64()) {
+        (Some(stake), Some(size)) if size > 0.0 => Some((stake as f64 / size) as u64),
+        _ => None,
+    };
+    let delegator_count = entry["delegators_count"].as_u64();
+
+    store.record_pool_snapshot(&PoolSnapshot {
+        pool_id: pool_id.to_string(),
+        epoch,
+        live_stake: None,
+        active_stake,
+        total_stake,
+        saturation: None,
+        delegator_count,
+        source: "blockfrost-history".to_string(),
+    })?;
+
+    if let Some(blocks) = entry["blocks"].as_u64() {
+        store.record_blocks(pool_id, epoch, blocks)?;
+    }
+
+    if let (Some(rewards), Some(fees)) = (lovelace(&entry["rewards"]), lovelace(&entry["fees"])) {
+        let total_rewards = rewards as f64 / 1_000_000.0;
+        let pool_rewards = fees as f64 / 1_000_000.0;
+        store.record_rewards(&RewardsRecord {
+            pool_id: pool_id.to_string(),
+            epoch,
+            total_rewards,
+            pool_rewards,
+            delegator_rewards: total_rewards - pool_rewards,
+            fees: 0.0,
+            delegator_count: delegator_count.unwrap_or(0),
+        })?;
+    }
+
+    Ok(())
+}
+
+#[cfg(unix)]
+async fn shutdown_signal() {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    match signal(SignalKind::terminate()) {
+        Ok(mut terminate) => {
+            tokio::select! {
+                _ = terminate.recv() => {}
+                _ = tokio::signal::ctrl_c() => {}
+            }
+        }
+        Err(_) => {
+            let _ = tokio::signal::ctrl_c().await;
+        }
+    }
+}
+
+#[cfg(not(unix))]
+async fn shutdown_signal() {
+    let _ = tokio::signal::ctrl_c().await;
+}
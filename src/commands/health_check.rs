@@ -1,59 +1,207 @@
 use anyhow::Result;
+use crate::cardano::chain_time::ChainTime;
 use crate::cardano::cli::CardanoCli;
 use crate::cardano::blockfrost::BlockfrostClient;
-use crate::utils::config::Config;
+use crate::cardano::kes::{KesParams, KesStatus};
+use crate::commands::kes::check_pool_kes;
+use crate::commands::metadata::verify_pool_metadata;
+use crate::commands::pledge::{check_pool_pledge, PledgeReport};
+use crate::cardano::provider::ProviderChain;
+use crate::commands::relays::check_pool_relays;
+use crate::commands::time::format_duration;
+use crate::commands::verify_sources::verify_sources;
+use crate::cardano::relay::RelayProbe;
+use crate::cardano::node::{CardanoNode, NodeMetrics};
+use crate::storage::history::{HealthSample, HistoryStore};
+use crate::utils::config::{Config, PoolConfig};
+use crate::utils::error::CliError;
+use crate::utils::output::{format_ada, render, render_stream, OutputFormat, Render};
+use crate::progress;
 use colored::*;
+use serde::Serialize;
 
-pub async fn execute(continuous: bool, config: &Config) -> Result<()> {
-    run_health_check(config, continuous, 30, false).await
+pub async fn execute(continuous: bool, interval: u64, export: bool, output: OutputFormat, config: &Config) -> Result<()> {
+    run_health_check(config, continuous, interval, export, output).await
 }
 
-pub async fn run_health_check(config: &Config, continuous: bool, interval: u64, export: bool) -> Result<()> {
-    println!("{}", "🏥 Starting Health Check...".bright_green().bold());
+pub async fn run_health_check(config: &Config, continuous: bool, interval: u64, export: bool, output: OutputFormat) -> Result<()> {
+    progress!("{}", "🏥 Starting Health Check...".bright_green().bold());
+
+    let history = HistoryStore::open_or_warn(config);
 
     loop {
         let health_status = perform_health_check(config).await?;
 
-        display_health_status(&health_status);
+        if continuous {
+            render_stream(&health_status, output)?;
+        } else {
+            render(&health_status, output)?;
+        }
+
+        if let Some(store) = &history {
+            if let Err(e) = store.record_health_sample(&health_status.to_sample()) {
+                log::warn!("Failed to record health sample: {}", e);
+            }
+        }
 
         if export {
             export_prometheus_metrics(&health_status)?;
         }
 
         if !continuous {
+            // Warnings are reported but only failures change the exit status
+            if !health_status.failures.is_empty() {
+                return Err(CliError::CheckFailed(format!(
+                    "Health check failed: {}", health_status.failures.join("; ")
+                )).into());
+            }
             break;
         }
 
-        println!("\n⏰ Waiting {} seconds for next check...", interval);
+        progress!("\n⏰ Waiting {} seconds for next check...", interval);
         tokio::time::sleep(tokio::time::Duration::from_secs(interval)).await;
     }
 
     Ok(())
 }
 
-#[derive(Debug)]
+/// Whether one configured chain data provider answered.
+#[derive(Debug, Serialize)]
+struct ProviderHealth {
+    name: String,
+    connected: bool,
+    error: Option<String>,
+}
+
+/// `overall` is `unhealthy` when there are failures, `partial` when there are
+/// only warnings, and `healthy` otherwise.
+#[derive(Debug, Serialize)]
 struct HealthStatus {
+    overall: String,
+    checked_at: String,
     node_connected: bool,
     node_synced: bool,
     sync_progress: f64,
     current_epoch: u64,
     current_slot: u64,
     blockfrost_connected: bool,
+    providers: Vec<ProviderHealth>,
     pool_active: bool,
     pool_id: Option<String>,
+    kes_remaining_days: Option<f64>,
+    kes_counter_valid: bool,
+    /// Every key has a valid counter and more than the warning threshold left.
+    #[serde(skip)]
+    kes_ok: bool,
+    pledge_met: bool,
+    relays: Vec<RelayProbe>,
+    node_metrics: Option<NodeMetrics>,
+    /// Problems that stop the pool from producing blocks or earning rewards.
+    failures: Vec<String>,
+    warnings: Vec<String>,
+}
+
+/// Run one health check and return it in the form stored in history.
+pub async fn collect_health_sample(config: &Config) -> Result<HealthSample> {
+    let status = perform_health_check(config).await?;
+    Ok(status.to_sample())
+}
+
+impl HealthStatus {
+    fn to_sample(&self) -> HealthSample {
+        HealthSample {
+            recorded_at: chrono::Utc::now().to_rfc3339(),
+            pool_id: self.pool_id.clone(),
+            epoch: self.current_epoch,
+            slot: self.current_slot,
+            node_connected: self.node_connected,
+            node_synced: self.node_synced,
+            sync_progress: self.sync_progress,
+            blockfrost_connected: self.blockfrost_connected,
+            pool_active: self.pool_active,
+            kes_remaining_days: self.kes_remaining_days,
+            warning_count: self.warnings.len() as u64,
+        }
+    }
+
+    fn new() -> Self {
+        HealthStatus {
+            overall: String::new(),
+            checked_at: chrono::Utc::now().to_rfc3339(),
+            node_connected: false,
+            node_synced: false,
+            sync_progress: 0.0,
+            current_epoch: 0,
+            current_slot: 0,
+            blockfrost_connected: false,
+            providers: Vec::new(),
+            pool_active: false,
+            pool_id: None,
+            kes_remaining_days: None,
+            kes_counter_valid: true,
+            kes_ok: true,
+            pledge_met: true,
+            relays: Vec::new(),
+            node_metrics: None,
+            failures: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Fold one pool's pledge in: an unmet pledge on any pool is a failure.
+    fn record_pledge(&mut self, report: &PledgeReport) {
+        match report.status.as_str() {
+            "met" => {}
+            "unknown" => self.warnings.push(format!(
+                "{}: pledge could not be confirmed - known owner stake {} of {} declared",
+                report.ticker,
+                format_ada(report.actual_pledge),
+                format_ada(report.declared_pledge)
+            )),
+            _ => {
+                self.pledge_met = false;
+                self.failures.push(format!(
+                    "CRITICAL: {} owners hold {} of {} declared pledge - the pool earns no rewards if this holds at the epoch {} snapshot",
+                    report.ticker,
+                    format_ada(report.actual_pledge),
+                    format_ada(report.declared_pledge),
+                    report.snapshot.as_ref().map_or("end".to_string(), |s| s.current_epoch.to_string())
+                ));
+            }
+        }
+    }
+
+    /// Fold one pool's KES state in: the key closest to expiry sets
+    /// `kes_remaining_days`, and any invalid counter clears `kes_counter_valid`.
+    fn record_kes(&mut self, ticker: &str, kes: &KesStatus, warning_days: f64) {
+        self.kes_remaining_days = Some(self.kes_remaining_days.map_or(kes.remaining_days, |d| d.min(kes.remaining_days)));
+        self.kes_counter_valid &= kes.counter_valid;
+        self.kes_ok &= kes.counter_valid && !kes.is_expired() && kes.remaining_days >= warning_days;
+
+        if kes.is_expired() {
+            self.failures.push(format!("{}: KES key has expired - the pool cannot forge blocks", ticker));
+        } else if kes.remaining_days < warning_days {
+            self.warnings.push(format!(
+                "{}: KES key expires in {:.1} days ({})",
+                ticker,
+                kes.remaining_days,
+                kes.expiry.format("%Y-%m-%d %H:%M UTC")
+            ));
+        }
+
+        if !kes.counter_valid {
+            self.failures.push(format!(
+                "{}: opcert counter {} does not match on-chain counter {}",
+                ticker,
+                kes.local_counter,
+                kes.on_chain_counter.unwrap_or(0)
+            ));
+        }
+    }
 }
 
 async fn perform_health_check(config: &Config) -> Result<HealthStatus> {
-    let mut status = HealthStatus {
-        node_connected: false,
-        node_synced: false,
-        sync_progress: 0.0,
-        current_epoch: 0,
-        current_slot: 0,
-        blockfrost_connected: false,
-        pool_active: false,
-        pool_id: None,
-    };
+    let mut status = HealthStatus::new();
 
     // Check Cardano Node Connection
     let cli = CardanoCli::new(config);
@@ -69,13 +217,59 @@ async fn perform_health_check(config: &Config) -> Result<HealthStatus> {
             status.current_epoch = tip["epoch"].as_u64().unwrap_or(0);
             status.current_slot = tip["slot"].as_u64().unwrap_or(0);
 
-            println!("✅ Node connection: {}", "OK".green());
-            println!("📊 Sync progress: {:.2}%", status.sync_progress);
-            println!("📅 Current epoch: {}", status.current_epoch);
-            println!("🎰 Current slot: {}", status.current_slot);
+            progress!("✅ Node connection: {}", "OK".green());
+            progress!("📊 Sync progress: {:.2}%", status.sync_progress);
+            progress!("📅 Current epoch: {}", status.current_epoch);
+            progress!("🎰 Current slot: {}", status.current_slot);
+            if !status.node_synced {
+                status.failures.push(format!("Node is not synced ({:.2}%)", status.sync_progress));
+            }
         },
         Err(e) => {
-            println!("❌ Node connection: {} - {}", "FAILED".red(), e);
+            progress!("❌ Node connection: {} - {}", "FAILED".red(), e);
+        }
+    }
+
+    match ChainTime::load(&config.cardano) {
+        Ok(chain_time) => {
+            let now = chrono::Utc::now();
+            if !status.node_connected {
+                // Fall back to the wall clock so epoch-based checks still make sense
+                status.current_slot = chain_time.time_to_slot(now).unwrap_or(0);
+                status.current_epoch = chain_time.epoch_of_slot(status.current_slot).0;
+                progress!("📅 Estimated epoch (wall clock): {}", status.current_epoch);
+            }
+            progress!("⏳ Next epoch in {}", format_duration(chain_time.time_until_next_epoch(now)));
+        }
+        Err(e) => progress!("⚠️  Chain time unavailable: {}", e),
+    }
+
+    // Scrape the node's own metrics endpoint
+    match CardanoNode::new(config).get_metrics().await {
+        Ok(metrics) => {
+            progress!("✅ Node metrics: {}", "OK".green());
+            if let Some(peers) = metrics.connected_peers {
+                progress!("🌐 Connected peers: {}", peers);
+                if peers == 0 {
+                    status.warnings.push("Node has no connected peers".to_string());
+                }
+            }
+            if let Some(txs) = metrics.mempool_txs {
+                progress!("📥 Mempool transactions: {}", txs);
+            }
+            if let Some(forged) = metrics.blocks_forged {
+                progress!("⛏️  Blocks forged: {}", forged);
+            }
+            if let Some(missed) = metrics.missed_slots {
+                progress!("🎯 Missed slots: {}", missed);
+                if missed > config.monitoring.alerts.missed_slots_threshold {
+                    status.warnings.push(format!("Node has missed {} leader checks since start", missed));
+                }
+            }
+            status.node_metrics = Some(metrics);
+        }
+        Err(e) => {
+            progress!("⚠️  Node metrics: {} - {}", "UNAVAILABLE".yellow(), e);
         }
     }
 
@@ -84,45 +278,205 @@ async fn perform_health_check(config: &Config) -> Result<HealthStatus> {
         match blockfrost_client.get_network_info().await {
             Ok(_) => {
                 status.blockfrost_connected = true;
-                println!("✅ Blockfrost API: {}", "OK".green());
+                progress!("✅ Blockfrost API: {}", "OK".green());
             },
             Err(e) => {
-                println!("❌ Blockfrost API: {} - {}", "FAILED".red(), e);
+                progress!("❌ Blockfrost API: {} - {}", "FAILED".red(), e);
             }
         }
     }
 
+    // Only the providers in [providers] order decide whether data is reachable
+    let chain = ProviderChain::from_config(config, None);
+    match &chain {
+        Ok(chain) => {
+            for provider in chain.providers() {
+                let result = provider.tip().await;
+                if let Err(e) = &result {
+                    progress!("⚠️  Provider {}: {} - {}", provider.name(), "UNREACHABLE".yellow(), e);
+                    status.warnings.push(format!("Provider {} is unreachable", provider.name()));
+                }
+                status.providers.push(ProviderHealth {
+                    name: provider.name().to_string(),
+                    connected: result.is_ok(),
+                    error: result.err().map(|e| e.to_string()),
+                });
+            }
+            if !status.providers.iter().any(|p| p.connected) {
+                status.failures.push("No configured chain data provider answered".to_string());
+            }
+        }
+        Err(e) => status.failures.push(format!("No chain data providers: {}", e)),
+    }
+
+    // Cross-check every configured data source against the others
+    let pool_id = config.pools.first().map(|p| p.pool_id.as_str());
+    match verify_sources(config, pool_id).await {
+        Ok(report) if report.responding() < 2 => {
+            progress!("🔀 Source consistency: {} (fewer than two sources answered)", "SKIPPED".yellow());
+        }
+        Ok(report) if report.is_consistent() => {
+            progress!("✅ Source consistency: {} ({} sources agree)", "OK".green(), report.responding());
+        }
+        Ok(report) => {
+            progress!("⚠️  Source consistency: {}", "DIVERGED".yellow());
+            for divergence in report.divergences {
+                status.warnings.push(match divergence.suspect {
+                    Some(suspect) => format!("Data sources diverge ({}): {} - {} is likely wrong", divergence.check, divergence.detail, suspect),
+                    None => format!("Data sources diverge ({}): {}", divergence.check, divergence.detail),
+                });
+            }
+        }
+        Err(e) => {
+            progress!("❌ Source consistency: {} - {}", "FAILED".red(), e);
+        }
+    }
+
     // Check Pool Status (if configured)
     if !config.pools.is_empty() {
         let pool = &config.pools[0]; // Check first pool
-        status.pool_id = Some(pool.pool_id.clone());
+        status.pool_id = Some(pool.pool_id.to_string());
 
         if status.node_connected {
-            match cli.query_pool_params(&pool.pool_id).await {
+            match cli.query_pool_params(pool.pool_id.as_str()).await {
                 Ok(_) => {
                     status.pool_active = true;
-                    println!("✅ Pool {} ({}): {}", pool.ticker, pool.pool_id, "ACTIVE".green());
+                    progress!("✅ Pool {} ({}): {}", pool.ticker, pool.pool_id, "ACTIVE".green());
                 },
                 Err(_) => {
-                    println!("❌ Pool {} ({}): {}", pool.ticker, pool.pool_id, "INACTIVE".red());
+                    progress!("❌ Pool {} ({}): {}", pool.ticker, pool.pool_id, "INACTIVE".red());
+                }
+            }
+
+            check_kes(config, &cli, &mut status).await;
+        }
+
+        match verify_pool_metadata(config, pool, None).await {
+            Ok(report) if report.is_valid() => {
+                progress!("✅ Pool metadata: {}", "OK".green());
+            }
+            Ok(report) => {
+                progress!("⚠️  Pool metadata: {}", "INVALID".yellow());
+                if !report.hash_matches {
+                    status.warnings.push(format!(
+                        "Metadata hash mismatch: registered {}, served {}",
+                        report.anchor.hash, report.computed_hash
+                    ));
+                }
+                status.warnings.extend(report.issues.into_iter().map(|i| format!("Metadata: {}", i)));
+            }
+            Err(e) => {
+                progress!("❌ Pool metadata: {} - {}", "FAILED".red(), e);
+            }
+        }
+
+        // Without providers the failure is already recorded above
+        if let Ok(chain) = &chain {
+            check_pledge(config, chain, &mut status).await;
+        }
+
+        match check_pool_relays(config, pool.pool_id.as_str()).await {
+            Ok(relays) => {
+                let healthy = relays.iter().filter(|r| r.is_healthy()).count();
+                progress!("🛰️  Relays healthy: {}/{}", healthy, relays.len());
+                if healthy == 0 && !relays.is_empty() {
+                    status.failures.push("No relay is reachable".to_string());
+                }
+                for relay in relays.iter().filter(|r| !r.is_healthy()) {
+                    status.warnings.push(format!(
+                        "Relay {} ({}) is unhealthy: {}",
+                        relay.relay,
+                        relay.endpoint.map_or("-".to_string(), |e| e.to_string()),
+                        relay.error.as_deref().unwrap_or("handshake failed or wrong network")
+                    ));
                 }
+                if relays.is_empty() {
+                    status.warnings.push("No relays registered for the pool".to_string());
+                }
+                status.relays = relays;
+            }
+            Err(e) => {
+                progress!("❌ Relay check: {} - {}", "FAILED".red(), e);
             }
         }
     }
 
+    status.overall = if !status.failures.is_empty() {
+        "unhealthy"
+    } else if !status.warnings.is_empty() {
+        "partial"
+    } else {
+        "healthy"
+    }.to_string();
+
     Ok(status)
 }
 
+/// Check the pledge of every configured pool.
+async fn check_pledge(config: &Config, chain: &ProviderChain, status: &mut HealthStatus) {
+    for pool in &config.pools {
+        match check_pool_pledge(chain, config, pool).await {
+            Ok(report) => {
+                match report.status.as_str() {
+                    "met" => progress!("✅ {} pledge: {}", pool.ticker, "MET".green()),
+                    "unknown" => progress!("⚠️  {} pledge: {}", pool.ticker, "UNKNOWN".yellow()),
+                    _ => progress!("🚨 {} pledge: {}", pool.ticker, "UNMET".red()),
+                }
+                status.record_pledge(&report);
+            }
+            Err(e) => {
+                progress!("❌ {} pledge check: {} - {}", pool.ticker, "FAILED".red(), e);
+                status.warnings.push(format!("{}: pledge check failed - {:#}", pool.ticker, e));
+            }
+        }
+    }
+}
+
+/// Check the KES key of every pool with an operational certificate.
+async fn check_kes(config: &Config, cli: &CardanoCli, status: &mut HealthStatus) {
+    let pools: Vec<&PoolConfig> = config.pools.iter().filter(|p| p.opcert_file.is_some()).collect();
+    if pools.is_empty() {
+        return;
+    }
+    let params = match KesParams::load(&config.cardano) {
+        Ok(params) => params,
+        Err(e) => {
+            progress!("❌ KES check: {} - {}", "FAILED".red(), e);
+            return;
+        }
+    };
+
+    let warning_days = config.monitoring.alerts.kes_warning_days as f64;
+    for pool in pools {
+        let opcert_file = pool.opcert_file.as_deref().unwrap_or_default();
+        match check_pool_kes(cli, &params, opcert_file).await {
+            Ok(kes) => {
+                status.record_kes(&pool.ticker, &kes, warning_days);
+                progress!("🔑 {} KES periods remaining: {} ({:.1} days)", pool.ticker, kes.remaining_periods, kes.remaining_days);
+            }
+            Err(e) => {
+                progress!("❌ {} KES check: {} - {}", pool.ticker, "FAILED".red(), e);
+            }
+        }
+    }
+}
+
+impl Render for HealthStatus {
+    const SCHEMA: &'static str = "health-check";
+
+    fn render_table(&self) {
+        display_health_status(self);
+    }
+}
+
 fn display_health_status(status: &HealthStatus) {
     println!("\n{}", "📋 HEALTH CHECK SUMMARY".bright_blue().bold());
     println!("{}", "═".repeat(50).blue());
 
-    let overall_health = if status.node_connected && status.node_synced && status.blockfrost_connected {
-        "🟢 HEALTHY".green().bold()
-    } else if status.node_connected || status.blockfrost_connected {
-        "🟡 PARTIAL".yellow().bold()
-    } else {
-        "🔴 UNHEALTHY".red().bold()
+    let overall_health = match status.overall.as_str() {
+        "healthy" => "🟢 HEALTHY".green().bold(),
+        "partial" => "🟡 PARTIAL".yellow().bold(),
+        _ => "🔴 UNHEALTHY".red().bold(),
     };
 
     println!("Overall Status: {}", overall_health);
@@ -131,15 +485,45 @@ fn display_health_status(status: &HealthStatus) {
     println!("  Node Connection: {}", if status.node_connected { "✅" } else { "❌" });
     println!("  Node Synced: {}", if status.node_synced { "✅" } else { "❌" });
     println!("  Blockfrost API: {}", if status.blockfrost_connected { "✅" } else { "❌" });
+    for provider in &status.providers {
+        println!("  Provider {}: {}", provider.name, if provider.connected { "✅" } else { "❌" });
+    }
     if let Some(_pool_id) = &status.pool_id {
         println!("  Pool Active: {}", if status.pool_active { "✅" } else { "❌" });
     }
+    if let Some(days) = status.kes_remaining_days {
+        println!("  KES Key: {} ({:.1} days left)", if status.kes_ok { "✅" } else { "⚠️" }, days);
+    }
+
+    if status.pool_id.is_some() {
+        println!("  Pledge Met: {}", if status.pledge_met { "✅" } else { "🚨" });
+    }
+
+    if !status.relays.is_empty() {
+        let healthy = status.relays.iter().filter(|r| r.is_healthy()).count();
+        println!("  Relays: {} ({}/{} healthy)",
+                 if healthy == status.relays.len() { "✅" } else { "⚠️" }, healthy, status.relays.len());
+    }
+
+    if !status.failures.is_empty() {
+        println!("\n{}", "🚨 Failures:".red().bold());
+        for failure in &status.failures {
+            println!("  • {}", failure.red());
+        }
+    }
+
+    if !status.warnings.is_empty() {
+        println!("\n{}", "⚠️  Warnings:".yellow().bold());
+        for warning in &status.warnings {
+            println!("  • {}", warning.yellow());
+        }
+    }
 
     println!("{}", "═".repeat(50).blue());
 }
 
 fn export_prometheus_metrics(status: &HealthStatus) -> Result<()> {
-    let metrics = format!(
+    let mut metrics = format!(
         "# HELP cardano_node_connected Whether the Cardano node is connected\n\
          # TYPE cardano_node_connected gauge\n\
          cardano_node_connected {}\n\
@@ -158,8 +542,153 @@ fn export_prometheus_metrics(status: &HealthStatus) -> Result<()> {
         if status.blockfrost_connected { 1 } else { 0 }
     );
 
+    if let Some(days) = status.kes_remaining_days {
+        metrics.push_str(&format!(
+            "# HELP cardano_kes_remaining_days Days until the KES key expires\n\
+             # TYPE cardano_kes_remaining_days gauge\n\
+             cardano_kes_remaining_days {}\n",
+            days
+        ));
+    }
+
+    if let Some(node_metrics) = &status.node_metrics {
+        for (name, help, value) in node_metric_samples(node_metrics) {
+            metrics.push_str(&format!(
+                "# HELP {} {}\n# TYPE {} gauge\n{} {}\n",
+                name, help, name, name, value
+            ));
+        }
+    }
+
+    if !status.relays.is_empty() {
+        metrics.push_str("# HELP cardano_relay_up Whether the relay accepted a node-to-node handshake on our network\n");
+        metrics.push_str("# TYPE cardano_relay_up gauge\n");
+        for relay in &status.relays {
+            metrics.push_str(&format!(
+                "cardano_relay_up{{relay=\"{}\",endpoint=\"{}\"}} {}\n",
+                relay.relay,
+                relay.endpoint.map_or("-".to_string(), |e| e.to_string()),
+                if relay.is_healthy() { 1 } else { 0 }
+            ));
+        }
+
+        metrics.push_str("# HELP cardano_relay_latency_ms TCP connect latency to the relay\n");
+        metrics.push_str("# TYPE cardano_relay_latency_ms gauge\n");
+        for relay in &status.relays {
+            if let (Some(endpoint), Some(latency)) = (relay.endpoint, relay.latency_ms) {
+                metrics.push_str(&format!(
+                    "cardano_relay_latency_ms{{relay=\"{}\",endpoint=\"{}\"}} {:.1}\n",
+                    relay.relay, endpoint, latency
+                ));
+            }
+        }
+    }
+
     std::fs::write("cardano_health_metrics.prom", metrics)?;
-    println!("📊 Metrics exported to: cardano_health_metrics.prom");
+    progress!("📊 Metrics exported to: cardano_health_metrics.prom");
 
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Node metrics re-exported under the toolkit's own metric names.
+pub fn node_metric_samples(metrics: &NodeMetrics) -> Vec<(&'static str, &'static str, f64)> {
+    let samples = [
+        ("cardano_node_connected_peers", "Peers connected to the node", metrics.connected_peers.map(|v| v as f64)),
+        ("cardano_node_mempool_txs", "Transactions in the node mempool", metrics.mempool_txs.map(|v| v as f64)),
+        ("cardano_node_mempool_bytes", "Bytes in the node mempool", metrics.mempool_bytes.map(|v| v as f64)),
+        ("cardano_node_remaining_kes_periods", "KES periods left on the node's key", metrics.remaining_kes_periods.map(|v| v as f64)),
+        ("cardano_node_blocks_forged", "Blocks forged since node start", metrics.blocks_forged.map(|v| v as f64)),
+        ("cardano_node_missed_slots", "Leader checks missed since node start", metrics.missed_slots.map(|v| v as f64)),
+        ("cardano_node_chain_density", "Chain density seen by the node", metrics.density),
+    ];
+
+    samples.into_iter()
+        .filter_map(|(name, help, value)| value.map(|v| (name, help, v)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kes(remaining_days: f64, counter_valid: bool) -> KesStatus {
+        KesStatus {
+            current_period: 900,
+            start_period: 838,
+            end_period: 900 + (remaining_days / 1.5) as u64,
+            remaining_periods: (remaining_days / 1.5) as u64,
+            remaining_days,
+            expiry: chrono::Utc::now() + chrono::Duration::seconds((remaining_days * 86_400.0) as i64),
+            local_counter: 4,
+            on_chain_counter: Some(if counter_valid { 4 } else { 7 }),
+            counter_valid,
+        }
+    }
+
+    #[test]
+    fn kes_check_keeps_the_key_closest_to_expiry() {
+        let mut status = HealthStatus::new();
+        status.record_kes("AAA", &kes(60.0, true), 14.0);
+        status.record_kes("BBB", &kes(5.0, true), 14.0);
+        status.record_kes("CCC", &kes(30.0, true), 14.0);
+
+        assert_eq!(status.kes_remaining_days, Some(5.0));
+        assert!(!status.kes_ok);
+        assert_eq!(status.warnings.len(), 1);
+        assert!(status.warnings[0].starts_with("BBB: KES key expires in 5.0 days"), "{}", status.warnings[0]);
+        assert!(status.failures.is_empty());
+    }
+
+    #[test]
+    fn kes_failures_on_any_pool_are_reported() {
+        let mut status = HealthStatus::new();
+        status.record_kes("AAA", &kes(0.0, true), 14.0);
+        status.record_kes("BBB", &kes(60.0, false), 14.0);
+        status.record_kes("CCC", &kes(60.0, true), 14.0);
+
+        assert!(!status.kes_counter_valid);
+        assert!(!status.kes_ok);
+        assert_eq!(status.failures.len(), 2);
+        assert!(status.failures[0].starts_with("AAA: KES key has expired"));
+        assert!(status.failures[1].starts_with("BBB: opcert counter 4"));
+    }
+
+    #[test]
+    fn kes_row_ignores_unrelated_problems() {
+        let mut status = HealthStatus::new();
+        status.failures.push("Node is not synced".to_string());
+        status.warnings.push("Relay r2 unreachable".to_string());
+        status.record_kes("AAA", &kes(60.0, true), 14.0);
+
+        assert!(status.kes_ok);
+    }
+
+    fn pledge(ticker: &str, status: &str) -> PledgeReport {
+        PledgeReport {
+            pool_id: String::new(),
+            ticker: ticker.to_string(),
+            declared_pledge: 1_000_000_000,
+            actual_pledge: if status == "met" { 1_000_000_000 } else { 400_000_000 },
+            shortfall: if status == "met" { 0 } else { 600_000_000 },
+            status: status.to_string(),
+            owners: Vec::new(),
+            snapshot: None,
+            source: "blockfrost".to_string(),
+            warnings: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn unmet_pledge_on_any_pool_is_a_failure() {
+        let mut status = HealthStatus::new();
+        status.record_pledge(&pledge("AAA", "met"));
+        status.record_pledge(&pledge("BBB", "unmet"));
+        status.record_pledge(&pledge("CCC", "unknown"));
+
+        assert!(!status.pledge_met);
+        assert_eq!(status.failures.len(), 1);
+        assert!(status.failures[0].starts_with("CRITICAL: BBB owners hold"), "{}", status.failures[0]);
+        assert_eq!(status.warnings.len(), 1);
+        assert!(status.warnings[0].starts_with("CCC: pledge could not be confirmed"));
+    }
+}
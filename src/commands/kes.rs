@@ -0,0 +1,553 @@
+use anyhow::{Result, Context};
+use colored::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+use tabled::{Table, Tabled};
+use crate::cardano::cli::CardanoCli;
+use crate::cardano::kes::{read_kes_vkey, KesParams, KesStatus, OperationalCertificate};
+use crate::utils::actions::{find_pool, step};
+use crate::utils::config::{Config, PoolConfig};
+use crate::utils::error::CliError;
+use crate::utils::output::{render, OutputFormat, Render};
+use crate::cardano::pool_id::PoolId;
+use crate::progress;
+
+#[derive(Tabled)]
+struct KesRow {
+    #[tabled(rename = "Pool")]
+    ticker: String,
+    #[tabled(rename = "KES Period")]
+    current_period: String,
+    #[tabled(rename = "Opcert Start")]
+    start_period: String,
+    #[tabled(rename = "Remaining")]
+    remaining: String,
+    #[tabled(rename = "Expires")]
+    expiry: String,
+    #[tabled(rename = "Counter (disk/chain)")]
+    counter: String,
+    #[tabled(rename = "Status")]
+    status: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PoolKes {
+    pub pool_id: String,
+    pub ticker: String,
+    /// `ok`, `rotate_soon`, `expired`, `counter_mismatch`, `skipped` or `error`.
+    pub health: String,
+    #[serde(flatten)]
+    pub status: Option<KesStatus>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct KesCheck {
+    pub period_days: f64,
+    pub max_kes_evolutions: u64,
+    pub pools: Vec<PoolKes>,
+}
+
+impl Render for KesCheck {
+    const SCHEMA: &'static str = "kes-status";
+
+    fn render_table(&self) {
+        let mut rows = Vec::new();
+        for pool in &self.pools {
+            match &pool.status {
+                Some(status) => rows.push(build_row(&pool.ticker, &pool.health, status)),
+                None if pool.health == "skipped" => println!("⚠️  {}: no opcert_file configured, skipping", pool.ticker.yellow()),
+                None => println!("❌ {}: {}", pool.ticker.red(), pool.error.as_deref().unwrap_or_default()),
+            }
+        }
+
+        if !rows.is_empty() {
+            println!("\n{}", "📊 KES Summary:".blue().bold());
+            println!("{}", Table::new(rows));
+            println!("\nKES period length: {:.1} days, max evolutions: {}", self.period_days, self.max_kes_evolutions);
+        }
+    }
+
+    fn records(&self) -> Result<Vec<Value>> {
+        self.pools.iter().map(|p| Ok(serde_json::to_value(p)?)).collect()
+    }
+}
+
+pub async fn execute(pool_id: Option<PoolId>, output: OutputFormat, config: &Config) -> Result<()> {
+    progress!("{}", "🔑 KES Key & Operational Certificate Check".blue().bold());
+    progress!("{}", "=".repeat(50).blue());
+
+    let cli = CardanoCli::new(config);
+    let params = KesParams::load(&config.cardano)?;
+    let warning_days = config.monitoring.alerts.kes_warning_days;
+
+    let pools: Vec<_> = config.pools.iter()
+        .filter(|p| pool_id.as_ref().is_none_or(|id| &p.pool_id == id))
+        .collect();
+
+    if pools.is_empty() {
+        return Err(CliError::NotFound("No matching pools configured".to_string()).into());
+    }
+
+    let mut check = KesCheck {
+        period_days: params.period_duration_days(),
+        max_kes_evolutions: params.max_kes_evolutions,
+        pools: Vec::new(),
+    };
+
+    for pool in pools {
+        let mut entry = PoolKes {
+            pool_id: pool.pool_id.to_string(),
+            ticker: pool.ticker.clone(),
+            health: "skipped".to_string(),
+            status: None,
+            error: None,
+        };
+
+        if let Some(opcert_file) = &pool.opcert_file {
+            match check_pool_kes(&cli, &params, opcert_file).await {
+                Ok(status) => {
+                    entry.health = kes_health(&status, warning_days).to_string();
+                    entry.status = Some(status);
+                }
+                Err(e) => {
+                    entry.health = "error".to_string();
+                    entry.error = Some(e.to_string());
+                }
+            }
+        }
+        check.pools.push(entry);
+    }
+
+    render(&check, output)?;
+
+    let failures = check.pools.iter()
+        .filter(|p| matches!(p.health.as_str(), "expired" | "counter_mismatch" | "error"))
+        .count();
+    if failures > 0 {
+        return Err(CliError::CheckFailed(format!("{} pool(s) have an expired or invalid operational certificate", failures)).into());
+    }
+    Ok(())
+}
+
+pub async fn check_pool_kes(cli: &CardanoCli, params: &KesParams, opcert_file: &str) -> Result<KesStatus> {
+    let opcert = OperationalCertificate::from_file(opcert_file)?;
+
+    let tip = cli.query_tip().await?;
+    let tip_slot = tip["slot"].as_u64().context("Tip has no slot")?;
+
+    // The node state counter is only known once the pool has forged a block
+    let on_chain_counter = match cli.query_kes_period_info(opcert_file).await {
+        Ok(info) => info["qKesNodeStateOperationalCertificateNumber"].as_u64(),
+        Err(e) => {
+            log::warn!("kes-period-info unavailable: {}", e);
+            None
+        }
+    };
+
+    Ok(KesStatus::evaluate(params, &opcert, tip_slot, on_chain_counter, chrono::Utc::now()))
+}
+
+fn kes_health(status: &KesStatus, warning_days: u64) -> &'static str {
+    if status.is_expired() {
+        "expired"
+    } else if !status.counter_valid {
+        "counter_mismatch"
+    } else if status.remaining_days < warning_days as f64 {
+        "rotate_soon"
+    } else {
+        "ok"
+    }
+}
+
+fn build_row(ticker: &str, health: &str, status: &KesStatus) -> KesRow {
+    let health = match health {
+        "expired" => "🔴 Expired".red().to_string(),
+        "counter_mismatch" => "🔴 Counter Mismatch".red().to_string(),
+        "rotate_soon" => "🟡 Rotate Soon".yellow().to_string(),
+        _ => "🟢 OK".green().to_string(),
+    };
+
+    KesRow {
+        ticker: ticker.to_string(),
+        current_period: status.current_period.to_string(),
+        start_period: status.start_period.to_string(),
+        remaining: format!("{} periods ({:.1}d)", status.remaining_periods, status.remaining_days),
+        expiry: status.expiry.format("%Y-%m-%d %H:%M UTC").to_string(),
+        counter: format!(
+            "{}/{}",
+            status.local_counter,
+            status.on_chain_counter.map_or("-".to_string(), |c| c.to_string())
+        ),
+        status: health,
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RotationPlan {
+    pool_id: String,
+    kes_period: u64,
+    expected_counter: u64,
+    kes_vkey_file: String,
+    kes_skey_file: String,
+    created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct InstallManifest {
+    backup_dir: String,
+    // (installed path, backup path) pairs; backup is None for files that did not exist
+    files: Vec<(String, Option<String>)>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct KesRotation {
+    pub pool_id: String,
+    pub ticker: String,
+    pub dry_run: bool,
+    pub tip_slot: u64,
+    pub kes_period: u64,
+    pub expected_counter: u64,
+    pub kes_vkey_file: String,
+    pub kes_skey_file: String,
+    pub request_script: String,
+}
+
+impl Render for KesRotation {
+    const SCHEMA: &'static str = "kes-rotate";
+
+    fn render_table(&self) {
+        println!("\n{}", "📝 Next steps:".yellow().bold());
+        println!("  • Copy {} and issue-op-cert.sh to the air-gapped cold machine", self.kes_vkey_file);
+        println!("  • Run issue-op-cert.sh there to sign node.cert");
+        println!("  • Bring node.cert back and run: kes install --cert node.cert");
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReplacedFile {
+    pub target: String,
+    /// Where the previous file was copied; `None` when there was none.
+    pub backup: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct KesInstall {
+    pub pool_id: String,
+    pub ticker: String,
+    pub dry_run: bool,
+    pub kes_period: u64,
+    pub counter: u64,
+    pub backup_dir: String,
+    pub files: Vec<ReplacedFile>,
+    /// Staged files left in place because no install path is configured.
+    pub skipped: Vec<String>,
+}
+
+impl Render for KesInstall {
+    const SCHEMA: &'static str = "kes-install";
+
+    fn render_table(&self) {
+        println!("\n{}", "📝 Next steps:".yellow().bold());
+        println!("  • Restart cardano-node (or send SIGHUP) to load the new KES key");
+        println!("  • Confirm with: kes status");
+        println!("  • If anything is wrong: kes rollback");
+    }
+
+    fn records(&self) -> Result<Vec<Value>> {
+        self.files.iter().map(|f| Ok(serde_json::to_value(f)?)).collect()
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct KesRollback {
+    pub pool_id: String,
+    pub ticker: String,
+    pub dry_run: bool,
+    pub files: Vec<ReplacedFile>,
+}
+
+impl Render for KesRollback {
+    const SCHEMA: &'static str = "kes-rollback";
+
+    fn render_table(&self) {
+        if !self.dry_run {
+            println!("{}", "✅ Previous KES key and certificate restored".green());
+        }
+    }
+
+    fn records(&self) -> Result<Vec<Value>> {
+        self.files.iter().map(|f| Ok(serde_json::to_value(f)?)).collect()
+    }
+}
+
+pub async fn rotate(pool_id: Option<PoolId>, staging_dir: &str, dry_run: bool, output: OutputFormat, config: &Config) -> Result<()> {
+    progress!("{}", "🔄 KES Key Rotation - Step 1: Prepare".blue().bold());
+    progress!("{}", "=".repeat(50).blue());
+    if dry_run {
+        progress!("{}", "🧪 Dry run: no files will be written".yellow());
+    }
+
+    let pool = find_pool(config, pool_id.as_ref())?;
+    let cli = CardanoCli::new(config);
+    let params = KesParams::load(&config.cardano)?;
+
+    let tip = cli.query_tip().await?;
+    let tip_slot = tip["slot"].as_u64().context("Tip has no slot")?;
+    let kes_period = params.period_for_slot(tip_slot);
+    progress!("📍 Tip slot {} → start KES period {}", tip_slot, kes_period);
+
+    let expected_counter = expected_next_counter(&cli, pool).await?;
+    progress!("🔢 Next opcert counter: {}", expected_counter);
+
+    let pool_dir = Path::new(staging_dir).join(&pool.ticker);
+    let vkey_file = pool_dir.join("kes.vkey").to_string_lossy().to_string();
+    let skey_file = pool_dir.join("kes.skey").to_string_lossy().to_string();
+
+    step(dry_run, &format!("create staging directory {}", pool_dir.display()));
+    step(dry_run, &format!("cardano-cli node key-gen-KES --verification-key-file {} --signing-key-file {}", vkey_file, skey_file));
+
+    let plan = RotationPlan {
+        pool_id: pool.pool_id.to_string(),
+        kes_period,
+        expected_counter,
+        kes_vkey_file: vkey_file.clone(),
+        kes_skey_file: skey_file.clone(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+    };
+    let request = issue_request_script(&plan);
+    let request_file = pool_dir.join("issue-op-cert.sh");
+    step(dry_run, &format!("write {}", pool_dir.join("rotation.json").display()));
+    step(dry_run, &format!("write {}", request_file.display()));
+
+    if !dry_run {
+        std::fs::create_dir_all(&pool_dir)?;
+        cli.key_gen_kes(&vkey_file, &skey_file).await?;
+        restrict_permissions(&skey_file)?;
+        std::fs::write(pool_dir.join("rotation.json"), serde_json::to_string_pretty(&plan)?)?;
+        std::fs::write(&request_file, &request)?;
+    }
+
+    render(&KesRotation {
+        pool_id: plan.pool_id,
+        ticker: pool.ticker.clone(),
+        dry_run,
+        tip_slot,
+        kes_period,
+        expected_counter,
+        kes_vkey_file: vkey_file,
+        kes_skey_file: skey_file,
+        request_script: request_file.to_string_lossy().to_string(),
+    }, output)
+}
+
+pub async fn install(
+    pool_id: Option<PoolId>,
+    cert_file: &str,
+    staging_dir: &str,
+    dry_run: bool,
+    output: OutputFormat,
+    config: &Config,
+) -> Result<()> {
+    progress!("{}", "🔄 KES Key Rotation - Step 2: Install".blue().bold());
+    progress!("{}", "=".repeat(50).blue());
+    if dry_run {
+        progress!("{}", "🧪 Dry run: no files will be changed".yellow());
+    }
+
+    let pool = find_pool(config, pool_id.as_ref())?;
+    let pool_dir = Path::new(staging_dir).join(&pool.ticker);
+    let plan: RotationPlan = serde_json::from_str(
+        &std::fs::read_to_string(pool_dir.join("rotation.json"))
+            .context("No staged rotation found - run `kes rotate` first")?,
+    )?;
+
+    let new_cert = OperationalCertificate::from_file(cert_file)?;
+    verify_signed_cert(&new_cert, &plan, pool)?;
+    progress!("✅ Signed certificate matches staged KES key, period {} and counter {}",
+              new_cert.kes_period, new_cert.counter);
+
+    let targets = [
+        (cert_file.to_string(), pool.opcert_file.clone()),
+        (plan.kes_skey_file.clone(), pool.kes_skey_file.clone()),
+        (plan.kes_vkey_file.clone(), pool.kes_vkey_file.clone()),
+    ];
+
+    let backup_dir = pool_dir.join(format!("backup-{}", chrono::Utc::now().format("%Y%m%d%H%M%S")));
+    let mut manifest = InstallManifest {
+        backup_dir: backup_dir.to_string_lossy().to_string(),
+        files: Vec::new(),
+    };
+    let mut skipped = Vec::new();
+
+    let manifest_path = pool_dir.join("install.json");
+    step(dry_run, &format!("create backup directory {}", backup_dir.display()));
+    if !dry_run {
+        std::fs::create_dir_all(&backup_dir)?;
+    }
+
+    for (source, target) in targets.iter() {
+        let target = match target {
+            Some(target) => target,
+            None => {
+                progress!("⚠️  No install path configured for {}, leaving it staged", source);
+                skipped.push(source.clone());
+                continue;
+            }
+        };
+
+        let backup = if Path::new(target).exists() {
+            let name = Path::new(target).file_name().context("Invalid install path")?;
+            let backup = backup_dir.join(name).to_string_lossy().to_string();
+            step(dry_run, &format!("back up {} → {}", target, backup));
+            if !dry_run {
+                std::fs::copy(target, &backup)?;
+            }
+            Some(backup)
+        } else {
+            None
+        };
+
+        // Record the file before replacing it so a failure part-way through
+        // can still be rolled back
+        manifest.files.push((target.clone(), backup));
+        if !dry_run {
+            std::fs::write(&manifest_path, serde_json::to_string_pretty(&manifest)?)?;
+        }
+
+        step(dry_run, &format!("install {} → {}", source, target));
+        if !dry_run {
+            std::fs::copy(source, target)
+                .with_context(|| format!("Failed to install {} - run `kes rollback` to restore", target))?;
+        }
+    }
+
+    if !dry_run {
+        if let Some(opcert_file) = &pool.opcert_file {
+            let installed = OperationalCertificate::from_file(opcert_file)?;
+            if installed.counter != plan.expected_counter {
+                anyhow::bail!("Installed opcert counter {} does not match expected {} - run `kes rollback`",
+                              installed.counter, plan.expected_counter);
+            }
+        }
+    }
+
+    render(&KesInstall {
+        pool_id: pool.pool_id.to_string(),
+        ticker: pool.ticker.clone(),
+        dry_run,
+        kes_period: new_cert.kes_period,
+        counter: new_cert.counter,
+        backup_dir: manifest.backup_dir,
+        files: manifest.files.into_iter().map(|(target, backup)| ReplacedFile { target, backup }).collect(),
+        skipped,
+    }, output)
+}
+
+pub async fn rollback(pool_id: Option<PoolId>, staging_dir: &str, dry_run: bool, output: OutputFormat, config: &Config) -> Result<()> {
+    progress!("{}", "⏪ KES Key Rotation - Rollback".blue().bold());
+    progress!("{}", "=".repeat(50).blue());
+
+    let pool = find_pool(config, pool_id.as_ref())?;
+    let manifest_path = Path::new(staging_dir).join(&pool.ticker).join("install.json");
+    let manifest: InstallManifest = serde_json::from_str(
+        &std::fs::read_to_string(&manifest_path)
+            .context("No installed rotation found to roll back")?,
+    )?;
+
+    for (target, backup) in &manifest.files {
+        match backup {
+            Some(backup) => {
+                step(dry_run, &format!("restore {} → {}", backup, target));
+                if !dry_run {
+                    std::fs::copy(backup, target)?;
+                }
+            }
+            None => {
+                step(dry_run, &format!("remove {}", target));
+                if !dry_run && Path::new(target).exists() {
+                    std::fs::remove_file(target)?;
+                }
+            }
+        }
+    }
+
+    step(dry_run, &format!("remove {}", manifest_path.display()));
+    if !dry_run {
+        std::fs::remove_file(&manifest_path)?;
+    }
+
+    render(&KesRollback {
+        pool_id: pool.pool_id.to_string(),
+        ticker: pool.ticker.clone(),
+        dry_run,
+        files: manifest.files.into_iter().map(|(target, backup)| ReplacedFile { target, backup }).collect(),
+    }, output)
+}
+
+async fn expected_next_counter(cli: &CardanoCli, pool: &PoolConfig) -> Result<u64> {
+    let opcert_file = pool.opcert_file.as_deref()
+        .context("opcert_file must be configured to rotate KES keys")?;
+    let current = OperationalCertificate::from_file(opcert_file)?;
+
+    // Prefer the counter the node has seen on chain; fall back to the local cert
+    match cli.query_kes_period_info(opcert_file).await {
+        Ok(info) => match info["qKesNodeStateOperationalCertificateNumber"].as_u64() {
+            Some(chain) => Ok(chain + 1),
+            None => Ok(current.counter + 1),
+        },
+        Err(_) => Ok(current.counter + 1),
+    }
+}
+
+fn verify_signed_cert(cert: &OperationalCertificate, plan: &RotationPlan, pool: &PoolConfig) -> Result<()> {
+    let staged_vkey = read_kes_vkey(&plan.kes_vkey_file)?;
+    if cert.hot_vkey != staged_vkey {
+        anyhow::bail!("Signed certificate was not issued for the staged KES key");
+    }
+    if cert.kes_period != plan.kes_period {
+        anyhow::bail!("Signed certificate KES period {} does not match planned {}", cert.kes_period, plan.kes_period);
+    }
+    if cert.counter != plan.expected_counter {
+        anyhow::bail!("Signed certificate counter {} does not match expected {}", cert.counter, plan.expected_counter);
+    }
+
+    if let Some(opcert_file) = &pool.opcert_file {
+        let current = OperationalCertificate::from_file(opcert_file)?;
+        if current.cold_vkey != cert.cold_vkey {
+            anyhow::bail!("Signed certificate was issued by a different cold key");
+        }
+    }
+
+    Ok(())
+}
+
+fn issue_request_script(plan: &RotationPlan) -> String {
+    format!(
+        "#!/bin/sh\n\
+         # Operational certificate issuance request for {}\n\
+         # Generated {} - run on the air-gapped cold machine\n\
+         # Expected issue counter after signing: {}\n\
+         set -e\n\
+         cardano-cli node issue-op-cert \\\n  \
+           --kes-verification-key-file kes.vkey \\\n  \
+           --cold-signing-key-file node.skey \\\n  \
+           --operational-certificate-issue-counter node.counter \\\n  \
+           --kes-period {} \\\n  \
+           --out-file node.cert\n",
+        plan.pool_id, plan.created_at, plan.expected_counter, plan.kes_period
+    )
+}
+
+#[cfg(unix)]
+fn restrict_permissions(path: &str) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn restrict_permissions(_path: &str) -> Result<()> {
+    Ok(())
+}
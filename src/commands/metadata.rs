@@ -0,0 +1,151 @@
+use anyhow::Result;
+use colored::*;
+use serde::Serialize;
+use serde_json::Value;
+use crate::cardano::blockfrost::BlockfrostClient;
+use crate::cardano::cli::CardanoCli;
+use crate::cardano::koios::KoiosClient;
+use crate::cardano::metadata::{self, MetadataAnchor, MAX_URL_BYTES};
+use crate::utils::config::{Config, PoolConfig};
+use crate::utils::error::CliError;
+use crate::utils::output::{render, OutputFormat, Render};
+use crate::cardano::pool_id::PoolId;
+use crate::progress;
+
+#[derive(Debug, Serialize)]
+pub struct MetadataReport {
+    pub anchor: MetadataAnchor,
+    pub computed_hash: String,
+    pub hash_matches: bool,
+    pub issues: Vec<String>,
+}
+
+impl MetadataReport {
+    pub fn is_valid(&self) -> bool {
+        self.hash_matches && self.issues.is_empty()
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct PoolMetadata {
+    pub pool_id: String,
+    pub name: String,
+    pub valid: bool,
+    #[serde(flatten)]
+    pub report: Option<MetadataReport>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MetadataCheck {
+    pub pools: Vec<PoolMetadata>,
+}
+
+impl Render for MetadataCheck {
+    const SCHEMA: &'static str = "metadata-verify";
+
+    fn render_table(&self) {
+        for pool in &self.pools {
+            println!("\n🏊 {} ({})", pool.name.cyan(), pool.pool_id.dimmed());
+            match (&pool.report, &pool.error) {
+                (Some(report), _) => display_report(report),
+                (None, error) => println!("  ❌ Verification failed: {}", error.as_deref().unwrap_or_default()),
+            }
+        }
+    }
+
+    fn records(&self) -> Result<Vec<Value>> {
+        self.pools.iter().map(|p| Ok(serde_json::to_value(p)?)).collect()
+    }
+}
+
+pub async fn verify(pool_id: Option<PoolId>, file: Option<String>, output: OutputFormat, config: &Config) -> Result<()> {
+    progress!("{}", "🏷️  Pool Metadata Verification".blue().bold());
+    progress!("{}", "=".repeat(50).blue());
+
+    let pools: Vec<_> = config.pools.iter()
+        .filter(|p| pool_id.as_ref().is_none_or(|id| &p.pool_id == id))
+        .collect();
+
+    if pools.is_empty() {
+        return Err(CliError::NotFound("No matching pools configured".to_string()).into());
+    }
+
+    let mut check = MetadataCheck { pools: Vec::new() };
+    for pool in pools {
+        let (report, error) = match verify_pool_metadata(config, pool, file.as_deref()).await {
+            Ok(report) => (Some(report), None),
+            Err(e) => (None, Some(e.to_string())),
+        };
+        check.pools.push(PoolMetadata {
+            pool_id: pool.pool_id.to_string(),
+            name: pool.name.clone(),
+            valid: report.as_ref().is_some_and(MetadataReport::is_valid),
+            report,
+            error,
+        });
+    }
+
+    render(&check, output)?;
+
+    let failures = check.pools.iter().filter(|p| !p.valid).count();
+    if failures > 0 {
+        return Err(CliError::CheckFailed(format!("Metadata for {} pool(s) is invalid", failures)).into());
+    }
+    Ok(())
+}
+
+/// Fetch the registered metadata for a pool and check hash, schema and limits.
+/// `source` overrides where the document is read from (local file or URL).
+pub async fn verify_pool_metadata(
+    config: &Config,
+    pool: &PoolConfig,
+    source: Option<&str>,
+) -> Result<MetadataReport> {
+    let cli = CardanoCli::new(config);
+    let blockfrost = BlockfrostClient::new(config);
+    let koios = KoiosClient::new(config);
+
+    let anchor = metadata::fetch_anchor(&cli, blockfrost.as_ref(), koios.as_ref(), pool.pool_id.as_str()).await?;
+    let raw = metadata::download(source.unwrap_or(&anchor.url)).await?;
+
+    let computed_hash = metadata::blake2b_256_hex(&raw);
+    let hash_matches = computed_hash.eq_ignore_ascii_case(&anchor.hash);
+
+    let mut issues = metadata::validate_cip6(&raw);
+
+    if anchor.url.len() > MAX_URL_BYTES {
+        issues.push(format!("Registered URL is {} bytes, maximum is {}", anchor.url.len(), MAX_URL_BYTES));
+    }
+
+    if let Ok(json) = serde_json::from_slice::<serde_json::Value>(&raw) {
+        if let Some(ticker) = json["ticker"].as_str() {
+            if ticker != pool.ticker {
+                issues.push(format!("Metadata ticker '{}' differs from configured '{}'", ticker, pool.ticker));
+            }
+        }
+    }
+
+    Ok(MetadataReport { anchor, computed_hash, hash_matches, issues })
+}
+
+fn display_report(report: &MetadataReport) {
+    println!("  • URL: {}", report.anchor.url);
+    println!("  • Registered hash: {}", report.anchor.hash.dimmed());
+    println!("  • Computed hash:   {}", report.computed_hash.dimmed());
+
+    if report.hash_matches {
+        println!("  ✅ Hash: {}", "MATCH".green());
+    } else {
+        println!("  ❌ Hash: {} - re-register the pool certificate or restore the original file", "MISMATCH".red());
+    }
+
+    if report.issues.is_empty() {
+        println!("  ✅ Schema: {}", "VALID".green());
+    } else {
+        println!("  ⚠️  Schema: {} issue(s)", report.issues.len().to_string().yellow());
+        for issue in &report.issues {
+            println!("    • {}", issue.yellow());
+        }
+    }
+}
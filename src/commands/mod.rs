@@ -1,7 +1,17 @@
+pub mod address;
 pub mod analytics;
+pub mod config;
+pub mod daemon;
 pub mod health_check;
+pub mod kes;
+pub mod metadata;
 pub mod monitoring;
+pub mod node;
+pub mod pledge;
 pub mod pool_status;
+pub mod relays;
 pub mod rewards;
 pub mod setup;
 pub mod test_api;
+pub mod time;
+pub mod verify_sources;
@@ -1,31 +1,180 @@
-
 use anyhow::Result;
-use crate::utils::config::Config;
+use serde::Serialize;
+use serde_json::Value;
+use crate::utils::alerts::send_webhook;
+use crate::utils::config::{Config, PoolConfig};
+use crate::utils::error::CliError;
+use crate::utils::output::{format_ada, render, render_stream, OutputFormat, Render};
+use crate::progress;
 use crate::cardano::blockfrost::BlockfrostClient;
+use crate::cardano::cache::{CacheCounters, QueryCache};
+use crate::cardano::provider::{ChainDataProvider, EpochInfo, PoolInfo, ProviderChain};
+use crate::commands::pledge::{check_pool_pledge, PledgeReport, PledgeWatch};
+use crate::commands::relays::check_pool_relays;
+use crate::commands::health_check::node_metric_samples;
+use crate::cardano::node::CardanoNode;
+use crate::storage::history::{HistoryStore, PoolSnapshot};
+use crate::cardano::pool_id::PoolId;
 use tokio::time::{interval, Duration};
 
-pub async fn execute(prometheus: bool, port: u16, config: &Config) -> Result<()> {
-    println!("📊 Monitoring command executed!");
-    println!("  Prometheus enabled: {}", prometheus);
-    println!("  Port: {}", port);
+#[derive(Debug, Serialize)]
+pub struct MonitoringReport {
+    pub checked_at: String,
+    pub providers: Vec<String>,
+    pub network_supply: Option<Value>,
+    pub epoch: Option<EpochInfo>,
+    pub pools: Vec<MonitoredPool>,
+    pub cache: CacheCounters,
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MonitoredPool {
+    pub pool_id: String,
+    pub name: String,
+    pub ticker: String,
+    pub info: Option<PoolInfo>,
+    pub error: Option<String>,
+    pub pledge: Option<PledgeReport>,
+    pub pledge_error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MetricsSnapshot {
+    pub collected_at: String,
+    pub metrics: Vec<Metric>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Metric {
+    pub name: String,
+    pub value: String,
+}
+
+impl Render for MonitoringReport {
+    const SCHEMA: &'static str = "monitor";
+
+    fn render_table(&self) {
+        if let Some(supply) = &self.network_supply {
+            println!("✅ Network Status: Healthy");
+            println!("  📊 Total Supply: {} ADA", supply);
+        }
+        if let Some(epoch) = &self.epoch {
+            println!("✅ Current Epoch: {}", epoch.epoch);
+        }
+        for error in &self.errors {
+            println!("❌ {}", error);
+        }
+
+        for pool in &self.pools {
+            println!("🏊 Checking pool: {} ({})", pool.name, pool.ticker);
+            match (&pool.info, &pool.error) {
+                (Some(info), _) => {
+                    println!("  ✅ Pool Status: {} (via {})",
+                             if info.active { "Active" } else { "Retiring" }, info.source);
+                    if let Some(live_stake) = info.live_stake {
+                        println!("  📊 Live Stake: {} lovelace", live_stake);
+                    }
+                }
+                (None, error) => println!("  ❌ Pool Status: Failed - {}", error.as_deref().unwrap_or("unknown error")),
+            }
+            match (&pool.pledge, &pool.pledge_error) {
+                (Some(pledge), _) => match pledge.status.as_str() {
+                    "met" => println!("  ✅ Pledge: met"),
+                    "unmet" => println!("  🚨 Pledge: UNMET (short by {})", format_ada(pledge.shortfall)),
+                    _ => println!("  ⚠️  Pledge: unconfirmed (short by up to {})", format_ada(pledge.shortfall)),
+                },
+                (None, Some(error)) => println!("  ❌ Pledge Check: Failed - {}", error),
+                (None, None) => {}
+            }
+        }
+
+        println!("🗄️  Cache: {} hits ({} coalesced, {} from disk), {} misses",
+                 self.cache.hits + self.cache.disk_hits, self.cache.coalesced, self.cache.disk_hits, self.cache.misses);
+        println!("🎉 Monitoring check completed!");
+    }
+
+    fn records(&self) -> Result<Vec<Value>> {
+        self.pools.iter().map(|p| Ok(serde_json::to_value(p)?)).collect()
+    }
+}
+
+impl Render for MetricsSnapshot {
+    const SCHEMA: &'static str = "monitor-metrics";
+
+    fn render_table(&self) {
+        println!("📊 Metrics collected:");
+        for metric in &self.metrics {
+            println!("  {} = {}", metric.name, metric.value);
+        }
+    }
+
+    fn records(&self) -> Result<Vec<Value>> {
+        self.metrics.iter().map(|m| Ok(serde_json::to_value(m)?)).collect()
+    }
+}
+
+pub async fn execute(
+    pool_id: Option<PoolId>,
+    continuous: bool,
+    prometheus: bool,
+    port: u16,
+    provider: Option<String>,
+    output: OutputFormat,
+    config: &Config,
+) -> Result<()> {
+    progress!("📊 Monitoring command executed!");
+    progress!("  Prometheus enabled: {}", prometheus);
+    progress!("  Port: {}", port);
+
+    let chain = ProviderChain::from_config(config, provider.as_deref())?;
+    progress!("  Providers: {}", chain.names().join(" → "));
+
+    let pools: Vec<&PoolConfig> = config.pools.iter()
+        .filter(|p| pool_id.as_ref().is_none_or(|id| &p.pool_id == id))
+        .collect();
+    if let (Some(id), true) = (&pool_id, pools.is_empty()) {
+        return Err(CliError::NotFound(format!("Pool {} is not configured in config.toml", id)).into());
+    }
 
     if prometheus {
-        println!("🚀 Starting Prometheus metrics server on port {}", port);
-        start_prometheus_server(port, config).await?;
+        progress!("🚀 Starting Prometheus metrics server on port {}", port);
+        start_prometheus_server(port, &chain, &pools, output, config).await?;
     } else {
-        println!("📈 Running one-time monitoring check...");
-        run_monitoring_check(config).await?;
+        let mut pledge_watch = PledgeWatch::default();
+        loop {
+            progress!("📈 Running monitoring check...");
+            let report = run_monitoring_check(&chain, &pools, config).await;
+            send_pledge_alerts(&report, &mut pledge_watch, config).await;
+            if continuous {
+                render_stream(&report, output)?;
+            } else {
+                render(&report, output)?;
+            }
+
+            if !continuous {
+                let failures = report.errors.len() + report.pools.iter().filter(|p| p.error.is_some()).count();
+                if failures > 0 {
+                    return Err(CliError::CheckFailed(format!("{} monitoring check(s) failed", failures)).into());
+                }
+                break;
+            }
+            tokio::time::sleep(Duration::from_secs(config.monitoring.check_interval_seconds.max(1))).await;
+        }
     }
 
     Ok(())
 }
 
-async fn start_prometheus_server(port: u16, config: &Config) -> Result<()> {
-    let blockfrost = BlockfrostClient::new(config)
-        .ok_or_else(|| anyhow::anyhow!("Blockfrost configuration not found"))?;
-
-    println!("🔧 Prometheus metrics server starting...");
-    println!("📍 Metrics will be available at: http://localhost:{}/metrics", port);
+async fn start_prometheus_server(
+    port: u16,
+    chain: &ProviderChain,
+    pools: &[&PoolConfig],
+    output: OutputFormat,
+    config: &Config,
+) -> Result<()> {
+    progress!("🔧 Prometheus metrics server starting...");
+    progress!("📍 Metrics will be available at: http://localhost:{}/metrics", port);
 
     // Create a simple HTTP server for Prometheus metrics
     let mut interval = interval(Duration::from_secs(30));
@@ -34,105 +183,187 @@ async fn start_prometheus_server(port: u16, config: &Config) -> Result<()> {
         interval.tick().await;
 
         // Collect metrics
-        match collect_metrics(&blockfrost, config).await {
+        match collect_metrics(chain, pools, config).await {
             Ok(metrics) => {
-                println!("📊 Metrics collected:");
-                for (key, value) in metrics {
-                    println!("  {} = {}", key, value);
-                }
+                let snapshot = MetricsSnapshot {
+                    collected_at: chrono::Utc::now().to_rfc3339(),
+                    metrics: metrics.into_iter().map(|(name, value)| Metric { name, value }).collect(),
+                };
+                render_stream(&snapshot, output)?;
             }
             Err(e) => {
-                println!("❌ Error collecting metrics: {}", e);
+                log::error!("Error collecting metrics: {}", e);
+                progress!("❌ Error collecting metrics: {}", e);
             }
         }
 
-        println!("⏰ Next collection in 30 seconds... (Press Ctrl+C to stop)");
+        progress!("⏰ Next collection in 30 seconds... (Press Ctrl+C to stop)");
     }
 }
 
-async fn run_monitoring_check(config: &Config) -> Result<()> {
-    let blockfrost = BlockfrostClient::new(config)
-        .ok_or_else(|| anyhow::anyhow!("Blockfrost configuration not found"))?;
-
-    println!("🔍 Running monitoring checks...");
+async fn run_monitoring_check(chain: &ProviderChain, pools: &[&PoolConfig], config: &Config) -> MonitoringReport {
+    let mut report = MonitoringReport {
+        checked_at: chrono::Utc::now().to_rfc3339(),
+        providers: chain.names().iter().map(|n| n.to_string()).collect(),
+        network_supply: None,
+        epoch: None,
+        pools: Vec::new(),
+        cache: CacheCounters::default(),
+        errors: Vec::new(),
+    };
 
-    // Check network status
-    match blockfrost.get_network_info().await {
-        Ok(info) => {
-            println!("✅ Network Status: Healthy");
-            if let Some(supply) = info.get("supply") {
-                println!("  📊 Total Supply: {} ADA", supply);
-            }
-        }
-        Err(e) => {
-            println!("❌ Network Status: Failed - {}", e);
+    // Network supply is only available from Blockfrost
+    if let Some(blockfrost) = BlockfrostClient::new(config) {
+        match blockfrost.get_network_info().await {
+            Ok(info) => report.network_supply = info.get("supply").cloned(),
+            Err(e) => report.errors.push(format!("Network Status: Failed - {}", e)),
         }
     }
 
     // Check latest epoch
-    match blockfrost.get_latest_epoch().await {
-        Ok(epoch) => {
-            println!("✅ Current Epoch: {}", epoch.get("epoch").unwrap_or(&serde_json::Value::Null));
-        }
-        Err(e) => {
-            println!("❌ Epoch Check: Failed - {}", e);
-        }
+    match chain.epoch_info().await {
+        Ok(epoch) => report.epoch = Some(epoch),
+        Err(e) => report.errors.push(format!("Epoch Check: Failed - {}", e)),
     }
 
+    let history = HistoryStore::open_or_warn(config);
+
     // Check configured pools
-    for pool in &config.pools {
-        println!("🏊 Checking pool: {} ({})", pool.name, pool.ticker);
+    for pool in pools {
+        let mut monitored = MonitoredPool {
+            pool_id: pool.pool_id.to_string(),
+            name: pool.name.clone(),
+            ticker: pool.ticker.clone(),
+            info: None,
+            error: None,
+            pledge: None,
+            pledge_error: None,
+        };
 
-        match blockfrost.get_pool_info(&pool.pool_id).await {
+        match chain.pool_info(pool.pool_id.as_str()).await {
             Ok(pool_info) => {
-                println!("  ✅ Pool Status: Active");
-                if let Some(live_stake) = pool_info.get("live_stake") {
-                    println!("  📊 Live Stake: {} lovelace", live_stake);
+                if let (Some(store), Some(epoch)) = (&history, &report.epoch) {
+                    if let Err(e) = record_pool_snapshot(store, epoch, &pool_info) {
+                        log::warn!("Failed to record pool snapshot: {}", e);
+                    }
                 }
+                monitored.info = Some(pool_info);
             }
-            Err(e) => {
-                println!("  ❌ Pool Status: Failed - {}", e);
-            }
+            Err(e) => monitored.error = Some(e.to_string()),
+        }
+
+        match check_pool_pledge(chain, config, pool).await {
+            Ok(pledge) => monitored.pledge = Some(pledge),
+            Err(e) => monitored.pledge_error = Some(format!("{:#}", e)),
         }
+
+        report.pools.push(monitored);
     }
 
-    println!("🎉 Monitoring check completed!");
-    Ok(())
+    report.cache = QueryCache::shared(config).counters();
+    report
+}
+
+/// Alert the webhook when a pool falls below its pledge or recovers. A failed
+/// delivery is retried on the next check.
+async fn send_pledge_alerts(report: &MonitoringReport, watch: &mut PledgeWatch, config: &Config) {
+    for pledge in report.pools.iter().filter_map(|p| p.pledge.as_ref()) {
+        if let Some(alert) = watch.alert_for(pledge) {
+            progress!("🔔 {}", alert.message);
+            if let Err(e) = send_webhook(&config.monitoring.alerts, &alert).await {
+                log::error!("Failed to send pledge alert: {:#}", e);
+                progress!("❌ Failed to send pledge alert: {}", e);
+                continue;
+            }
+        }
+        watch.record(pledge);
+    }
 }
 
-async fn collect_metrics(blockfrost: &BlockfrostClient, config: &Config) -> Result<Vec<(String, String)>> {
+async fn collect_metrics(chain: &ProviderChain, pools: &[&PoolConfig], config: &Config) -> Result<Vec<(String, String)>> {
     let mut metrics = Vec::new();
 
     // Network metrics
-    if let Ok(network) = blockfrost.get_network_info().await {
-        if let Some(supply) = network.get("supply") {
-            metrics.push(("cardano_total_supply".to_string(), supply.to_string()));
+    if let Some(blockfrost) = BlockfrostClient::new(config) {
+        if let Ok(network) = blockfrost.get_network_info().await {
+            if let Some(supply) = network.get("supply") {
+                metrics.push(("cardano_total_supply".to_string(), supply.to_string()));
+            }
         }
     }
 
     // Epoch metrics
-    if let Ok(epoch) = blockfrost.get_latest_epoch().await {
-        if let Some(epoch_num) = epoch.get("epoch") {
-            metrics.push(("cardano_current_epoch".to_string(), epoch_num.to_string()));
+    if let Ok(epoch) = chain.epoch_info().await {
+        metrics.push(("cardano_current_epoch".to_string(), epoch.epoch.to_string()));
+    }
+
+    // Node metrics
+    if let Ok(node_metrics) = CardanoNode::new(config).get_metrics().await {
+        for (name, _help, value) in node_metric_samples(&node_metrics) {
+            metrics.push((name.to_string(), value.to_string()));
         }
     }
 
     // Pool metrics
-    for pool in &config.pools {
-        if let Ok(pool_info) = blockfrost.get_pool_info(&pool.pool_id).await {
+    for pool in pools {
+        if let Ok(pool_info) = chain.pool_info(pool.pool_id.as_str()).await {
             let pool_prefix = format!("cardano_pool_{}", pool.ticker.to_lowercase());
 
-            if let Some(live_stake) = pool_info.get("live_stake") {
+            if let Some(live_stake) = pool_info.live_stake {
                 metrics.push((format!("{}_live_stake", pool_prefix), live_stake.to_string()));
             }
 
-            if let Some(active_stake) = pool_info.get("active_stake") {
+            if let Some(active_stake) = pool_info.active_stake {
                 metrics.push((format!("{}_active_stake", pool_prefix), active_stake.to_string()));
             }
 
-            metrics.push((format!("{}_status", pool_prefix), "1".to_string()));
+            metrics.push((format!("{}_status", pool_prefix), if pool_info.active { "1" } else { "0" }.to_string()));
+        }
+
+        if let Ok(relays) = check_pool_relays(config, pool.pool_id.as_str()).await {
+            let pool_prefix = format!("cardano_pool_{}", pool.ticker.to_lowercase());
+            for relay in relays {
+                let endpoint = relay.endpoint.map_or(relay.relay.clone(), |e| e.to_string());
+                metrics.push((
+                    format!("{}_relay_up{{endpoint=\"{}\"}}", pool_prefix, endpoint),
+                    if relay.is_healthy() { "1" } else { "0" }.to_string(),
+                ));
+                if let Some(latency) = relay.latency_ms {
+                    metrics.push((
+                        format!("{}_relay_latency_ms{{endpoint=\"{}\"}}", pool_prefix, endpoint),
+                        format!("{:.1}", latency),
+                    ));
+                }
+            }
         }
     }
 
+    // Cache effectiveness
+    let cache = QueryCache::shared(config).counters();
+    metrics.push(("cardano_cache_hits_total".to_string(), cache.hits.to_string()));
+    metrics.push(("cardano_cache_misses_total".to_string(), cache.misses.to_string()));
+    metrics.push(("cardano_cache_coalesced_total".to_string(), cache.coalesced.to_string()));
+    metrics.push(("cardano_cache_disk_hits_total".to_string(), cache.disk_hits.to_string()));
+
     Ok(metrics)
-}
\ No newline at end of file
+}
+
+/// Store a per-epoch snapshot built from provider pool and epoch info.
+pub fn record_pool_snapshot(store: &HistoryStore, epoch: &EpochInfo, pool_info: &PoolInfo) -> Result<()> {
+    store.record_pool_snapshot(&PoolSnapshot {
+        pool_id: pool_info.pool_id.clone(),
+        epoch: epoch.epoch,
+        live_stake: pool_info.live_stake,
+        active_stake: pool_info.active_stake,
+        total_stake: epoch.active_stake,
+        saturation: pool_info.live_saturation,
+        delegator_count: pool_info.live_delegators,
+        source: pool_info.source.clone(),
+    })?;
+
+    if let Some(blocks) = pool_info.blocks_epoch {
+        store.record_blocks(&pool_info.pool_id, epoch.epoch, blocks)?;
+    }
+
+    Ok(())
+}
@@ -1,93 +1,143 @@
-use anyhow::Result;
+use anyhow::{Result, Context};
 use colored::*;
+use serde::Serialize;
 use serde_json::Value;
 
 use crate::cardano::node::CardanoNode;
+use crate::cardano::ogmios::{ChainSyncEvent, OgmiosClient};
 use crate::utils::config::Config;
+use crate::utils::output::{render, render_stream, OutputFormat, Render};
+use crate::progress;
+
+#[derive(Debug, Serialize)]
+pub struct NodeStatus {
+    pub running: bool,
+    pub socket_path: String,
+    /// Node status and Prometheus metrics, when the node answered.
+    pub info: Option<Value>,
+    pub error: Option<String>,
+}
 
-pub async fn status(config: &Config) -> Result<()> {
-    println!("{}", "🔍 Node Status Check".blue().bold());
-    println!("{}", "=".repeat(30).blue());
-
-    let node = CardanoNode::new(config.cardano.node_socket_path.clone());
-
-    match node.get_node_info().await {
-        Ok(info) => {
-            println!("{}", "✅ Node Status: Running".green().bold());
-            display_node_info(&info)?;
-        }
-        Err(e) => {
-            println!("{}", "❌ Node Status: Unreachable".red().bold());
-            println!("Error: {}", e.to_string().red());
-            println!("\n{}", "💡 Troubleshooting:".yellow().bold());
-            println!("  • Check if cardano-node is running");
-            println!("  • Verify socket path: {}", config.cardano.node_socket_path);
-            println!("  • Check network connectivity");
+impl Render for NodeStatus {
+    const SCHEMA: &'static str = "node-status";
+
+    fn render_table(&self) {
+        match (&self.info, &self.error) {
+            (Some(info), _) => {
+                println!("{}", "✅ Node Status: Running".green().bold());
+                display_node_info(info);
+            }
+            (None, error) => {
+                println!("{}", "❌ Node Status: Unreachable".red().bold());
+                println!("Error: {}", error.as_deref().unwrap_or_default().red());
+                println!("\n{}", "💡 Troubleshooting:".yellow().bold());
+                println!("  • Check if cardano-node is running");
+                println!("  • Verify socket path: {}", self.socket_path);
+                println!("  • Check network connectivity");
+            }
         }
     }
-
-    Ok(())
 }
 
-pub async fn start(config: &Config) -> Result<()> {
-    println!("{}", "🚀 Starting Cardano Node".blue().bold());
-    println!("{}", "=".repeat(30).blue());
-
-    // Simulate node start (in real implementation, use system commands)
-    println!("{}", "🔄 Initiating node startup sequence...".cyan());
-    tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-
-    println!("{}", "✅ Node startup initiated".green());
-    println!("Socket path: {}", config.cardano.node_socket_path.dimmed());
-    println!("Network: {}", config.cardano.network.dimmed());
-
-    println!("\n{}", "📝 Next steps:".yellow().bold());
-    println!("  • Monitor node sync status");
-    println!("  • Check logs for any errors");
-    println!("  • Verify connectivity with other commands");
-
-    Ok(())
+/// One chain-sync event seen by `node follow`.
+#[derive(Debug, Serialize)]
+pub struct ChainEvent {
+    /// `roll_forward` or `roll_backward`.
+    pub event: String,
+    /// Block slot, or the rollback point; `None` for origin.
+    pub slot: Option<u64>,
+    pub height: Option<u64>,
+    pub block_id: Option<String>,
+    pub tx_count: Option<usize>,
+    pub tip_slot: Option<u64>,
+    pub tip_height: Option<u64>,
 }
 
-pub async fn stop(config: &Config) -> Result<()> {
-    println!("{}", "🛑 Stopping Cardano Node".blue().bold());
-    println!("{}", "=".repeat(30).blue());
+impl Render for ChainEvent {
+    const SCHEMA: &'static str = "node-follow";
 
-    // Simulate node stop (in real implementation, use system commands)
-    println!("{}", "🔄 Initiating graceful shutdown...".cyan());
-    tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+    fn render_table(&self) {
+        let or_dash = |value: Option<u64>| value.map_or("-".to_string(), |v| v.to_string());
 
-    println!("{}", "✅ Node shutdown initiated".green());
-    println!("Socket: {}", config.cardano.node_socket_path.dimmed());
-
-    println!("\n{}", "⚠️  Note:".yellow().bold());
-    println!("  • Node may take a few moments to fully stop");
-    println!("  • Check system processes to confirm shutdown");
+        if self.event == "roll_backward" {
+            println!("  • {} rolled back to slot {} (tip slot {})",
+                     "◀".yellow(), self.slot.map_or("origin".to_string(), |s| s.to_string()), or_dash(self.tip_slot));
+            return;
+        }
 
-    Ok(())
+        let behind = self.tip_height.zip(self.height).map_or(0, |(tip, height)| tip.saturating_sub(height));
+        println!("  • {} slot {} height {} {} ({} txs{})",
+                 "▶".green(),
+                 or_dash(self.slot),
+                 or_dash(self.height),
+                 self.block_id.as_deref().unwrap_or("-").dimmed(),
+                 self.tx_count.unwrap_or(0),
+                 if behind > 0 { format!(", {} behind tip", behind) } else { String::new() });
+    }
 }
 
-pub async fn restart(config: &Config) -> Result<()> {
-    println!("{}", "🔄 Restarting Cardano Node".blue().bold());
-    println!("{}", "=".repeat(30).blue());
-
-    // Stop first
-    println!("{}", "🛑 Stopping node...".cyan());
-    tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
-    println!("{}", "✅ Node stopped".green());
-
-    // Start again
-    println!("{}", "🚀 Starting node...".cyan());
-    tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-    println!("{}", "✅ Node restarted successfully".green());
+pub async fn status(output: OutputFormat, config: &Config) -> Result<()> {
+    progress!("{}", "🔍 Node Status Check".blue().bold());
+    progress!("{}", "=".repeat(30).blue());
+
+    let node = CardanoNode::new(config);
+    let (info, error) = match node.get_node_info().await {
+        Ok(info) => (Some(info), None),
+        Err(e) => (None, Some(e.to_string())),
+    };
+
+    render(&NodeStatus {
+        running: info.is_some(),
+        socket_path: config.cardano.node_socket_path.clone(),
+        info,
+        error,
+    }, output)
+}
 
-    println!("Socket: {}", config.cardano.node_socket_path.dimmed());
-    println!("Network: {}", config.cardano.network.dimmed());
+pub async fn follow(blocks: Option<u64>, output: OutputFormat, config: &Config) -> Result<()> {
+    progress!("{}", "📡 Following Chain".blue().bold());
+    progress!("{}", "=".repeat(30).blue());
+
+    let ogmios = OgmiosClient::new(config)
+        .context("Following the chain needs Ogmios - add an [ogmios] section with its url")?;
+
+    // Start from the current tip rather than replaying from origin
+    let tip = ogmios.query_tip().await?;
+    let point = if tip["slot"].is_u64() { tip } else { Value::from("origin") };
+    ogmios.find_intersection(vec![point]).await?;
+
+    let mut seen = 0;
+    while blocks.is_none_or(|limit| seen < limit) {
+        let event = match ogmios.next_block().await? {
+            ChainSyncEvent::RollForward { block, tip } => {
+                seen += 1;
+                ChainEvent {
+                    event: "roll_forward".to_string(),
+                    slot: block["slot"].as_u64(),
+                    height: block["height"].as_u64(),
+                    block_id: block["id"].as_str().map(String::from),
+                    tx_count: Some(block["transactions"].as_array().map_or(0, |txs| txs.len())),
+                    tip_slot: tip["slot"].as_u64(),
+                    tip_height: tip["height"].as_u64(),
+                }
+            }
+            ChainSyncEvent::RollBackward { point, tip } => ChainEvent {
+                event: "roll_backward".to_string(),
+                slot: point["slot"].as_u64(),
+                height: None,
+                block_id: point["id"].as_str().map(String::from),
+                tx_count: None,
+                tip_slot: tip["slot"].as_u64(),
+                tip_height: tip["height"].as_u64(),
+            },
+        };
+        render_stream(&event, output)?;
+    }
 
     Ok(())
 }
 
-fn display_node_info(info: &Value) -> Result<()> {
+fn display_node_info(info: &Value) {
     println!("\n{}", "📊 Node Information".cyan().bold());
 
     if let Some(status) = info.get("status") {
@@ -98,12 +148,48 @@ fn display_node_info(info: &Value) -> Result<()> {
         println!("  • Socket: {}", socket.as_str().unwrap_or("Unknown").dimmed());
     }
 
-    // In a real implementation, you'd display:
-    println!("  • Sync Progress: {}%", "99.8".green());
-    println!("  • Current Epoch: {}", "450".cyan());
-    println!("  • Slot: {}", "45123456".cyan());
-    println!("  • Block Height: {}", "8234567".cyan());
-    println!("  • Uptime: {}", "5d 12h 34m".green());
+    let metrics = &info["metrics"];
+    if metrics.is_null() {
+        println!("  • Metrics: {}", "unavailable (check hasPrometheus in the node config)".yellow());
+        return;
+    }
 
-    Ok(())
-}
\ No newline at end of file
+    let show = |label: &str, key: &str| {
+        match &metrics[key] {
+            Value::Null => println!("  • {}: {}", label, "-".dimmed()),
+            value => println!("  • {}: {}", label, value.to_string().cyan()),
+        }
+    };
+
+    show("Current Epoch", "epoch");
+    show("Slot", "slot_number");
+    show("Slot in Epoch", "slot_in_epoch");
+    show("Block Height", "block_number");
+    if let Some(density) = metrics["density"].as_f64() {
+        println!("  • Chain Density: {}", format!("{:.2}%", density * 100.0).cyan());
+    }
+    if let Some(uptime) = metrics["uptime_seconds"].as_u64() {
+        println!("  • Uptime: {}", format_uptime(uptime).green());
+    }
+
+    println!("\n{}", "🌐 Network".cyan().bold());
+    show("Connected Peers", "connected_peers");
+    show("Hot Peers", "hot_peers");
+    show("Warm Peers", "warm_peers");
+    show("Cold Peers", "cold_peers");
+    show("Mempool Transactions", "mempool_txs");
+    show("Mempool Bytes", "mempool_bytes");
+
+    println!("\n{}", "⛏️  Block Production".cyan().bold());
+    show("Current KES Period", "current_kes_period");
+    show("Remaining KES Periods", "remaining_kes_periods");
+    show("Leader Slots", "leader_slots");
+    show("Blocks Forged", "blocks_forged");
+    show("Blocks Adopted", "blocks_adopted");
+    show("Blocks Not Adopted", "blocks_not_adopted");
+    show("Missed Slots", "missed_slots");
+}
+
+fn format_uptime(seconds: u64) -> String {
+    format!("{}d {}h {}m", seconds / 86_400, (seconds % 86_400) / 3_600, (seconds % 3_600) / 60)
+}
@@ -0,0 +1,419 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use colored::*;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use tabled::{Table, Tabled};
+use crate::cardano::address::Address;
+use crate::cardano::chain_time::ChainTime;
+use crate::cardano::cli::{utxo_lovelace, CardanoCli};
+use crate::cardano::pool_id::PoolId;
+use crate::cardano::provider::{ChainDataProvider, ProviderChain};
+use crate::commands::time::format_duration;
+use crate::utils::alerts::Alert;
+use crate::utils::config::{Config, PoolConfig};
+use crate::utils::error::CliError;
+use crate::utils::output::{format_ada, render, OutputFormat, Render};
+use crate::progress;
+
+// A pool whose owners hold less than the declared pledge in the stake
+// snapshot earns no rewards for the epoch that snapshot is used in. Only
+// owner stake delegated to the pool itself counts.
+
+/// Live stake of one pool owner.
+#[derive(Debug, Clone, Serialize)]
+pub struct OwnerStake {
+    pub stake_address: String,
+    pub registered: Option<bool>,
+    pub delegated_to_pool: Option<bool>,
+    /// Live stake under the owner's key, rewards included.
+    pub stake: Option<u64>,
+    /// Stake counted towards pledge: zero unless delegated to the pool.
+    pub counted: u64,
+    pub source: Option<String>,
+    pub error: Option<String>,
+}
+
+/// When today's live stake becomes the snapshot the pledge check uses.
+#[derive(Debug, Clone, Serialize)]
+pub struct SnapshotProjection {
+    pub current_epoch: u64,
+    /// End of the current epoch, when the "mark" snapshot is taken.
+    pub snapshot_at: DateTime<Utc>,
+    /// The epoch whose rewards are calculated from that snapshot.
+    pub rewards_epoch: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PledgeReport {
+    pub pool_id: String,
+    pub ticker: String,
+    pub declared_pledge: u64,
+    pub actual_pledge: u64,
+    pub shortfall: u64,
+    /// `met`, `unmet`, or `unknown` when owner balances are missing and the
+    /// known ones fall short.
+    pub status: String,
+    pub owners: Vec<OwnerStake>,
+    pub snapshot: Option<SnapshotProjection>,
+    pub source: String,
+    pub warnings: Vec<String>,
+}
+
+impl PledgeReport {
+    pub fn is_met(&self) -> bool {
+        self.status == "met"
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PledgeCheck {
+    pub pools: Vec<PledgeReport>,
+    pub errors: Vec<String>,
+}
+
+#[derive(Tabled)]
+struct OwnerRow {
+    #[tabled(rename = "Owner")]
+    owner: String,
+    #[tabled(rename = "Live Stake")]
+    stake: String,
+    #[tabled(rename = "Delegated")]
+    delegated: String,
+    #[tabled(rename = "Counted")]
+    counted: String,
+    #[tabled(rename = "Source")]
+    source: String,
+}
+
+impl Render for PledgeCheck {
+    const SCHEMA: &'static str = "pledge";
+
+    fn render_table(&self) {
+        for report in &self.pools {
+            println!("\n🏊 {} ({})", report.ticker.cyan().bold(), report.pool_id.dimmed());
+
+            let rows: Vec<OwnerRow> = report.owners.iter().map(|owner| OwnerRow {
+                owner: owner.stake_address.clone(),
+                stake: owner.stake.map_or("-".to_string(), format_ada),
+                delegated: match owner.delegated_to_pool {
+                    Some(true) => "✅".to_string(),
+                    Some(false) => "❌".to_string(),
+                    None => "-".to_string(),
+                },
+                counted: format_ada(owner.counted),
+                source: owner.error.clone().or_else(|| owner.source.clone()).unwrap_or_default(),
+            }).collect();
+            if !rows.is_empty() {
+                println!("{}", Table::new(rows));
+            }
+
+            println!("  Declared pledge: {}", format_ada(report.declared_pledge));
+            println!("  Actual pledge:   {}", format_ada(report.actual_pledge));
+            match report.status.as_str() {
+                "met" => println!("  Status:          {}", "✅ MET".green().bold()),
+                "unmet" => println!("  Status:          {} (short by {})",
+                                    "🚨 UNMET".red().bold(), format_ada(report.shortfall)),
+                _ => println!("  Status:          {} (short by up to {})",
+                              "❓ UNKNOWN".yellow().bold(), format_ada(report.shortfall)),
+            }
+            if let Some(snapshot) = &report.snapshot {
+                println!("  Snapshot:        end of epoch {} ({}, in {}) - used for epoch {} rewards",
+                         snapshot.current_epoch,
+                         snapshot.snapshot_at.format("%Y-%m-%d %H:%M UTC"),
+                         format_duration(snapshot.snapshot_at - Utc::now()),
+                         snapshot.rewards_epoch);
+            }
+            println!("  Source:          {}", report.source.dimmed());
+            for warning in &report.warnings {
+                println!("  ⚠️  {}", warning.yellow());
+            }
+        }
+        for error in &self.errors {
+            println!("❌ {}", error);
+        }
+    }
+
+    fn records(&self) -> Result<Vec<Value>> {
+        self.pools.iter().map(|p| Ok(serde_json::to_value(p)?)).collect()
+    }
+}
+
+pub async fn execute(pool_id: Option<PoolId>, provider: Option<String>, output: OutputFormat, config: &Config) -> Result<()> {
+    let pools: Vec<&PoolConfig> = config.pools.iter()
+        .filter(|p| pool_id.as_ref().is_none_or(|id| &p.pool_id == id))
+        .collect();
+    if pools.is_empty() {
+        return Err(CliError::NotFound("No matching pools configured".to_string()).into());
+    }
+
+    let chain = ProviderChain::from_config(config, provider.as_deref())?;
+    progress!("{}", format!("Providers: {}", chain.names().join(" → ")).dimmed());
+
+    let mut check = PledgeCheck { pools: Vec::new(), errors: Vec::new() };
+    for pool in pools {
+        match check_pool_pledge(&chain, config, pool).await {
+            Ok(report) => check.pools.push(report),
+            Err(e) => check.errors.push(format!("{}: {:#}", pool.ticker, e)),
+        }
+    }
+    render(&check, output)?;
+
+    let count = |status: &str| check.pools.iter().filter(|p| p.status == status).count();
+    let problems: Vec<String> = [
+        (count("unmet"), "below declared pledge"),
+        (count("unknown"), "with unconfirmed pledge"),
+        (check.errors.len(), "could not be checked"),
+    ].into_iter()
+        .filter(|(n, _)| *n > 0)
+        .map(|(n, what)| format!("{} pool(s) {}", n, what))
+        .collect();
+    if !problems.is_empty() {
+        return Err(CliError::CheckFailed(problems.join(", ")).into());
+    }
+    Ok(())
+}
+
+/// Compare a pool's declared pledge with its owners' live stake.
+pub async fn check_pool_pledge(chain: &ProviderChain, config: &Config, pool: &PoolConfig) -> Result<PledgeReport> {
+    let info = chain.pool_info(pool.pool_id.as_str()).await?;
+    let declared_pledge = info.pledge.context("Provider did not report the declared pledge")?;
+    let mut warnings = Vec::new();
+
+    if info.owners.is_empty() {
+        warnings.push("Provider did not report the pool owners".to_string());
+    }
+
+    // cardano-cli cannot total a stake key's funds; the configured pledge
+    // address is the best estimate for the owner it belongs to
+    let pledge_stake_address = if pool.pledge_address.is_empty() {
+        None
+    } else {
+        let address = Address::parse(&pool.pledge_address)
+            .map_err(|e| CliError::Config(format!("{} pledge address: {:#}", pool.ticker, e)))?;
+        if address.stake_address.is_none() {
+            warnings.push("Pledge address has no stake key - its funds never count as pledge".to_string());
+        }
+        address.stake_address
+    };
+    if let Some(stake_address) = &pledge_stake_address {
+        if !info.owners.is_empty() && !info.owners.contains(stake_address) {
+            warnings.push(format!("Pledge address stake key {} is not a registered pool owner", stake_address));
+        }
+    }
+
+    let mut owners = Vec::new();
+    for stake_address in &info.owners {
+        let mut owner = OwnerStake {
+            stake_address: stake_address.clone(),
+            registered: None,
+            delegated_to_pool: None,
+            stake: None,
+            counted: 0,
+            source: None,
+            error: None,
+        };
+
+        match chain.account_info(stake_address).await {
+            Ok(account) => {
+                owner.registered = Some(account.registered);
+                owner.delegated_to_pool = Some(
+                    account.delegated_pool.as_deref().and_then(|d| d.parse::<PoolId>().ok()).as_ref() == Some(&pool.pool_id)
+                );
+                owner.stake = account.controlled_amount;
+                owner.source = Some(account.source);
+
+                if owner.stake.is_none() && pledge_stake_address.as_ref() == Some(stake_address) {
+                    let utxos = CardanoCli::new(config).query_utxo(&pool.pledge_address).await;
+                    match utxos {
+                        Ok(utxos) => {
+                            owner.stake = Some(utxo_lovelace(&utxos) + account.rewards_available.unwrap_or(0));
+                            owner.source = Some("cardano-cli (pledge address only)".to_string());
+                        }
+                        Err(e) => owner.error = Some(format!("UTxO query failed: {}", e)),
+                    }
+                }
+            }
+            Err(e) => owner.error = Some(e.to_string()),
+        }
+
+        if owner.delegated_to_pool == Some(true) {
+            owner.counted = owner.stake.unwrap_or(0);
+        } else if owner.delegated_to_pool == Some(false) {
+            warnings.push(format!("Owner {} is not delegated to the pool - its stake does not count", stake_address));
+        }
+        if owner.registered == Some(false) {
+            warnings.push(format!("Owner stake key {} is not registered", stake_address));
+        }
+        owners.push(owner);
+    }
+
+    let (actual_pledge, status) = pledge_status(declared_pledge, &owners);
+
+    let snapshot = match ChainTime::load(&config.cardano) {
+        Ok(chain_time) => {
+            let current_epoch = match chain.tip().await {
+                Ok(tip) => tip.epoch,
+                Err(_) => chain_time.current_epoch(Utc::now()),
+            };
+            Some(SnapshotProjection {
+                current_epoch,
+                snapshot_at: chain_time.epoch_boundaries(current_epoch).end_time,
+                rewards_epoch: current_epoch + 2,
+            })
+        }
+        Err(e) => {
+            warnings.push(format!("Chain time unavailable: {}", e));
+            None
+        }
+    };
+
+    Ok(PledgeReport {
+        pool_id: pool.pool_id.to_string(),
+        ticker: pool.ticker.clone(),
+        declared_pledge,
+        actual_pledge,
+        shortfall: declared_pledge.saturating_sub(actual_pledge),
+        status: status.to_string(),
+        owners,
+        snapshot,
+        source: info.source,
+        warnings,
+    })
+}
+
+/// Owner stake counted towards pledge and the resulting status.
+fn pledge_status(declared_pledge: u64, owners: &[OwnerStake]) -> (u64, &'static str) {
+    let actual_pledge: u64 = owners.iter().map(|o| o.counted).sum();
+    let complete = owners.iter().all(|o| o.stake.is_some() || o.delegated_to_pool == Some(false));
+    let status = if actual_pledge >= declared_pledge {
+        "met"
+    } else if complete {
+        "unmet"
+    } else {
+        "unknown"
+    };
+    (actual_pledge, status)
+}
+
+/// Pledge status of each pool at the last alert-worthy check, so repeated
+/// checks alert once when a pool falls below its pledge and once when it
+/// recovers. An `unknown` status says nothing either way and is ignored.
+#[derive(Debug, Default)]
+pub struct PledgeWatch {
+    last: HashMap<String, String>,
+}
+
+impl PledgeWatch {
+    /// The alert a new report calls for, if any.
+    pub fn alert_for(&self, report: &PledgeReport) -> Option<Alert> {
+        let previous = self.last.get(&report.pool_id).map(String::as_str);
+        let message = match (previous, report.status.as_str()) {
+            (Some("unmet"), "unmet") => return None,
+            (_, "unmet") => format!(
+                "{} pledge UNMET: owners hold {} of {} declared - the pool earns no rewards if this holds at the epoch {} snapshot",
+                report.ticker,
+                format_ada(report.actual_pledge),
+                format_ada(report.declared_pledge),
+                report.snapshot.as_ref().map_or("end".to_string(), |s| s.current_epoch.to_string())
+            ),
+            (Some("unmet"), "met") => format!(
+                "{} pledge met again: owners hold {} of {} declared",
+                report.ticker,
+                format_ada(report.actual_pledge),
+                format_ada(report.declared_pledge)
+            ),
+            _ => return None,
+        };
+        Some(Alert::new(&format!("pledge_{}", report.status), &report.pool_id, &report.ticker, message))
+    }
+
+    /// Remember a report once its alert, if any, has been delivered.
+    pub fn record(&mut self, report: &PledgeReport) {
+        if report.status != "unknown" {
+            self.last.insert(report.pool_id.clone(), report.status.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn owner(stake: Option<u64>, delegated: Option<bool>) -> OwnerStake {
+        OwnerStake {
+            stake_address: "stake1uyehkck0lajq8gr28t9uxnuvgcqrc6070x3k9r8048z8y5gh6ffgw".to_string(),
+            registered: Some(true),
+            delegated_to_pool: delegated,
+            stake,
+            counted: if delegated == Some(true) { stake.unwrap_or(0) } else { 0 },
+            source: None,
+            error: None,
+        }
+    }
+
+    fn report(status: &str) -> PledgeReport {
+        PledgeReport {
+            pool_id: "pool1pu5jlj4q9w9jlxeu370a3c9myx47md5j5m2str0naunn2q3lkdy".to_string(),
+            ticker: "TEST".to_string(),
+            declared_pledge: 500_000_000,
+            actual_pledge: if status == "met" { 500_000_000 } else { 100_000_000 },
+            shortfall: 0,
+            status: status.to_string(),
+            owners: Vec::new(),
+            snapshot: None,
+            source: "test".to_string(),
+            warnings: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn delegated_owner_stake_meets_pledge() {
+        let owners = [owner(Some(300), Some(true)), owner(Some(200), Some(true))];
+        assert_eq!(pledge_status(500, &owners), (500, "met"));
+    }
+
+    #[test]
+    fn undelegated_owner_stake_does_not_count() {
+        let owners = [owner(Some(300), Some(true)), owner(Some(1_000), Some(false))];
+        assert_eq!(pledge_status(500, &owners), (300, "unmet"));
+    }
+
+    #[test]
+    fn missing_balances_make_a_shortfall_unknown() {
+        let owners = [owner(Some(300), Some(true)), owner(None, Some(true))];
+        assert_eq!(pledge_status(500, &owners), (300, "unknown"));
+        // Known stake alone can still prove the pledge met
+        assert_eq!(pledge_status(200, &owners), (300, "met"));
+    }
+
+    #[test]
+    fn alerts_once_on_falling_below_pledge_and_once_on_recovery() {
+        let mut watch = PledgeWatch::default();
+        assert!(watch.alert_for(&report("met")).is_none());
+        watch.record(&report("met"));
+
+        let alert = watch.alert_for(&report("unmet")).expect("falling below pledge alerts");
+        assert_eq!(alert.kind, "pledge_unmet");
+        assert!(alert.message.contains("100.000000 ₳ of 500.000000 ₳"), "{}", alert.message);
+        watch.record(&report("unmet"));
+        assert!(watch.alert_for(&report("unmet")).is_none());
+
+        // An unconfirmed check neither clears nor repeats the alert
+        watch.record(&report("unknown"));
+        assert!(watch.alert_for(&report("unmet")).is_none());
+
+        assert_eq!(watch.alert_for(&report("met")).map(|a| a.kind), Some("pledge_met".to_string()));
+    }
+
+    #[test]
+    fn undelivered_alerts_are_raised_again() {
+        let mut watch = PledgeWatch::default();
+        assert!(watch.alert_for(&report("unmet")).is_some());
+        // Not recorded because the webhook failed
+        assert!(watch.alert_for(&report("unmet")).is_some());
+        watch.record(&report("unmet"));
+        assert!(watch.alert_for(&report("unmet")).is_none());
+    }
+}
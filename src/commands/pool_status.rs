@@ -1,13 +1,20 @@
 
 use anyhow::Result;
 use colored::*;
-use tabled::{Table, Tabled};
+use serde::Serialize;
 use serde_json::Value;
+use tabled::{Table, Tabled};
 use crate::cardano::cli::CardanoCli;
+use crate::cardano::provider::{ChainDataProvider, ProviderChain};
+use crate::storage::history::{HistoryStore, PoolSnapshot};
 use crate::utils::config::Config;
+use crate::utils::error::CliError;
+use crate::utils::output::{format_ada_short, is_table, render, OutputFormat, Render};
+use crate::progress;
+use crate::cardano::pool_id::PoolId;
 
 #[derive(Tabled)]
-struct PoolStatus {
+struct PoolStatusRow {
     #[tabled(rename = "Pool ID")]
     pool_id: String,
     #[tabled(rename = "Name")]
@@ -22,14 +29,58 @@ struct PoolStatus {
     blocks_epoch: String,
 }
 
-pub async fn execute(pool_id: Option<String>, config: &Config) -> Result<()> {
-    let cardano_cli = CardanoCli::new(&config);
+#[derive(Debug, Serialize)]
+pub struct PoolStatusReport {
+    pub providers: Vec<String>,
+    pub pools: Vec<PoolStatus>,
+}
 
-    println!("{}", "🔍 Checking pool status...".blue().bold());
+/// One pool's status. `state` is one of `oversaturated`, `high_saturation`,
+/// `active`, `low_stake`, `retired` or `unknown`.
+#[derive(Debug, Serialize)]
+pub struct PoolStatus {
+    pub pool_id: String,
+    pub name: String,
+    pub state: String,
+    pub demo: bool,
+    pub epoch: Option<u64>,
+    pub saturation_percent: Option<f64>,
+    pub live_stake: Option<u64>,
+    pub total_stake: Option<u64>,
+    pub blocks_epoch: Option<u64>,
+    pub stake_source: Option<String>,
+    pub error: Option<String>,
+}
 
-    // Check if cardano-cli is available
-    if !cardano_cli.is_available().await {
-        println!("{}", "⚠️  cardano-cli not available - using demo mode".yellow());
+impl Render for PoolStatusReport {
+    const SCHEMA: &'static str = "pool-status";
+
+    fn render_table(&self) {
+        if self.pools.is_empty() {
+            return;
+        }
+        println!("\n{}", "📊 Pool Status Summary:".blue().bold());
+        let rows: Vec<PoolStatusRow> = self.pools.iter().map(build_row).collect();
+        println!("{}", Table::new(rows));
+    }
+
+    fn records(&self) -> Result<Vec<Value>> {
+        self.pools.iter().map(|p| Ok(serde_json::to_value(p)?)).collect()
+    }
+}
+
+pub async fn execute(pool_id: Option<PoolId>, provider: Option<String>, output: OutputFormat, config: &Config) -> Result<()> {
+    let chain = ProviderChain::from_config(config, provider.as_deref())?;
+
+    progress!("{}", "🔍 Checking pool status...".blue().bold());
+    progress!("{}", format!("Providers: {}", chain.names().join(" → ")).dimmed());
+
+    if chain.names().contains(&"cardano-cli") && !CardanoCli::new(config).is_available().await {
+        if chain.names().len() > 1 {
+            progress!("{}", "⚠️  cardano-cli not available - falling back to other providers".yellow());
+        } else {
+            progress!("{}", "⚠️  cardano-cli not available - using demo mode".yellow());
+        }
     }
 
     let pools_to_check = if let Some(id) = pool_id {
@@ -41,112 +92,86 @@ pub async fn execute(pool_id: Option<String>, config: &Config) -> Result<()> {
     };
 
     if pools_to_check.is_empty() {
-        println!("{}", "❌ No pools configured or specified".red());
-        return Ok(());
+        return Err(CliError::Config("No pools configured or specified".to_string()).into());
     }
 
+    let history = HistoryStore::open_or_warn(config);
     let mut statuses = Vec::new();
 
     for (pool_id, pool_name) in pools_to_check.iter() {
-        print!("Checking {} ({})... ", pool_name.cyan(), pool_id.dimmed());
+        if is_table() {
+            print!("Checking {} ({})... ", pool_name.cyan(), pool_id.as_str().dimmed());
+        }
 
-        match check_pool_status(&cardano_cli, &pool_id, &pool_name).await {
+        match check_pool_status(&chain, pool_id.as_str(), pool_name, history.as_ref()).await {
             Ok(status) => {
-                println!("{}", "✓".green());
+                progress!("{}", "✓".green());
                 statuses.push(status);
             },
             Err(e) => {
-                println!("{}", "✗".red());
-                println!("{} Failed to check pool {}: {}", "❌".red(), pool_id, e);
+                progress!("{}", "✗".red());
+                progress!("{} Failed to check pool {}: {}", "❌".red(), pool_id, e);
                 // Add placeholder status for failed checks
-                statuses.push(create_placeholder_status(pool_id, pool_name));
+                statuses.push(create_placeholder_status(pool_id.as_str(), pool_name, e.to_string()));
             }
         }
     }
 
-    if !statuses.is_empty() {
-        println!("\n{}", "📊 Pool Status Summary:".blue().bold());
-        let table = Table::new(statuses);
-        println!("{}", table);
-    }
+    let report = PoolStatusReport {
+        providers: chain.names().iter().map(|n| n.to_string()).collect(),
+        pools: statuses,
+    };
+    render(&report, output)?;
 
+    let degraded = report.pools.iter().filter(|p| p.demo || p.error.is_some()).count();
+    if degraded > 0 {
+        return Err(CliError::CheckFailed(format!(
+            "{} of {} pools could not be checked against live chain data", degraded, report.pools.len()
+        )).into());
+    }
     Ok(())
 }
 
-fn create_placeholder_status(pool_id: &str, pool_name: &str) -> PoolStatus {
+fn create_placeholder_status(pool_id: &str, pool_name: &str, error: String) -> PoolStatus {
     PoolStatus {
-        pool_id: truncate_pool_id(pool_id),
+        pool_id: pool_id.to_string(),
         name: pool_name.to_string(),
-        status: "⚪ Unknown".to_string(),
-        saturation: "-.-%".to_string(),
-        live_stake: "-".to_string(),
-        blocks_epoch: "-".to_string(),
+        state: "unknown".to_string(),
+        demo: false,
+        epoch: None,
+        saturation_percent: None,
+        live_stake: None,
+        total_stake: None,
+        blocks_epoch: None,
+        stake_source: None,
+        error: Some(error),
     }
 }
 
-fn get_pool_stake_from_distribution(distribution: &Value, pool_id: &str) -> u64 {
-    // The stake distribution format can vary, try different possible structures
-    if let Some(pools) = distribution.get("pools") {
-        if let Some(stake_str) = pools.get(pool_id).and_then(|v| v.as_str()) {
-            return stake_str.parse::<u64>().unwrap_or(0);
-        }
-    }
-
-    // Alternative: direct pool lookup
-    if let Some(stake_str) = distribution.get(pool_id).and_then(|v| v.as_str()) {
-        return stake_str.parse::<u64>().unwrap_or(0);
-    }
-
-    0
-}
-
-fn get_total_stake(distribution: &Value) -> u64 {
-    // Try different possible locations for total stake
-    if let Some(total_str) = distribution.get("total").and_then(|v| v.as_str()) {
-        return total_str.parse::<u64>().unwrap_or(0);
-    }
-
-    if let Some(total_str) = distribution.get("totalStake").and_then(|v| v.as_str()) {
-        return total_str.parse::<u64>().unwrap_or(0);
-    }
-
-    // If we can't find total, sum all pool stakes
-    if let Some(pools) = distribution.get("pools").and_then(|v| v.as_object()) {
-        return pools.values()
-            .filter_map(|v| v.as_str())
-            .filter_map(|s| s.parse::<u64>().ok())
-            .sum();
-    }
-
-    0
-}
-
 async fn check_pool_status(
-    cardano_cli: &CardanoCli,
+    chain: &ProviderChain,
     pool_id: &str,
     pool_name: &str,
+    history: Option<&HistoryStore>,
 ) -> Result<PoolStatus> {
     // Try to get actual tip information first
-    let tip = match cardano_cli.query_tip().await {
+    let tip = match chain.tip().await {
         Ok(tip) => tip,
-        Err(_) => {
-            // Fallback to demo mode if cardano-cli not available
+        Err(e) => {
+            // Fallback to demo mode if no provider is reachable
+            log::warn!("No chain data available, using demo mode: {}", e);
             return Ok(create_demo_status(pool_id, pool_name));
         }
     };
 
-    let current_epoch = tip["epoch"].as_u64().unwrap_or(0);
+    let current_epoch = tip.epoch;
 
     // Try to get stake distribution (this can be slow/fail)
-    let (pool_stake, total_stake) = match cardano_cli.query_stake_distribution().await {
-        Ok(distribution) => {
-            let pool_stake = get_pool_stake_from_distribution(&distribution, pool_id);
-            let total_stake = get_total_stake(&distribution);
-            (pool_stake, total_stake)
-        },
+    let (pool_stake, total_stake, stake_source) = match chain.pool_stake(pool_id).await {
+        Ok(stake) => (stake.pool_stake, stake.total_stake, Some(stake.source)),
         Err(_) => {
-            // Use placeholder values if stake distribution query fails
-            (1_000_000_000_000u64, 32_000_000_000_000_000u64) // 1M ADA, 32B total
+            // Use placeholder values if every provider fails
+            (1_000_000_000_000u64, 32_000_000_000_000_000u64, None) // 1M ADA, 32B total
         }
     };
 
@@ -157,38 +182,59 @@ async fn check_pool_status(
     };
 
     // Check if pool is active
-    let is_active = match cardano_cli.query_pool_info(pool_id).await {
-        Ok(info) => info["active"].as_bool().unwrap_or(false),
+    let is_active = match chain.pool_info(pool_id).await {
+        Ok(info) => info.active,
         Err(_) => true, // Assume active if we can't check
     };
 
-    let status = if is_active {
+    let state = if is_active {
         if saturation > 95.0 {
-            "🔴 Oversaturated".red().to_string()
+            "oversaturated"
         } else if saturation > 70.0 {
-            "🟡 High Saturation".yellow().to_string()
+            "high_saturation"
         } else if saturation > 0.1 {
-            "🟢 Active".green().to_string()
+            "active"
         } else {
-            "🔵 Active (Low Stake)".blue().to_string()
+            "low_stake"
         }
     } else {
-        "⚫ Retired".to_string()
+        "retired"
     };
 
-    // Get blocks for current epoch (simplified)
-    let blocks_count = match cardano_cli.query_pool_blocks(pool_id, current_epoch).await {
-        Ok(blocks) => blocks.as_array().map(|a| a.len()).unwrap_or(0),
-        Err(_) => 0, // Default to 0 if query fails
-    };
+    let blocks_count = chain.pool_blocks(pool_id, current_epoch).await.ok();
+
+    // Only real chain data goes into history, never placeholder values
+    if let (Some(store), Some(source)) = (history, &stake_source) {
+        let snapshot = PoolSnapshot {
+            pool_id: pool_id.to_string(),
+            epoch: current_epoch,
+            live_stake: Some(pool_stake),
+            active_stake: None,
+            total_stake: Some(total_stake),
+            saturation: None,
+            delegator_count: None,
+            source: source.clone(),
+        };
+        if let Err(e) = store.record_pool_snapshot(&snapshot) {
+            log::warn!("Failed to record pool snapshot: {}", e);
+        }
+    }
+
+    // Placeholder stake figures are not worth reporting
+    let known = stake_source.is_some();
 
     Ok(PoolStatus {
-        pool_id: truncate_pool_id(pool_id),
+        pool_id: pool_id.to_string(),
         name: pool_name.to_string(),
-        status,
-        saturation: format!("{:.2}%", saturation),
-        live_stake: format_ada(pool_stake),
-        blocks_epoch: blocks_count.to_string(),
+        state: state.to_string(),
+        demo: false,
+        epoch: Some(current_epoch),
+        saturation_percent: known.then_some(saturation),
+        live_stake: known.then_some(pool_stake),
+        total_stake: known.then_some(total_stake),
+        blocks_epoch: blocks_count,
+        stake_source,
+        error: None,
     })
 }
 
@@ -198,12 +244,38 @@ fn create_demo_status(pool_id: &str, pool_name: &str) -> PoolStatus {
     let demo_saturation = 4.2; // 4.2%
 
     PoolStatus {
-        pool_id: truncate_pool_id(pool_id),
+        pool_id: pool_id.to_string(),
         name: pool_name.to_string(),
-        status: "🟢 Active (Demo)".green().to_string(),
-        saturation: format!("{:.2}%", demo_saturation),
-        live_stake: format_ada(demo_stake),
-        blocks_epoch: "3".to_string(),
+        state: "active".to_string(),
+        demo: true,
+        epoch: None,
+        saturation_percent: Some(demo_saturation),
+        live_stake: Some(demo_stake),
+        total_stake: None,
+        blocks_epoch: Some(3),
+        stake_source: None,
+        error: None,
+    }
+}
+
+fn build_row(status: &PoolStatus) -> PoolStatusRow {
+    let label = match status.state.as_str() {
+        "oversaturated" => "🔴 Oversaturated".red().to_string(),
+        "high_saturation" => "🟡 High Saturation".yellow().to_string(),
+        "active" if status.demo => "🟢 Active (Demo)".green().to_string(),
+        "active" => "🟢 Active".green().to_string(),
+        "low_stake" => "🔵 Active (Low Stake)".blue().to_string(),
+        "retired" => "⚫ Retired".to_string(),
+        _ => "⚪ Unknown".to_string(),
+    };
+
+    PoolStatusRow {
+        pool_id: truncate_pool_id(&status.pool_id),
+        name: status.name.clone(),
+        status: label,
+        saturation: status.saturation_percent.map_or("-.-%".to_string(), |s| format!("{:.2}%", s)),
+        live_stake: status.live_stake.map_or("-".to_string(), format_ada_short),
+        blocks_epoch: status.blocks_epoch.map_or("-".to_string(), |b| b.to_string()),
     }
 }
 
@@ -214,14 +286,3 @@ fn truncate_pool_id(pool_id: &str) -> String {
         pool_id.to_string()
     }
 }
-
-fn format_ada(lovelace: u64) -> String {
-    let ada = lovelace as f64 / 1_000_000.0;
-    if ada >= 1_000_000.0 {
-        format!("{:.1}M ₳", ada / 1_000_000.0)
-    } else if ada >= 1_000.0 {
-        format!("{:.1}K ₳", ada / 1_000.0)
-    } else {
-        format!("{:.1} ₳", ada)
-    }
-}
\ No newline at end of file
@@ -0,0 +1,170 @@
+use anyhow::Result;
+use colored::*;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tabled::{Table, Tabled};
+use crate::cardano::blockfrost::BlockfrostClient;
+use crate::cardano::cli::CardanoCli;
+use crate::cardano::relay::{self, Handshake, Relay, RelayProbe};
+use crate::utils::config::Config;
+use crate::utils::error::CliError;
+use crate::utils::output::{render, OutputFormat, Render};
+use crate::cardano::pool_id::PoolId;
+use crate::progress;
+
+#[derive(Tabled)]
+struct RelayRow {
+    #[tabled(rename = "Relay")]
+    relay: String,
+    #[tabled(rename = "Endpoint")]
+    endpoint: String,
+    #[tabled(rename = "TCP")]
+    tcp: String,
+    #[tabled(rename = "Latency")]
+    latency: String,
+    #[tabled(rename = "Handshake")]
+    handshake: String,
+    #[tabled(rename = "Status")]
+    status: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PoolRelays {
+    pub pool_id: String,
+    pub name: String,
+    pub probes: Vec<RelayProbe>,
+    pub error: Option<String>,
+}
+
+impl PoolRelays {
+    fn is_healthy(&self) -> bool {
+        self.error.is_none() && self.probes.iter().all(RelayProbe::is_healthy)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct RelayCheck {
+    pub pools: Vec<PoolRelays>,
+}
+
+impl Render for RelayCheck {
+    const SCHEMA: &'static str = "relays";
+
+    fn render_table(&self) {
+        for pool in &self.pools {
+            println!("\n🏊 {} ({})", pool.name.cyan(), pool.pool_id.dimmed());
+
+            if let Some(error) = &pool.error {
+                println!("  ❌ Relay check failed: {}", error);
+            } else if pool.probes.is_empty() {
+                println!("  ⚠️  {}", "No relays registered for this pool".yellow());
+            } else {
+                let healthy = pool.probes.iter().filter(|p| p.is_healthy()).count();
+                let rows: Vec<RelayRow> = pool.probes.iter().map(build_row).collect();
+                println!("{}", Table::new(rows));
+                println!("  {} of {} relay endpoints healthy", healthy, pool.probes.len());
+            }
+        }
+    }
+
+    /// One record per probed endpoint.
+    fn records(&self) -> Result<Vec<Value>> {
+        let mut records = Vec::new();
+        for pool in &self.pools {
+            if pool.probes.is_empty() {
+                records.push(json!({ "pool_id": pool.pool_id, "error": pool.error }));
+            }
+            for probe in &pool.probes {
+                let mut record = serde_json::to_value(probe)?;
+                record["pool_id"] = json!(pool.pool_id);
+                records.push(record);
+            }
+        }
+        Ok(records)
+    }
+}
+
+pub async fn execute(pool_id: Option<PoolId>, output: OutputFormat, config: &Config) -> Result<()> {
+    progress!("{}", "🛰️  Relay Reachability Check".blue().bold());
+    progress!("{}", "=".repeat(50).blue());
+
+    let pools: Vec<_> = config.pools.iter()
+        .filter(|p| pool_id.as_ref().is_none_or(|id| &p.pool_id == id))
+        .collect();
+
+    if pools.is_empty() {
+        return Err(CliError::NotFound("No matching pools configured".to_string()).into());
+    }
+
+    let mut check = RelayCheck { pools: Vec::new() };
+    for pool in pools {
+        let (probes, error) = match check_pool_relays(config, pool.pool_id.as_str()).await {
+            Ok(probes) => (probes, None),
+            Err(e) => (Vec::new(), Some(e.to_string())),
+        };
+        check.pools.push(PoolRelays {
+            pool_id: pool.pool_id.to_string(),
+            name: pool.name.clone(),
+            probes,
+            error,
+        });
+    }
+
+    render(&check, output)?;
+
+    let failures = check.pools.iter().filter(|p| !p.is_healthy()).count();
+    if failures > 0 {
+        return Err(CliError::CheckFailed(format!("{} pool(s) have unreachable relays", failures)).into());
+    }
+    Ok(())
+}
+
+/// Look up the registered relays for a pool and probe each of them.
+pub async fn check_pool_relays(config: &Config, pool_id: &str) -> Result<Vec<RelayProbe>> {
+    let relays = fetch_relays(config, pool_id).await?;
+    relay::probe_relays(&relays, config.cardano.network_magic()).await
+}
+
+async fn fetch_relays(config: &Config, pool_id: &str) -> Result<Vec<Relay>> {
+    let cli = CardanoCli::new(config);
+    if let Ok(params) = cli.query_pool_params(pool_id).await {
+        let relays = relay::relays_from_pool_params(&params, pool_id);
+        if !relays.is_empty() {
+            return Ok(relays);
+        }
+    }
+
+    let blockfrost = BlockfrostClient::new(config)
+        .ok_or_else(|| anyhow::anyhow!("Pool params unavailable from cardano-cli and no Blockfrost configuration found"))?;
+    let relays = blockfrost.get_pool_relays(pool_id).await?;
+    Ok(relay::relays_from_blockfrost(&relays))
+}
+
+fn build_row(probe: &RelayProbe) -> RelayRow {
+    let handshake = match &probe.handshake {
+        Some(Handshake::Accepted { version, network_magic }) => format!("v{} magic {}", version, network_magic),
+        Some(Handshake::Refused(reason)) => format!("refused: {}", reason),
+        Some(Handshake::Failed(error)) => format!("failed: {}", error),
+        None => "-".to_string(),
+    };
+
+    let status = if probe.is_healthy() {
+        "🟢 OK".green().to_string()
+    } else if probe.reachable {
+        match probe.handshake {
+            Some(Handshake::Failed(_)) => "🟡 No Handshake".yellow().to_string(),
+            _ => "🟡 Wrong Network".yellow().to_string(),
+        }
+    } else {
+        format!("🔴 {}", probe.error.as_deref().unwrap_or("Unreachable")).red().to_string()
+    };
+
+    RelayRow {
+        relay: probe.relay.clone(),
+        endpoint: probe.endpoint.map_or("-".to_string(), |e| e.to_string()),
+        tcp: if probe.reachable { "✅" } else { "❌" }.to_string(),
+        latency: probe.latency_ms.map_or("-".to_string(), |ms| format!("{:.0}ms", ms)),
+        handshake,
+        status,
+    }
+}
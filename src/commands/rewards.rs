@@ -1,10 +1,22 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use chrono::Utc;
 use colored::*;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use tabled::{Table, Tabled};
 
+use crate::cardano::address::{Address, AddressKind, Credential};
+use crate::cardano::chain_time::ChainTime;
 use crate::cardano::cli::CardanoCli;
-use crate::utils::config::Config;
+use crate::cardano::pool_id::PoolId;
+use crate::cardano::provider::{AccountReward, ChainDataProvider, ProviderChain};
+use crate::commands::time::format_duration;
+use crate::storage::history::{HistoryStore, RewardsRecord};
+use crate::utils::actions::{find_pool, step};
+use crate::utils::config::{Config, PoolConfig};
+use crate::utils::error::CliError;
+use crate::utils::output::{format_ada, render, OutputFormat, Render};
+use crate::progress;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RewardsReport {
@@ -17,6 +29,7 @@ pub struct RewardsReport {
     pub fees: f64,
     pub delegator_count: u64,
     pub average_reward_per_delegator: f64,
+    pub source: String,
 }
 
 #[derive(Tabled)]
@@ -35,68 +48,394 @@ struct RewardsSummary {
     avg_per_delegator: String,
 }
 
-pub async fn execute(epoch: Option<u64>, detailed: bool, config: &Config) -> Result<()> {
-    println!("{}", "💰 Rewards Calculation System".blue().bold());
-    println!("{}", "=".repeat(50).blue());
+#[derive(Debug, Serialize)]
+pub struct RewardsResult {
+    pub epoch: u64,
+    pub detailed: bool,
+    pub reports: Vec<RewardsReport>,
+    /// Only filled in for `--detailed`.
+    pub reward_accounts: Vec<RewardAccount>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RewardAccount {
+    pub pool_id: String,
+    pub reward_address: String,
+    pub rewards: Vec<AccountReward>,
+    pub error: Option<String>,
+}
+
+impl Render for RewardsResult {
+    const SCHEMA: &'static str = "rewards";
+
+    fn render_table(&self) {
+        if !self.detailed {
+            display_rewards_summary(&self.reports);
+            return;
+        }
+
+        for report in &self.reports {
+            display_detailed_rewards_report(report);
+            if let Some(account) = self.reward_accounts.iter().find(|a| a.pool_id == report.pool_id) {
+                display_reward_account(account);
+            }
+        }
+    }
+
+    fn records(&self) -> Result<Vec<Value>> {
+        self.reports.iter().map(|r| Ok(serde_json::to_value(r)?)).collect()
+    }
+}
+
+pub async fn execute(epoch: Option<u64>, detailed: bool, output: OutputFormat, config: &Config) -> Result<()> {
+    progress!("{}", "💰 Rewards Calculation System".blue().bold());
+    progress!("{}", "=".repeat(50).blue());
 
     let cardano_cli = CardanoCli::new(config);
 
-    let target_epoch = epoch.unwrap_or_else(|| {
-        // Get current epoch - 1 (rewards are calculated for previous epoch)
-        450 // Demo epoch
-    });
+    let target_epoch = match epoch {
+        Some(epoch) => epoch,
+        None => {
+            // Rewards are calculated for the previous epoch
+            let chain_time = ChainTime::load(&config.cardano)?;
+            chain_time.tip_epoch(&cardano_cli).await.saturating_sub(1)
+        }
+    };
+
+    let chain = ProviderChain::from_config(config, None)?;
 
     if detailed {
-        generate_detailed_rewards_report(&cardano_cli, config, target_epoch).await
+        progress!("{}", format!("🔍 Generating detailed rewards report for epoch {}...", target_epoch).cyan());
     } else {
-        generate_rewards_summary(&cardano_cli, config, target_epoch).await
+        progress!("{}", format!("📊 Calculating rewards for epoch {}...", target_epoch).cyan());
     }
-}
 
-async fn generate_rewards_summary(
-    cardano_cli: &CardanoCli,
-    config: &Config,
-    epoch: u64,
-) -> Result<()> {
-    println!("{}", format!("📊 Calculating rewards for epoch {}...", epoch).cyan());
-
-    let mut reports = Vec::new();
+    let mut result = RewardsResult {
+        epoch: target_epoch,
+        detailed,
+        reports: Vec::new(),
+        reward_accounts: Vec::new(),
+    };
 
     for pool in &config.pools {
-        let report = calculate_pool_rewards(cardano_cli, pool, epoch, config).await?;
-        reports.push(report);
+        result.reports.push(calculate_pool_rewards(&chain, pool, target_epoch, config).await?);
+        if detailed {
+            result.reward_accounts.push(fetch_reward_account(&chain, pool).await);
+        }
     }
 
-    display_rewards_summary(&reports);
+    record_rewards_history(&result.reports, config);
+
+    render(&result, output)?;
 
     // Auto-export in JSON format
-    export_rewards_report(&reports, config).await?;
+    if !detailed {
+        export_rewards_report(&result.reports, config).await?;
+    }
 
+    let simulated = result.reports.iter().filter(|r| r.source == "simulated").count();
+    if simulated > 0 {
+        return Err(CliError::CheckFailed(format!(
+            "Rewards for {} of {} pools are simulated - no chain data for epoch {}", simulated, result.reports.len(), target_epoch
+        )).into());
+    }
     Ok(())
 }
 
-async fn generate_detailed_rewards_report(
-    cardano_cli: &CardanoCli,
+// Enough for the fee; the withdrawal itself ends up in the change output
+const MIN_INPUT_LOVELACE: u64 = 2_000_000;
+
+// Time left in the epoch below which signing offline may not finish in time
+const EPOCH_BOUNDARY_MARGIN: chrono::Duration = chrono::Duration::hours(6);
+
+#[derive(Debug, Serialize)]
+pub struct WithdrawalInput {
+    pub tx_in: String,
+    pub lovelace: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Withdrawal {
+    pub pool_id: String,
+    pub ticker: String,
+    pub reward_address: String,
+    pub payment_address: String,
+    pub balance: u64,
+    pub inputs: Vec<WithdrawalInput>,
+    /// Unsigned transaction; `None` when there is nothing to withdraw.
+    pub out_file: Option<String>,
+    pub built: bool,
+    pub summary: Option<String>,
+    pub warnings: Vec<String>,
+}
+
+impl Render for Withdrawal {
+    const SCHEMA: &'static str = "rewards-withdraw";
+
+    fn render_table(&self) {
+        let Some(out_file) = &self.out_file else {
+            println!("{}", "Nothing to withdraw".yellow());
+            return;
+        };
+        if let Some(summary) = &self.summary {
+            println!("💰 {}", summary);
+        }
+        if self.built {
+            println!("{}", format!("✅ Unsigned transaction written to {}", out_file).green());
+        }
+
+        println!("\n{}", "📝 Next steps:".yellow().bold());
+        println!("  • Copy {} to the offline machine and sign it with the payment and stake keys:", out_file);
+        println!("    cardano-cli latest transaction sign --tx-body-file {} \\", out_file);
+        println!("      --signing-key-file payment.skey --signing-key-file stake.skey --out-file withdraw.tx.signed");
+        println!("  • Bring the signed file back and run: rewards submit --tx-file withdraw.tx.signed");
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Submission {
+    pub tx_file: String,
+    pub tx_id: String,
+    pub submitted: bool,
+}
+
+impl Render for Submission {
+    const SCHEMA: &'static str = "rewards-submit";
+
+    fn render_table(&self) {
+        if self.submitted {
+            println!("{}", "✅ Transaction submitted".green());
+            println!("  • The reward balance drops once it is in a block: address inspect");
+        }
+    }
+}
+
+/// Build an unsigned transaction withdrawing the pool's rewards to a payment
+/// address, for signing on the offline machine.
+pub async fn withdraw(
+    pool_id: Option<PoolId>,
+    payment_address: Option<String>,
+    out_file: Option<String>,
+    dry_run: bool,
+    output: OutputFormat,
     config: &Config,
-    epoch: u64,
 ) -> Result<()> {
-    println!("{}", format!("🔍 Generating detailed rewards report for epoch {}...", epoch).cyan());
+    progress!("{}", "💸 Reward Withdrawal - Step 1: Build".blue().bold());
+    progress!("{}", "=".repeat(50).blue());
+    if dry_run {
+        progress!("{}", "🧪 Dry run: no transaction will be built".yellow());
+    }
 
-    for pool in &config.pools {
-        let report = calculate_pool_rewards(cardano_cli, pool, epoch, config).await?;
-        display_detailed_rewards_report(&report).await?;
+    let pool = find_pool(config, pool_id.as_ref())?;
+    if pool.reward_address.is_empty() {
+        return Err(CliError::Config(format!("No reward_address configured for {}", pool.ticker)).into());
+    }
+    let reward_address = Address::parse(&pool.reward_address)
+        .map_err(|e| CliError::Config(format!("{} reward address: {:#}", pool.ticker, e)))?;
+    if reward_address.kind != AddressKind::Reward {
+        return Err(CliError::Config(format!("{} reward_address must be a stake address", pool.ticker)).into());
     }
 
-    Ok(())
+    let payment_address = payment_address.unwrap_or_else(|| pool.pledge_address.clone());
+    if payment_address.is_empty() {
+        return Err(CliError::Config("No payment address - pass --payment-address or configure pledge_address".to_string()).into());
+    }
+    let payment = Address::parse(&payment_address)
+        .map_err(|e| CliError::Config(format!("Payment address: {:#}", e)))?;
+    if !matches!(payment.payment, Some(Credential::Key(_))) {
+        return Err(CliError::Config("Payment address must be spendable with a payment key".to_string()).into());
+    }
+
+    let cli = CardanoCli::new(config);
+    // Straight from the node: the withdrawal must equal the balance exactly
+    let info = cli.fetch_stake_address_info(&pool.reward_address).await?;
+    let account = info.as_array().and_then(|a| a.first())
+        .ok_or_else(|| CliError::NotFound(format!("Reward address {} is not registered", pool.reward_address)))?;
+    let balance = account["rewardAccountBalance"].as_u64()
+        .context("No reward balance in stake-address-info output")?;
+    progress!("🏦 Reward balance: {:.6} ADA", balance as f64 / 1_000_000.0);
+
+    let mut withdrawal = Withdrawal {
+        pool_id: pool.pool_id.to_string(),
+        ticker: pool.ticker.clone(),
+        reward_address: pool.reward_address.clone(),
+        payment_address: payment_address.clone(),
+        balance,
+        inputs: Vec::new(),
+        out_file: None,
+        built: false,
+        summary: None,
+        warnings: Vec::new(),
+    };
+    if balance == 0 {
+        return render(&withdrawal, output);
+    }
+
+    // Rewards are credited at the epoch boundary; a transaction landing
+    // after it no longer matches the balance and is rejected
+    match ChainTime::load(&config.cardano) {
+        Ok(chain_time) => {
+            let remaining = chain_time.time_until_next_epoch(Utc::now());
+            if remaining < EPOCH_BOUNDARY_MARGIN {
+                withdrawal.warnings.push(format!(
+                    "Next epoch starts in {} - submit before then or the withdrawal must be rebuilt",
+                    format_duration(remaining),
+                ));
+            }
+        }
+        Err(e) => withdrawal.warnings.push(format!("Chain time unavailable, cannot check the epoch boundary: {}", e)),
+    }
+    for warning in &withdrawal.warnings {
+        progress!("{}", format!("⚠️  {}", warning).yellow());
+    }
+
+    let utxos = cli.query_utxo(&payment_address).await?;
+    let inputs = select_inputs(&utxos, &payment_address)?;
+    for (tx_in, lovelace) in &inputs {
+        progress!("📥 Input {} ({:.6} ADA)", tx_in, *lovelace as f64 / 1_000_000.0);
+    }
+
+    let out_file = out_file.unwrap_or_else(|| format!("withdraw-{}.tx.raw", pool.ticker.to_lowercase()));
+    let mut args = Vec::new();
+    for (tx_in, _) in &inputs {
+        args.extend(["--tx-in".to_string(), tx_in.clone()]);
+    }
+    args.extend([
+        "--withdrawal".to_string(), format!("{}+{}", pool.reward_address, balance),
+        "--change-address".to_string(), payment_address.clone(),
+        // Signed by the payment key and the stake key
+        "--witness-override".to_string(), "2".to_string(),
+    ]);
+
+    step(dry_run, &format!("cardano-cli latest transaction build {} --out-file {}", args.join(" "), out_file));
+    if !dry_run {
+        let summary = cli.transaction_build(&args, &out_file).await?;
+        withdrawal.summary = Some(summary).filter(|s| !s.is_empty());
+        withdrawal.built = true;
+    }
+
+    withdrawal.inputs = inputs.into_iter()
+        .map(|(tx_in, lovelace)| WithdrawalInput { tx_in, lovelace })
+        .collect();
+    withdrawal.out_file = Some(out_file);
+    render(&withdrawal, output)
+}
+
+/// Submit a transaction signed offline.
+pub async fn submit(tx_file: &str, dry_run: bool, output: OutputFormat, config: &Config) -> Result<()> {
+    progress!("{}", "💸 Reward Withdrawal - Step 2: Submit".blue().bold());
+    progress!("{}", "=".repeat(50).blue());
+    if dry_run {
+        progress!("{}", "🧪 Dry run: nothing will be submitted".yellow());
+    }
+
+    let envelope: Value = serde_json::from_str(
+        &std::fs::read_to_string(tx_file).with_context(|| format!("Failed to read {}", tx_file))?,
+    ).with_context(|| format!("{} is not a cardano-cli text envelope", tx_file))?;
+    let tx_type = envelope["type"].as_str().unwrap_or_default();
+    if tx_type.starts_with("Unwitnessed") || tx_type.starts_with("TxBody") {
+        anyhow::bail!("{} is not signed - sign it on the offline machine first", tx_file);
+    }
+    if !tx_type.contains("Tx") {
+        anyhow::bail!("{} is a '{}', not a transaction", tx_file, tx_type);
+    }
+
+    let cli = CardanoCli::new(config);
+    let tx_id = cli.transaction_txid(tx_file).await?;
+    progress!("🧾 Transaction ID: {}", tx_id);
+
+    step(dry_run, &format!("cardano-cli latest transaction submit --tx-file {}", tx_file));
+    if !dry_run {
+        cli.transaction_submit(tx_file).await?;
+    }
+
+    render(&Submission { tx_file: tx_file.to_string(), tx_id, submitted: !dry_run }, output)
+}
+
+/// Largest ADA-only UTxOs first until the fee is covered. UTxOs holding
+/// tokens are left alone so they never move by accident.
+fn select_inputs(utxos: &Value, address: &str) -> Result<Vec<(String, u64)>> {
+    let mut candidates: Vec<(String, u64)> = utxos.as_object().into_iter().flatten()
+        .filter(|(_, utxo)| utxo["value"].as_object().is_some_and(|v| v.len() == 1))
+        .filter_map(|(tx_in, utxo)| Some((tx_in.clone(), utxo["value"]["lovelace"].as_u64()?)))
+        .collect();
+    candidates.sort_by_key(|c| std::cmp::Reverse(c.1));
+
+    let mut selected = Vec::new();
+    let mut total = 0;
+    for candidate in candidates {
+        if total >= MIN_INPUT_LOVELACE {
+            break;
+        }
+        total += candidate.1;
+        selected.push(candidate);
+    }
+
+    if selected.is_empty() {
+        return Err(CliError::NotFound(format!("No ADA-only UTxO at {} to pay the fee", address)).into());
+    }
+    if total < MIN_INPUT_LOVELACE {
+        return Err(CliError::NotFound(format!(
+            "ADA-only UTxOs at {} hold {}, {} short of the {} needed to pay the fee",
+            address,
+            format_ada(total),
+            format_ada(MIN_INPUT_LOVELACE - total),
+            format_ada(MIN_INPUT_LOVELACE)
+        )).into());
+    }
+    Ok(selected)
 }
 
 async fn calculate_pool_rewards(
-    _cardano_cli: &CardanoCli,
-    pool: &crate::utils::config::PoolConfig,
+    chain: &ProviderChain,
+    pool: &PoolConfig,
     epoch: u64,
     config: &Config,
 ) -> Result<RewardsReport> {
-    // Simulate rewards calculation (in real implementation, query blockchain data)
+    // Reward history lags the chain by a couple of epochs
+    let history = chain.pool_rewards(pool.pool_id.as_str(), 5).await;
+    let entry = history.as_ref().ok().and_then(|h| h.iter().find(|r| r.epoch == epoch));
+
+    let entry = match entry {
+        Some(entry) => entry,
+        None => {
+            let reason = match &history {
+                Ok(_) => format!("no reward data for epoch {} yet", epoch),
+                Err(e) => e.to_string(),
+            };
+            progress!("{} {}: {} - showing simulated figures", "⚠️".yellow(), pool.name, reason);
+            return Ok(simulated_rewards(pool, epoch, config));
+        }
+    };
+
+    let total_rewards = entry.rewards as f64 / 1_000_000.0;
+    let pool_rewards = entry.fees as f64 / 1_000_000.0;
+    let delegator_rewards = total_rewards - pool_rewards;
+    let delegator_count = match entry.delegator_count {
+        Some(count) => count,
+        None => chain.pool_delegators(pool.pool_id.as_str()).await.map(|d| d.len() as u64).unwrap_or(0),
+    };
+    let average_reward_per_delegator = if delegator_count > 0 {
+        delegator_rewards / delegator_count as f64
+    } else {
+        0.0
+    };
+
+    Ok(RewardsReport {
+        pool_id: pool.pool_id.to_string(),
+        pool_name: pool.name.clone(),
+        epoch,
+        total_rewards,
+        pool_rewards,
+        delegator_rewards,
+        fees: 0.0,
+        delegator_count,
+        average_reward_per_delegator,
+        source: "chain".to_string(),
+    })
+}
+
+fn simulated_rewards(pool: &PoolConfig, epoch: u64, config: &Config) -> RewardsReport {
     let total_rewards = 1500.0; // 1500 ADA total rewards
     let pool_fee_percentage = 5.0; // 5% pool fee
     let pool_rewards = total_rewards * (pool_fee_percentage / 100.0);
@@ -105,8 +444,8 @@ async fn calculate_pool_rewards(
     let delegator_count = 250; // 250 delegators
     let average_reward_per_delegator = delegator_rewards / delegator_count as f64;
 
-    Ok(RewardsReport {
-        pool_id: pool.pool_id.clone(),
+    RewardsReport {
+        pool_id: pool.pool_id.to_string(),
         pool_name: pool.name.clone(),
         epoch,
         total_rewards,
@@ -115,7 +454,8 @@ async fn calculate_pool_rewards(
         fees,
         delegator_count,
         average_reward_per_delegator,
-    })
+        source: "simulated".to_string(),
+    }
 }
 
 fn display_rewards_summary(reports: &[RewardsReport]) {
@@ -145,7 +485,7 @@ fn display_rewards_summary(reports: &[RewardsReport]) {
     println!("  • Total Delegator Rewards: {:.2} ADA", total_delegator_rewards);
 }
 
-async fn display_detailed_rewards_report(report: &RewardsReport) -> Result<()> {
+fn display_detailed_rewards_report(report: &RewardsReport) {
     println!("\n{}", format!("💎 Detailed Rewards: {} (Epoch {})",
                              report.pool_name, report.epoch).blue().bold());
     println!("{}", "=".repeat(60));
@@ -170,8 +510,58 @@ async fn display_detailed_rewards_report(report: &RewardsReport) -> Result<()> {
     println!("\n{}", "📈 Performance Metrics".cyan().bold());
     println!("  • ROA (Return on ADA): {:.2}%", (report.total_rewards / 50000.0) * 100.0); // Assuming 50K ADA stake
     println!("  • Effective Pool Margin: {:.1}%", (report.pool_rewards / report.total_rewards) * 100.0);
+}
 
-    Ok(())
+async fn fetch_reward_account(chain: &ProviderChain, pool: &PoolConfig) -> RewardAccount {
+    let (rewards, error) = match chain.account_rewards(&pool.reward_address).await {
+        Ok(rewards) => (rewards, None),
+        Err(e) => (Vec::new(), Some(e.to_string())),
+    };
+
+    RewardAccount {
+        pool_id: pool.pool_id.to_string(),
+        reward_address: pool.reward_address.clone(),
+        rewards,
+        error,
+    }
+}
+
+fn display_reward_account(account: &RewardAccount) {
+    println!("\n{}", "🏦 Operator Reward Account".cyan().bold());
+
+    if let Some(error) = &account.error {
+        println!("  • Unavailable: {}", error);
+    } else if account.rewards.is_empty() {
+        println!("  • No rewards received yet");
+    } else {
+        for reward in account.rewards.iter().take(5) {
+            println!("  • Epoch {}: {:.2} ADA ({})",
+                     reward.epoch, reward.amount as f64 / 1_000_000.0, reward.kind);
+        }
+    }
+}
+
+fn record_rewards_history(reports: &[RewardsReport], config: &Config) {
+    let store = match HistoryStore::open_or_warn(config) {
+        Some(store) => store,
+        None => return,
+    };
+
+    // Simulated figures must never end up in history
+    for report in reports.iter().filter(|r| r.source != "simulated") {
+        let record = RewardsRecord {
+            pool_id: report.pool_id.clone(),
+            epoch: report.epoch,
+            total_rewards: report.total_rewards,
+            pool_rewards: report.pool_rewards,
+            delegator_rewards: report.delegator_rewards,
+            fees: report.fees,
+            delegator_count: report.delegator_count,
+        };
+        if let Err(e) = store.record_rewards(&record) {
+            log::warn!("Failed to record rewards for {}: {}", report.pool_id, e);
+        }
+    }
 }
 
 async fn export_rewards_report(reports: &[RewardsReport], config: &Config) -> Result<()> {
@@ -186,6 +576,66 @@ async fn export_rewards_report(reports: &[RewardsReport], config: &Config) -> Re
     let json = serde_json::to_string_pretty(reports)?;
     std::fs::write(&filename, json)?;
 
-    println!("\n{} Rewards report exported to: {}", "💾".cyan(), filename);
+    progress!("\n{} Rewards report exported to: {}", "💾".cyan(), filename);
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::error::classify;
+    use serde_json::json;
+
+    fn ada_only(lovelace: u64) -> Value {
+        json!({ "address": "addr1...", "value": { "lovelace": lovelace } })
+    }
+
+    const ADDRESS: &str = "addr_test1vz4ehc4t3mlqkx2ld5c9m9tmdzfn6w4wtkuth3d4n6xk3rqhz7e8a";
+
+    fn shortfall(utxos: &Value) -> String {
+        let error = select_inputs(utxos, ADDRESS).unwrap_err();
+        assert!(matches!(classify(&error), Some(CliError::NotFound(_))), "{:#}", error);
+        error.to_string()
+    }
+
+    #[test]
+    fn largest_ada_only_utxos_are_selected_first() {
+        let utxos = json!({
+            "aa#0": ada_only(500_000),
+            "bb#1": ada_only(1_500_000),
+            "cc#0": ada_only(900_000),
+        });
+        assert_eq!(select_inputs(&utxos, ADDRESS).unwrap(), vec![("bb#1".to_string(), 1_500_000), ("cc#0".to_string(), 900_000)]);
+    }
+
+    #[test]
+    fn one_large_utxo_is_enough() {
+        let utxos = json!({ "aa#0": ada_only(1_000_000), "bb#0": ada_only(50_000_000) });
+        assert_eq!(select_inputs(&utxos, ADDRESS).unwrap(), vec![("bb#0".to_string(), 50_000_000)]);
+    }
+
+    #[test]
+    fn utxos_holding_tokens_are_never_selected() {
+        let utxos = json!({
+            "aa#0": { "value": { "lovelace": 10_000_000, "policy": { "token": 1 } } },
+            "bb#0": ada_only(1_000_000),
+        });
+        let error = shortfall(&utxos);
+        assert!(error.contains("hold 1.000000 ₳, 1.000000 ₳ short of the 2.000000 ₳"), "{}", error);
+    }
+
+    #[test]
+    fn too_little_ada_is_an_error() {
+        let utxos = json!({ "aa#0": ada_only(1_200_000), "bb#0": ada_only(700_000) });
+        assert!(shortfall(&utxos).contains("0.100000 ₳ short"));
+
+        let utxos = json!({ "aa#0": ada_only(1_200_000), "bb#0": ada_only(800_000) });
+        assert_eq!(select_inputs(&utxos, ADDRESS).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn nothing_to_select_from() {
+        assert!(shortfall(&json!({})).starts_with("No ADA-only UTxO"));
+        assert!(shortfall(&Value::Null).starts_with("No ADA-only UTxO"));
+    }
+}
@@ -1,25 +1,308 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use colored::*;
+use serde::Deserialize;
+use serde_json::Value;
 use std::io::{self, Write};
-use crate::utils::config::{Config, PoolConfig, BlockfrostConfig, CardanoConfig, MonitoringConfig, RewardsConfig, AlertsConfig};
+use std::process::Command;
+use crate::utils::config::{self, Config, CardanoConfig};
+use crate::utils::error::CliError;
+use crate::utils::secret::{self, Secret};
 use crate::cardano::blockfrost::BlockfrostClient;
-use std::path::PathBuf;
+use crate::cardano::pool_id::PoolId;
+use std::path::{Path, PathBuf};
+
+/// Setup answers from flags, environment or an answers file. Anything left
+/// unset is prompted for, unless running unattended.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SetupAnswers {
+    pub network: Option<String>,
+    pub blockfrost_key: Option<String>,
+    pub socket: Option<String>,
+    pub cli_path: Option<String>,
+    pub webhook_url: Option<String>,
+    pub email_alerts: Option<bool>,
+    pub rewards_output_directory: Option<String>,
+    pub rewards_output_format: Option<String>,
+    #[serde(default)]
+    pub pools: Vec<PoolAnswer>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PoolAnswer {
+    pub id: String,
+    pub ticker: String,
+    pub name: String,
+    pub vrf_key_file: Option<String>,
+    pub pledge_address: Option<String>,
+    pub reward_address: Option<String>,
+    pub opcert_file: Option<String>,
+}
+
+impl PoolAnswer {
+    /// Parse `--pool id:ticker:name`; the name may itself contain colons.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut parts = spec.splitn(3, ':');
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(id), Some(ticker), Some(name)) if !id.is_empty() && !ticker.is_empty() && !name.is_empty() => Ok(PoolAnswer {
+                id: id.to_string(),
+                ticker: ticker.to_string(),
+                name: name.to_string(),
+                vrf_key_file: None,
+                pledge_address: None,
+                reward_address: None,
+                opcert_file: None,
+            }),
+            _ => Err(CliError::Config(format!("Invalid --pool '{}' - expected id:ticker:name", spec)).into()),
+        }
+    }
+}
+
+impl SetupAnswers {
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| CliError::Config(format!("Failed to read answers file {}: {}", path.display(), e)))?;
+        toml::from_str(&content)
+            .map_err(|e| CliError::Config(format!("Invalid answers file {}: {}", path.display(), e)).into())
+    }
+
+    /// Fill unset values from `other`; values already set win.
+    pub fn or(self, other: SetupAnswers) -> Self {
+        SetupAnswers {
+            network: self.network.or(other.network),
+            blockfrost_key: self.blockfrost_key.or(other.blockfrost_key),
+            socket: self.socket.or(other.socket),
+            cli_path: self.cli_path.or(other.cli_path),
+            webhook_url: self.webhook_url.or(other.webhook_url),
+            email_alerts: self.email_alerts.or(other.email_alerts),
+            rewards_output_directory: self.rewards_output_directory.or(other.rewards_output_directory),
+            rewards_output_format: self.rewards_output_format.or(other.rewards_output_format),
+            pools: if self.pools.is_empty() { other.pools } else { self.pools },
+        }
+    }
+}
+
+/// How the wizard gets values it does not have yet.
+struct Wizard {
+    /// Never prompt: a missing value is an error and confirmations are answered yes.
+    unattended: bool,
+}
+
+impl Wizard {
+    fn value(&self, given: Option<String>, what: &str, ask: impl FnOnce() -> Result<String>) -> Result<String> {
+        match given {
+            Some(value) => Ok(value),
+            None if self.unattended => Err(CliError::Config(format!("{} is required when running unattended", what)).into()),
+            None => ask(),
+        }
+    }
+
+    /// Like `value`, but an unattended run falls back to `default`.
+    fn value_or_default(&self, given: Option<String>, default: &str, ask: impl FnOnce() -> Result<String>) -> Result<String> {
+        match given {
+            Some(value) => Ok(value),
+            None if self.unattended => Ok(default.to_string()),
+            None => ask(),
+        }
+    }
+
+    fn confirm(&self, message: &str, default: bool) -> Result<bool> {
+        if self.unattended {
+            println!("{} [assuming yes]", message);
+            return Ok(true);
+        }
+        prompt_yes_no(message, default)
+    }
+}
+
+/// Run the wizard. Answers are merged into the existing config file, if any,
+/// and the resulting changes are shown before anything is written.
+pub async fn execute(config_path: Option<&Path>, answers: SetupAnswers, unattended: bool) -> Result<()> {
+    let wizard = Wizard { unattended };
+
+    if unattended {
+        println!("🚀 Cardano Validator CLI Setup (unattended)");
+    } else {
+        println!("🚀 Welcome to Cardano Validator CLI Setup!");
+        println!("This wizard will help you configure your stake pool monitoring.\n");
+    }
+
+    let target = get_config_path(config_path);
+    let original = if target.exists() {
+        let content = std::fs::read_to_string(&target)
+            .with_context(|| format!("Failed to read {}", target.display()))?;
+        println!("Updating existing configuration {}", target.display());
+        Some(content)
+    } else {
+        None
+    };
+    let existing: toml::Value = match &original {
+        Some(content) => toml::from_str(content)
+            .map_err(|e| CliError::Config(format!("Invalid existing config {}: {}", target.display(), e)))?,
+        None => toml::Value::Table(toml::map::Map::new()),
+    };
+    let current = |path: &[&str]| lookup(&existing, path).and_then(|v| v.as_str()).map(str::to_string);
+
+    let network_default = current(&["cardano", "network"]).unwrap_or_else(|| "mainnet".to_string());
+    let network = wizard.value_or_default(answers.network, &network_default, || prompt_choice(
+        "Select your network",
+        NETWORKS,
+        &network_default
+    ))?;
+    if !NETWORKS.contains(&network.as_str()) {
+        return Err(CliError::Config(format!("Unknown network '{}' - expected one of {}", network, NETWORKS.join(", "))).into());
+    }
 
-pub async fn execute() -> Result<()> {
-    println!("🚀 Welcome to Cardano Validator CLI Setup!");
-    println!("This wizard will help you configure your stake pool monitoring.\n");
+    // Get node configuration
+    println!("\n🖥️  Setting up the node connection...");
+    let detected_cli = detect_cli_path();
+    let detected_socket = detect_socket_path();
+    if let Some(path) = &detected_cli {
+        println!("  Found cardano-cli at {}", path);
+    }
+    if let Some(path) = &detected_socket {
+        println!("  Found node socket at {}", path);
+    }
+    let cli_default = current(&["cardano", "cli_path"]).or(detected_cli).unwrap_or_else(|| "cardano-cli".to_string());
+    let cli_path = wizard.value_or_default(answers.cli_path, &cli_default, || prompt_with_default(
+        "cardano-cli path",
+        &cli_default
+    ))?;
+    let socket_default = current(&["cardano", "node_socket_path"]).or(detected_socket).unwrap_or_else(|| DEFAULT_SOCKET.to_string());
+    let socket = wizard.value_or_default(answers.socket, &socket_default, || prompt_with_default(
+        "Node socket path",
+        &socket_default
+    ))?;
 
-    // Get Blockfrost configuration
-    let blockfrost_config = setup_blockfrost().await?;
+    // Get Blockfrost configuration. A key the config already resolves is
+    // kept, along with wherever it is read from.
+    let existing_blockfrost = lookup(&existing, &["blockfrost"]).and_then(|v| v.as_table()).cloned().unwrap_or_default();
+    let configured_key = configured_blockfrost_key(&existing_blockfrost, config_path)
+        .filter(|key| validate_blockfrost_key(key.expose(), &network));
+    let keep_configured_key = match (&answers.blockfrost_key, &configured_key) {
+        (None, Some(_)) => wizard.unattended || prompt_yes_no("Keep the configured Blockfrost API key?", true)?,
+        _ => false,
+    };
+    let (blockfrost_key, from_env) = match (configured_key, answers.blockfrost_key) {
+        (Some(key), _) if keep_configured_key => (key, false),
+        (_, Some(key)) => (setup_blockfrost(&wizard, &network, Some(key))?, false),
+        (_, None) => match std::env::var(BLOCKFROST_KEY_ENV).ok().filter(|k| !k.is_empty()) {
+            Some(key) => (setup_blockfrost(&wizard, &network, Some(key))?, true),
+            None => (setup_blockfrost(&wizard, &network, None)?, false),
+        },
+    };
 
     // Get pool configuration
-    let pools = setup_pools().await?;
+    let existing_pools = lookup(&existing, &["pools"]).and_then(|v| v.as_array()).cloned().unwrap_or_default();
+    let pools = setup_pools(&wizard, answers.pools, &existing_pools)?;
+
+    // Get alert and report settings
+    println!("\n🔔 Setting up alerts and reports...");
+    let webhook_default = current(&["monitoring", "alerts", "webhook_url"]).unwrap_or_default();
+    let webhook_url = wizard.value_or_default(answers.webhook_url, &webhook_default, || prompt_with_default(
+        "Alert webhook URL (blank for none)",
+        &webhook_default
+    ))?;
+    let email_default = lookup(&existing, &["monitoring", "alerts", "email_enabled"]).and_then(|v| v.as_bool()).unwrap_or(false);
+    let email_enabled = match answers.email_alerts {
+        Some(enabled) => enabled,
+        None if unattended => email_default,
+        None => prompt_yes_no("Enable email alerts?", email_default)?,
+    };
+    let directory_default = current(&["rewards", "output_directory"]).unwrap_or_else(|| "./reports".to_string());
+    let output_directory = wizard.value_or_default(answers.rewards_output_directory, &directory_default, || prompt_with_default(
+        "Rewards report directory",
+        &directory_default
+    ))?;
+    let format_default = current(&["rewards", "output_format"]).unwrap_or_else(|| "json".to_string());
+    let output_format = wizard.value_or_default(answers.rewards_output_format, &format_default, || prompt_choice(
+        "Rewards report format",
+        REPORT_FORMATS,
+        &format_default
+    ))?;
+    if !REPORT_FORMATS.contains(&output_format.as_str()) {
+        return Err(CliError::Config(format!("Unknown rewards output format '{}' - expected one of {}", output_format, REPORT_FORMATS.join(", "))).into());
+    }
+
+    // Only the settings the wizard asked about are written; everything else
+    // in the existing file is kept as it was.
+    let mut updated = existing.clone();
+    if original.is_none() {
+        let mut defaults = Config::default();
+        defaults.pools.clear();
+        updated = toml::Value::try_from(&defaults)?;
+    }
+    // Only the Blockfrost keys the wizard asked about change; a custom
+    // base_url (e.g. a proxy) survives, the default follows the network
+    let mut blockfrost = existing_blockfrost;
+    let base_url = blockfrost.get("base_url").and_then(|v| v.as_str());
+    if base_url.is_none_or(|url| url == blockfrost_url(&network_default)) {
+        blockfrost.insert("base_url".to_string(), blockfrost_url(&network).into());
+    }
+    if !keep_configured_key {
+        // A new key replaces whichever key source was configured before
+        for key in ["api_key", "api_key_file", "api_key_env"] {
+            blockfrost.remove(key);
+        }
+        if from_env {
+            blockfrost.insert("api_key_env".to_string(), BLOCKFROST_KEY_ENV.into());
+        } else {
+            blockfrost.insert("api_key".to_string(), blockfrost_key.expose().into());
+        }
+    }
+    let patch = [
+        (&["cardano", "cli_path"][..], toml::Value::from(cli_path)),
+        (&["cardano", "node_socket_path"], socket.into()),
+        (&["cardano", "network"], network.clone().into()),
+        (&["monitoring", "alerts", "webhook_url"], webhook_url.into()),
+        (&["monitoring", "alerts", "email_enabled"], email_enabled.into()),
+        (&["rewards", "output_directory"], output_directory.into()),
+        (&["rewards", "output_format"], output_format.into()),
+    ];
+    if let Some(table) = updated.as_table_mut() {
+        table.insert("blockfrost".to_string(), toml::Value::Table(blockfrost));
+    }
+    for (path, value) in patch {
+        let nested = path.iter().rev().fold(value, |value, key| {
+            toml::Value::Table(toml::map::Map::from_iter([(key.to_string(), value)]))
+        });
+        config::merge_values(&mut updated, nested);
+    }
+    merge_pools(&mut updated, pools);
+
+    // The connectivity test needs a complete config, even when the file
+    // being updated is only one layer of it.
+    let mut complete = toml::Value::try_from(Config::default())?;
+    config::merge_values(&mut complete, updated.clone());
+    let mut config: Config = complete.try_into()
+        .map_err(|e| CliError::Config(format!("Setup produced an invalid configuration: {}", e)))?;
+    if let Some(blockfrost) = config.blockfrost.as_mut() {
+        blockfrost.api_key = blockfrost_key;
+    }
 
     // Test the configuration
     println!("\n🔍 Testing configuration...");
-    test_configuration(&blockfrost_config, &pools).await?;
+    test_configuration(&wizard, &config).await?;
 
     // Save configuration
-    save_configuration(&blockfrost_config, &pools)?;
+    let updated_toml = toml::to_string_pretty(&updated)?;
+    if let Some(original) = &original {
+        if updated == existing {
+            println!("\n✅ {} is already up to date.", target.display());
+            return Ok(());
+        }
+        // Diff the re-serialized original so key order alone shows no change
+        println!("\n📝 Changes to {}:", target.display());
+        print_diff(&toml::to_string_pretty(&existing)?, &updated_toml);
+        if original.lines().any(|line| line.trim_start().starts_with('#')) {
+            println!("{}", "Note: comments in the existing file are not kept.".yellow());
+        }
+        if !wizard.confirm("Write these changes?", true)? {
+            anyhow::bail!("Setup cancelled");
+        }
+    }
+    save_configuration(&updated_toml, &target)?;
 
     println!("\n✅ Setup complete! You can now run:");
     println!("   cargo run -- monitor");
@@ -28,154 +311,284 @@ pub async fn execute() -> Result<()> {
     Ok(())
 }
 
-async fn setup_blockfrost() -> Result<BlockfrostConfig> {
-    println!("🔑 Setting up Blockfrost API...");
-    println!("Visit https://blockfrost.io to get a free API key\n");
+const NETWORKS: &[&str] = &["mainnet", "preprod", "preview", "testnet"];
+const REPORT_FORMATS: &[&str] = &["json", "csv"];
+const DEFAULT_SOCKET: &str = "/opt/cardano/cnode/sockets/node.socket";
+/// Read when `--blockfrost-key` is not given, keeping the key out of `ps`.
+const BLOCKFROST_KEY_ENV: &str = "BLOCKFROST_PROJECT_ID";
+
+/// Where CNTools/guild-operators, the cardano-node NixOS module and the
+/// official docker image put the binary and socket. `~` is `$HOME`.
+const CLI_CANDIDATES: &[&str] = &[
+    "~/.local/bin/cardano-cli",
+    "/opt/cardano/cnode/bin/cardano-cli",
+    "/usr/local/bin/cardano-cli",
+];
+const SOCKET_CANDIDATES: &[&str] = &[
+    "/opt/cardano/cnode/sockets/node.socket",
+    "/opt/cardano/cnode/sockets/node0.socket",
+    "/run/cardano-node/node.socket",
+    "/ipc/node.socket",
+    "~/cardano/db/node.socket",
+];
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+fn detect_cli_path() -> Option<String> {
+    let on_path = std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).map(|dir| dir.join("cardano-cli")).collect::<Vec<_>>())
+        .unwrap_or_default();
+    on_path.into_iter()
+        .chain(CLI_CANDIDATES.iter().map(|p| expand_home(p)))
+        .find(|path| {
+            path.is_file() && Command::new(path).arg("--version").output().is_ok_and(|o| o.status.success())
+        })
+        .map(|path| path.display().to_string())
+}
 
-    let network = prompt_choice(
-        "Select your network",
-        &["mainnet", "testnet", "preprod"],
-        "mainnet"
-    )?;
+fn detect_socket_path() -> Option<String> {
+    std::env::var("CARDANO_NODE_SOCKET_PATH").ok().map(PathBuf::from)
+        .into_iter()
+        .chain(SOCKET_CANDIDATES.iter().map(|p| expand_home(p)))
+        .find(|path| is_socket(path))
+        .map(|path| path.display().to_string())
+}
+
+#[cfg(unix)]
+fn is_socket(path: &Path) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+    std::fs::metadata(path).is_ok_and(|m| m.file_type().is_socket())
+}
+
+#[cfg(not(unix))]
+fn is_socket(path: &Path) -> bool {
+    path.exists()
+}
+
+/// `cardano-cli query tip` against the chosen node, bypassing the query cache.
+fn probe_tip(cardano: &CardanoConfig) -> Result<Value> {
+    let magic = cardano.network_magic().to_string();
+    let network_args: Vec<&str> = if cardano.network == "mainnet" && cardano.testnet_magic.is_none() {
+        vec!["--mainnet"]
+    } else {
+        vec!["--testnet-magic", &magic]
+    };
+    let output = Command::new(&cardano.cli_path)
+        .args(["query", "tip", "--socket-path", &cardano.node_socket_path])
+        .args(&network_args)
+        .output()
+        .map_err(|e| CliError::NodeUnreachable(format!("Failed to run {}: {}", cardano.cli_path, e)))?;
+    if !output.status.success() {
+        return Err(CliError::NodeUnreachable(String::from_utf8_lossy(&output.stderr).trim().to_string()).into());
+    }
+    serde_json::from_slice(&output.stdout).context("Failed to parse JSON response from cardano-cli")
+}
+
+fn blockfrost_url(network: &str) -> String {
+    format!("https://cardano-{}.blockfrost.io/api/v0", network)
+}
+
+/// The project ID the existing `[blockfrost]` table, or failing that the
+/// whole config, already resolves to.
+fn configured_blockfrost_key(blockfrost: &toml::map::Map<String, toml::Value>, config_path: Option<&Path>) -> Option<Secret> {
+    let field = |key: &str| blockfrost.get(key).and_then(|v| v.as_str());
+    let inline = Secret::new(field("api_key").unwrap_or_default());
+    secret::resolve("blockfrost.api_key", &inline, field("api_key_file"), field("api_key_env")).ok()
+        .filter(|key| !key.is_empty())
+        .or_else(|| Config::load(config_path, None).ok()?.blockfrost.map(|b| b.api_key).filter(|key| !key.is_empty()))
+}
+
+fn setup_blockfrost(wizard: &Wizard, network: &str, api_key: Option<String>) -> Result<Secret> {
+    if api_key.is_none() && !wizard.unattended {
+        println!("\n🔑 Setting up Blockfrost API...");
+        println!("Visit https://blockfrost.io to get a free API key\n");
+    }
 
-    let api_key = prompt(&format!(
+    let what = format!("--blockfrost-key (or ${})", BLOCKFROST_KEY_ENV);
+    let api_key = wizard.value(api_key, &what, || prompt(&format!(
         "Enter your Blockfrost API key for {} (starts with '{}'): ",
         network,
         network
-    ))?;
+    )))?;
 
-    if !validate_blockfrost_key(&api_key, &network) {
-        anyhow::bail!("Invalid Blockfrost API key format. Should start with '{}'", network);
+    if !validate_blockfrost_key(&api_key, network) {
+        return Err(CliError::Config(format!("Invalid Blockfrost API key format. Should start with '{}'", network)).into());
     }
 
-    let base_url = match network.as_str() {
-        "mainnet" => "https://cardano-mainnet.blockfrost.io/api/v0",
-        "testnet" => "https://cardano-testnet.blockfrost.io/api/v0",
-        "preprod" => "https://cardano-preprod.blockfrost.io/api/v0",
-        _ => anyhow::bail!("Invalid network"),
-    };
-
-    Ok(BlockfrostConfig {
-        api_key,
-        base_url: base_url.to_string(),
-    })
+    Ok(Secret::new(api_key))
 }
 
-async fn setup_pools() -> Result<Vec<PoolConfig>> {
+/// Pool entries to merge into `[[pools]]` by `pool_id`. Fields of an already
+/// configured pool default to its current values.
+fn setup_pools(wizard: &Wizard, answers: Vec<PoolAnswer>, existing: &[toml::Value]) -> Result<Vec<toml::map::Map<String, toml::Value>>> {
+    let find = |pool_id: &PoolId| existing.iter().find(|p| entry_pool_id(p).as_ref() == Some(pool_id));
+
+    if !answers.is_empty() {
+        return answers.into_iter().map(|answer| {
+            let pool_id: PoolId = answer.id.parse()
+                .map_err(|e| CliError::Config(format!("Invalid pool ID: {}", e)))?;
+            let known = find(&pool_id).is_some();
+            let mut pool = toml::map::Map::new();
+            pool.insert("pool_id".to_string(), pool_id.to_string().into());
+            pool.insert("name".to_string(), answer.name.into());
+            pool.insert("ticker".to_string(), answer.ticker.into());
+            let optional = [
+                ("vrf_key_file", answer.vrf_key_file, DEFAULT_VRF_KEY),
+                ("pledge_address", answer.pledge_address, ""),
+                ("reward_address", answer.reward_address, ""),
+                ("opcert_file", answer.opcert_file, DEFAULT_OPCERT),
+            ];
+            for (key, value, default) in optional {
+                match value {
+                    Some(value) => { pool.insert(key.to_string(), value.into()); }
+                    None if !known => { pool.insert(key.to_string(), default.into()); }
+                    None => {}
+                }
+            }
+            Ok(pool)
+        }).collect();
+    }
+    if wizard.unattended {
+        if existing.is_empty() {
+            return Err(CliError::Config("At least one --pool id:ticker:name is required when running unattended".to_string()).into());
+        }
+        return Ok(Vec::new());
+    }
+
     println!("\n🏊 Setting up your stake pools...");
+    for pool in existing {
+        let field = |key: &str| pool.get(key).and_then(|v| v.as_str()).unwrap_or("");
+        println!("  Configured: {} ({})", field("ticker"), field("pool_id"));
+    }
 
     let mut pools = Vec::new();
 
+    if !existing.is_empty() && !prompt_yes_no("Add or update a pool?", false)? {
+        return Ok(pools);
+    }
+
     loop {
         println!("\nAdding pool #{}", pools.len() + 1);
 
-        let pool_id = prompt("Enter your stake pool ID (pool1...): ")?;
-        if !validate_pool_id(&pool_id) {
-            println!("❌ Invalid pool ID format. Should start with 'pool1' and be 56 characters long.");
-            continue;
-        }
-
-        let name = prompt("Enter pool name: ")?;
-        let ticker = prompt("Enter pool ticker: ")?;
-
-        // Required fields - use defaults for now
-        let vrf_key_file = prompt_with_default(
+        let pool_id = match prompt("Enter your stake pool ID (pool1... or hex): ")?.parse::<PoolId>() {
+            Ok(pool_id) => pool_id,
+            Err(e) => {
+                println!("❌ Invalid pool ID: {}", e);
+                continue;
+            }
+        };
+        let current = find(&pool_id);
+        let default = |key: &str, fallback: &str| current
+            .and_then(|p| p.get(key))
+            .and_then(|v| v.as_str())
+            .unwrap_or(fallback)
+            .to_string();
+
+        let mut pool = toml::map::Map::new();
+        pool.insert("name".to_string(), prompt_with_default("Pool name", &default("name", ""))?.into());
+        pool.insert("ticker".to_string(), prompt_with_default("Pool ticker", &default("ticker", ""))?.into());
+        pool.insert("vrf_key_file".to_string(), prompt_with_default(
             "VRF key file path",
-            "/opt/cardano/keys/vrf.vkey"
-        )?;
-
-        let pledge_address = prompt_with_default(
+            &default("vrf_key_file", DEFAULT_VRF_KEY)
+        )?.into());
+        pool.insert("pledge_address".to_string(), prompt_with_default(
             "Pledge address",
-            "addr1..."
-        )?;
-
-        let reward_address = prompt_with_default(
+            &default("pledge_address", "")
+        )?.into());
+        pool.insert("reward_address".to_string(), prompt_with_default(
             "Reward address",
-            "stake1..."
-        )?;
-
-        pools.push(PoolConfig {
-            pool_id,
-            name,
-            ticker,
-            vrf_key_file,
-            pledge_address,
-            reward_address,
-        });
+            &default("reward_address", "")
+        )?.into());
+        pool.insert("opcert_file".to_string(), prompt_with_default(
+            "Operational certificate file path",
+            &default("opcert_file", DEFAULT_OPCERT)
+        )?.into());
+        pool.insert("pool_id".to_string(), pool_id.to_string().into());
+        pools.push(pool);
 
         if !prompt_yes_no("Add another pool?", false)? {
             break;
         }
     }
 
-    if pools.is_empty() {
-        anyhow::bail!("At least one pool is required");
+    Ok(pools)
+}
+
+const DEFAULT_VRF_KEY: &str = "/opt/cardano/keys/vrf.vkey";
+const DEFAULT_OPCERT: &str = "/opt/cardano/keys/node.cert";
+
+/// Update pools with a matching `pool_id` in place and append the rest.
+fn merge_pools(config: &mut toml::Value, pools: Vec<toml::map::Map<String, toml::Value>>) {
+    let Some(table) = config.as_table_mut() else { return };
+    let entries = table.entry("pools").or_insert_with(|| toml::Value::Array(Vec::new()));
+    let Some(entries) = entries.as_array_mut() else { return };
+
+    for pool in pools {
+        let pool_id = pool.get("pool_id").and_then(|v| v.as_str()).and_then(|id| id.parse::<PoolId>().ok());
+        match entries.iter_mut().find(|entry| entry_pool_id(entry).is_some() && entry_pool_id(entry) == pool_id) {
+            Some(entry) => config::merge_values(entry, toml::Value::Table(pool)),
+            None => entries.push(toml::Value::Table(pool)),
+        }
     }
+}
 
-    Ok(pools)
+/// The pool ID of a `[[pools]]` entry, in whichever form it was written.
+fn entry_pool_id(entry: &toml::Value) -> Option<PoolId> {
+    entry.get("pool_id")?.as_str()?.parse().ok()
 }
 
-async fn test_configuration(blockfrost_config: &BlockfrostConfig, pools: &[PoolConfig]) -> Result<()> {
-    // Create a temporary config for testing
-    let test_config = Config {
-        cardano: CardanoConfig {
-            cli_path: "cardano-cli".to_string(),
-            node_socket_path: "/tmp/socket".to_string(),
-            network: "mainnet".to_string(),
-            testnet_magic: None,
-        },
-        blockfrost: Some(blockfrost_config.clone()),
-        pools: pools.to_vec(),
-        monitoring: MonitoringConfig {
-            enabled: true,
-            prometheus_port: 9090,
-            check_interval_seconds: 30,
-            metrics_interval: 30, // Add missing field
-            alerts: AlertsConfig {
-                saturation_threshold: 0.8,
-                missed_blocks_threshold: 2,
-                email_enabled: false,
-                webhook_url: "".to_string(),
-            },
-        },
-        rewards: RewardsConfig {
-            calculation_method: "standard".to_string(), // Add missing field
-            output_format: "json".to_string(),          // Add missing field
-            output_directory: "./reports".to_string(),
-            include_fees: true,
-            delegation_rewards_percentage: 95.0,
-        },
-    };
+fn lookup<'a>(value: &'a toml::Value, path: &[&str]) -> Option<&'a toml::Value> {
+    path.iter().try_fold(value, |value, key| value.get(key))
+}
+
+async fn test_configuration(wizard: &Wizard, config: &Config) -> Result<()> {
+    // Test the node
+    print!("  ✓ Testing node connection (cardano-cli query tip)... ");
+    io::stdout().flush()?;
+
+    match probe_tip(&config.cardano) {
+        Ok(tip) => {
+            let sync = tip["syncProgress"].as_str().unwrap_or("?");
+            println!("✅ Slot {} ({}% synced)", tip["slot"], sync);
+        }
+        Err(e) => {
+            println!("⚠️  {}", e);
+            if !wizard.confirm("Node is not reachable from here. Continue anyway?", true)? {
+                anyhow::bail!("Setup cancelled");
+            }
+        }
+    }
 
     // Test Blockfrost connection
     print!("  ✓ Testing Blockfrost API connection... ");
     io::stdout().flush()?;
 
-    if let Some(client) = BlockfrostClient::new(&test_config) {
-        match client.get_network_info().await {
-            Ok(_) => println!("✅ Success!"),
-            Err(e) => {
-                println!("❌ Failed: {}", e);
-                anyhow::bail!("Blockfrost API test failed. Please check your API key.");
-            }
+    let client = BlockfrostClient::new(config)
+        .ok_or_else(|| anyhow::anyhow!("Failed to create Blockfrost client"))?;
+    match client.get_network_info().await {
+        Ok(_) => println!("✅ Success!"),
+        Err(e) => {
+            println!("❌ Failed: {}", e);
+            return Err(e.context("Blockfrost API test failed. Please check your API key."));
         }
-    } else {
-        anyhow::bail!("Failed to create Blockfrost client");
     }
 
     // Test pool existence
-    for pool in pools {
+    for pool in &config.pools {
         print!("  ✓ Testing pool {} ({})... ", pool.ticker, pool.pool_id);
         io::stdout().flush()?;
 
-        if let Some(client) = BlockfrostClient::new(&test_config) {
-            match client.get_pool_info(&pool.pool_id).await {
-                Ok(_) => println!("✅ Found!"),
-                Err(_) => {
-                    println!("⚠️  Pool not found or inactive");
-                    if !prompt_yes_no("Continue anyway?", true)? {
-                        anyhow::bail!("Setup cancelled");
-                    }
+        match client.get_pool_info(pool.pool_id.as_str()).await {
+            Ok(_) => println!("✅ Found!"),
+            Err(_) => {
+                println!("⚠️  Pool not found or inactive");
+                if !wizard.confirm("Continue anyway?", true)? {
+                    anyhow::bail!("Setup cancelled");
                 }
             }
         }
@@ -184,54 +597,92 @@ async fn test_configuration(blockfrost_config: &BlockfrostConfig, pools: &[PoolC
     Ok(())
 }
 
-fn save_configuration(blockfrost_config: &BlockfrostConfig, pools: &[PoolConfig]) -> Result<()> {
-    let config = Config {
-        cardano: CardanoConfig {
-            cli_path: "cardano-cli".to_string(),
-            node_socket_path: "/opt/cardano/cnode/sockets/node0.socket".to_string(),
-            network: "mainnet".to_string(),
-            testnet_magic: None,
-        },
-        blockfrost: Some(blockfrost_config.clone()),
-        pools: pools.to_vec(),
-        monitoring: MonitoringConfig {
-            enabled: true,
-            prometheus_port: 9090,
-            check_interval_seconds: 30,
-            metrics_interval: 30, // Add missing field
-            alerts: AlertsConfig {
-                saturation_threshold: 0.8,
-                missed_blocks_threshold: 2,
-                email_enabled: false,
-                webhook_url: "".to_string(),
-            },
-        },
-        rewards: RewardsConfig {
-            calculation_method: "standard".to_string(), // Add missing field
-            output_format: "json".to_string(),          // Add missing field
-            output_directory: "./reports".to_string(),
-            include_fees: true,
-            delegation_rewards_percentage: 95.0,
-        },
-    };
+/// Print a line diff of `old` to `new`, with two lines of context around
+/// each change.
+fn print_diff(old: &str, new: &str) {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // Longest common subsequence table, filled from the end
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines: Vec<(char, &str)> = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push((' ', old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            lines.push(('-', old[i]));
+            i += 1;
+        } else {
+            lines.push(('+', new[j]));
+            j += 1;
+        }
+    }
 
-    let config_path = get_config_path();
-    let config_toml = toml::to_string_pretty(&config)?;
+    const CONTEXT: usize = 2;
+    let changed: Vec<usize> = lines.iter().enumerate().filter(|(_, (op, _))| *op != ' ').map(|(n, _)| n).collect();
+    let near_change = |n: usize| changed.iter().any(|&c| c.abs_diff(n) <= CONTEXT);
+    let mut skipped = false;
+    for (n, (op, line)) in lines.iter().enumerate() {
+        if !near_change(n) {
+            skipped = true;
+            continue;
+        }
+        if skipped {
+            println!("{}", "  ...".dimmed());
+            skipped = false;
+        }
+        match op {
+            '+' => println!("{}", format!("+ {}", line).green()),
+            '-' => println!("{}", format!("- {}", line).red()),
+            _ => println!("  {}", line),
+        }
+    }
+}
 
+fn save_configuration(config_toml: &str, config_path: &Path) -> Result<()> {
     // Create directory if it doesn't exist
-    if let Some(parent) = config_path.parent() {
+    if let Some(parent) = config_path.parent().filter(|p| !p.as_os_str().is_empty()) {
         std::fs::create_dir_all(parent)?;
     }
 
-    std::fs::write(&config_path, config_toml)?;
+    // The file may hold the Blockfrost key, so it is owner-only before anything is written
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(config_path)?;
+
+    // An existing file keeps its mode on open
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    }
+    file.write_all(config_toml.as_bytes())?;
 
     println!("💾 Configuration saved to: {}", config_path.display());
 
     Ok(())
 }
 
-fn get_config_path() -> PathBuf {
-    PathBuf::from("config.toml")
+fn get_config_path(explicit: Option<&Path>) -> PathBuf {
+    explicit.map_or_else(config::project_config_path, PathBuf::from)
 }
 
 // Helper functions for user input
@@ -259,7 +710,8 @@ fn prompt_choice(message: &str, choices: &[&str], default: &str) -> Result<Strin
         println!("  {}. {}{}", i + 1, choice, marker);
     }
 
-    let input = prompt("Enter choice [1]: ")?;
+    let default_index = choices.iter().position(|c| *c == default).map_or(1, |i| i + 1);
+    let input = prompt(&format!("Enter choice [{}]: ", default_index))?;
     if input.is_empty() {
         Ok(default.to_string())
     } else {
@@ -290,10 +742,31 @@ fn prompt_yes_no(message: &str, default: bool) -> Result<bool> {
     }
 }
 
-fn validate_pool_id(pool_id: &str) -> bool {
-    pool_id.starts_with("pool1") && pool_id.len() == 56
-}
-
 fn validate_blockfrost_key(key: &str, network: &str) -> bool {
     key.len() > 10 && key.starts_with(network)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn saved_configuration_is_owner_only() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("cvc-setup-{}", std::process::id()));
+        let path = dir.join("config.toml");
+        save_configuration("[cardano]\n", &path).unwrap();
+        let created = std::fs::metadata(&path).unwrap().permissions().mode() & 0o777;
+
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+        save_configuration("[cardano]\nnetwork = \"preview\"\n", &path).unwrap();
+        let rewritten = std::fs::metadata(&path).unwrap().permissions().mode() & 0o777;
+        let content = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!((created, rewritten), (0o600, 0o600));
+        assert_eq!(content, "[cardano]\nnetwork = \"preview\"\n");
+    }
+}
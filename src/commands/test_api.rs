@@ -1,75 +1,230 @@
 use anyhow::Result;
 use colored::*;
+use serde::Serialize;
+use serde_json::{json, Value};
 use crate::utils::config::Config;
+use crate::utils::error::{classify, CliError};
+use crate::utils::output::{render, OutputFormat, Render};
+use crate::progress;
 
-pub async fn execute(config: &Config) -> Result<()> {
-    println!("{}", "🧪 Testing Blockfrost API Connection".blue().bold());
-    println!("{}", "=".repeat(50).blue());
+#[derive(Debug, Default, Serialize)]
+pub struct ApiTestReport {
+    pub checks: Vec<ApiCheck>,
+    #[serde(skip)]
+    first_failure: Option<CliError>,
+}
 
-    // Import BlockfrostClient directly
-    use crate::cardano::blockfrost::BlockfrostClient;
+#[derive(Debug, Serialize)]
+pub struct ApiCheck {
+    pub service: String,
+    pub check: String,
+    pub ok: bool,
+    pub details: Value,
+    pub error: Option<String>,
+}
 
-    match BlockfrostClient::new(config) {
-        Some(client) => {
-            println!("{} Blockfrost client created successfully", "✅".green());
-
-            // Test 1: Get network info
-            println!("\n{}", "📡 Testing network info...".cyan());
-            match client.get_network_info().await {
-                Ok(network_info) => {
-                    println!("{} Network info retrieved!", "✅".green());
-                    println!("   Supply: {}", network_info["supply"]["circulating"].as_str().unwrap_or("N/A"));
-                    println!("   Max Supply: {}", network_info["supply"]["max"].as_str().unwrap_or("N/A"));
-                    println!("   Stake: {}", network_info["stake"]["active"].as_str().unwrap_or("N/A"));
-                },
-                Err(e) => {
-                    println!("{} Failed to get network info: {}", "❌".red(), e);
-                    return Err(e);
+impl ApiTestReport {
+    /// Record one check and print it as it completes. Returns whether it passed.
+    fn record(&mut self, service: &str, check: &str, result: Result<Value>) -> bool {
+        let (ok, details, error) = match result {
+            Ok(details) => {
+                progress!("{} {} {}: OK", "✅".green(), service, check);
+                if let Some(fields) = details.as_object() {
+                    for (key, value) in fields {
+                        progress!("   {}: {}", key, value.as_str().map_or(value.to_string(), String::from));
+                    }
                 }
+                (true, details, None)
             }
-
-            // Test 2: Get latest epoch
-            println!("\n{}", "📅 Testing latest epoch...".cyan());
-            match client.get_latest_epoch().await {
-                Ok(epoch_info) => {
-                    println!("{} Latest epoch retrieved!", "✅".green());
-                    println!("   Epoch: {}", epoch_info["epoch"].as_u64().unwrap_or(0));
-                    println!("   Start Time: {}", epoch_info["start_time"].as_u64().unwrap_or(0));
-                    println!("   End Time: {}", epoch_info["end_time"].as_u64().unwrap_or(0));
-                },
-                Err(e) => {
-                    println!("{} Failed to get epoch info: {}", "❌".red(), e);
-                    return Err(e);
+            Err(e) => {
+                progress!("{} {} {} failed: {}", "❌".red(), service, check, e);
+                if self.first_failure.is_none() {
+                    self.first_failure = Some(classify(&e).cloned().unwrap_or(CliError::CheckFailed(String::new())));
                 }
+                (false, Value::Null, Some(e.to_string()))
             }
+        };
 
-            // Test 3: Get first 5 pools
-            println!("\n{}", "🏊 Testing pool data...".cyan());
-            match client.get_all_pools().await {
-                Ok(pools) => {
-                    println!("{} Pool list retrieved!", "✅".green());
-                    if let Some(pools_array) = pools.as_array() {
-                        println!("   Found {} pools", pools_array.len());
-                        for (i, pool_id) in pools_array.iter().take(3).enumerate() {
-                            if let Some(pool_id_str) = pool_id.as_str() {
-                                println!("   {}. {}", i + 1, pool_id_str);
-                            }
-                        }
-                    }
-                },
-                Err(e) => {
-                    println!("{} Failed to get pool list: {}", "❌".red(), e);
-                    return Err(e);
-                }
+        self.checks.push(ApiCheck {
+            service: service.to_string(),
+            check: check.to_string(),
+            ok,
+            details,
+            error,
+        });
+        ok
+    }
+
+    fn failed(&self) -> usize {
+        self.checks.iter().filter(|c| !c.ok).count()
+    }
+}
+
+impl Render for ApiTestReport {
+    const SCHEMA: &'static str = "test-api";
+
+    fn render_table(&self) {
+        let failed = self.failed();
+        if failed == 0 {
+            println!("\n{} All {} API checks passed! Your connection is working perfectly! 🚀", "🎉".green().bold(), self.checks.len());
+        } else {
+            println!("\n{} {} of {} API checks failed:", "❌".red().bold(), failed, self.checks.len());
+            for check in self.checks.iter().filter(|c| !c.ok) {
+                println!("  • {} {}: {}", check.service, check.check, check.error.as_deref().unwrap_or("-"));
             }
+        }
+    }
+
+    fn records(&self) -> Result<Vec<Value>> {
+        self.checks.iter().map(|c| Ok(serde_json::to_value(c)?)).collect()
+    }
+}
+
+pub async fn execute(output: OutputFormat, config: &Config) -> Result<()> {
+    let mut report = ApiTestReport::default();
+
+    test_blockfrost(config, &mut report).await;
+    test_koios(config, &mut report).await;
+    test_ogmios(config, &mut report).await;
+
+    if report.checks.is_empty() {
+        return Err(CliError::Config("No API providers configured - add a [blockfrost], [koios] or [ogmios] section".to_string()).into());
+    }
+
+    render(&report, output)?;
+
+    // Exit with the category of the first failure, e.g. a rejected API key
+    if let Some(kind) = &report.first_failure {
+        let message = format!("{} of {} API checks failed", report.failed(), report.checks.len());
+        return Err(kind.with_message(message).into());
+    }
+    Ok(())
+}
 
-            println!("\n{} All API tests passed! Your connection is working perfectly! 🚀", "🎉".green().bold());
-        },
+async fn test_blockfrost(config: &Config, report: &mut ApiTestReport) {
+    use crate::cardano::blockfrost::BlockfrostClient;
+
+    progress!("{}", "🧪 Testing Blockfrost API Connection".blue().bold());
+    progress!("{}", "=".repeat(50).blue());
+
+    let client = match BlockfrostClient::new(config) {
+        Some(client) => client,
         None => {
-            println!("{} No Blockfrost configuration found in config.toml", "❌".red());
-            println!("Make sure you have the [blockfrost] section in your config.toml");
+            progress!("{} No Blockfrost configuration found in config.toml", "❌".red());
+            progress!("Make sure you have the [blockfrost] section in your config.toml");
+            return;
         }
+    };
+
+    // Test 1: Get network info
+    progress!("\n{}", "📡 Testing network info...".cyan());
+    let result = client.get_network_info().await.map(|info| json!({
+        "supply": info["supply"]["circulating"],
+        "max_supply": info["supply"]["max"],
+        "active_stake": info["stake"]["active"],
+    }));
+    if !report.record("blockfrost", "network", result) {
+        return;
     }
 
-    Ok(())
-}
\ No newline at end of file
+    // Test 2: Get latest epoch
+    progress!("\n{}", "📅 Testing latest epoch...".cyan());
+    let result = client.get_latest_epoch().await.map(|epoch| json!({
+        "epoch": epoch["epoch"],
+        "start_time": epoch["start_time"],
+        "end_time": epoch["end_time"],
+    }));
+    if !report.record("blockfrost", "latest_epoch", result) {
+        return;
+    }
+
+    // Test 3: Get first pools
+    progress!("\n{}", "🏊 Testing pool data...".cyan());
+    let result = client.get_all_pools().await.map(|pools| {
+        let pools = pools.as_array().cloned().unwrap_or_default();
+        json!({
+            "pools_found": pools.len(),
+            "first_pools": pools.iter().take(3).collect::<Vec<_>>(),
+        })
+    });
+    report.record("blockfrost", "pools", result);
+}
+
+async fn test_koios(config: &Config, report: &mut ApiTestReport) {
+    use crate::cardano::koios::{KoiosClient, KoiosQuery};
+
+    let client = match KoiosClient::new(config) {
+        Some(client) => client,
+        None => return,
+    };
+
+    progress!("\n{}", "🧪 Testing Koios API Connection".blue().bold());
+    progress!("{}", "=".repeat(50).blue());
+
+    // Test 1: Get chain tip
+    progress!("\n{}", "📡 Testing chain tip...".cyan());
+    let result = client.get_tip().await.map(|tip| json!({
+        "epoch": tip[0]["epoch_no"],
+        "slot": tip[0]["abs_slot"],
+    }));
+    if !report.record("koios", "tip", result) {
+        return;
+    }
+
+    // Test 2: Get registered pools, following pagination
+    progress!("\n{}", "🏊 Testing pool list...".cyan());
+    let query = KoiosQuery::new()
+        .filter("pool_status", "eq", "registered")
+        .select(&["pool_id_bech32", "ticker"]);
+    let result = client.get_pool_list(&query).await.map(|pools| {
+        let pools = pools.as_array().cloned().unwrap_or_default();
+        json!({
+            "registered_pools": pools.len(),
+            "first_pools": pools.iter().take(3).map(|p| p["pool_id_bech32"].clone()).collect::<Vec<_>>(),
+        })
+    });
+    report.record("koios", "pool_list", result);
+}
+
+async fn test_ogmios(config: &Config, report: &mut ApiTestReport) {
+    use crate::cardano::ogmios::{self, OgmiosClient};
+
+    let client = match OgmiosClient::new(config) {
+        Some(client) => client,
+        None => return,
+    };
+
+    progress!("\n{}", "🧪 Testing Ogmios Connection".blue().bold());
+    progress!("{}", "=".repeat(50).blue());
+
+    // Test 1: Ledger and network tip
+    progress!("\n{}", "📡 Testing chain tip...".cyan());
+    let result = match client.query_tip().await {
+        Ok(tip) => {
+            let network_tip = client.query_network_tip().await.ok();
+            Ok(json!({
+                "slot": tip["slot"],
+                "network_slot": network_tip.map_or(Value::Null, |t| t["slot"].clone()),
+            }))
+        }
+        Err(e) => Err(e),
+    };
+    if !report.record("ogmios", "tip", result) {
+        return;
+    }
+
+    // Test 2: Current epoch
+    progress!("\n{}", "📅 Testing epoch...".cyan());
+    let result = client.query_epoch().await.map(|epoch| json!({ "epoch": epoch }));
+    if !report.record("ogmios", "epoch", result) {
+        return;
+    }
+
+    // Test 3: Protocol parameters
+    progress!("\n{}", "⚙️  Testing protocol parameters...".cyan());
+    let result = client.query_protocol_parameters().await.map(|params| json!({
+        "pledge_influence": ogmios::ratio(&params["stakePoolPledgeInfluence"]),
+        "min_pool_cost": ogmios::lovelace(&params["minStakePoolCost"]),
+    }));
+    report.record("ogmios", "protocol_parameters", result);
+}
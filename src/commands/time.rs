@@ -0,0 +1,117 @@
+use anyhow::{Result, Context};
+use chrono::{DateTime, Duration, Utc};
+use colored::*;
+use serde::Serialize;
+use crate::cardano::chain_time::{ChainTime, EpochBoundaries};
+use crate::cardano::cli::CardanoCli;
+use crate::utils::config::Config;
+use crate::utils::output::{render, OutputFormat, Render};
+use crate::progress;
+
+#[derive(Debug, Serialize)]
+pub struct ChainTimeReport {
+    pub slot: u64,
+    pub time: DateTime<Utc>,
+    pub epoch: u64,
+    pub slot_in_epoch: u64,
+    pub epoch_length: u64,
+    /// `node`, `wall_clock` or `argument`.
+    pub slot_source: String,
+    pub boundaries: EpochBoundaries,
+    /// Byron epochs have no stability window or reward calculation point.
+    pub shelley: bool,
+    /// Time left in the epoch, only when the slot is in the current epoch.
+    pub next_epoch_in_seconds: Option<i64>,
+}
+
+impl Render for ChainTimeReport {
+    const SCHEMA: &'static str = "time";
+
+    fn render_table(&self) {
+        let boundaries = &self.boundaries;
+
+        println!("🎰 Slot: {}", self.slot.to_string().cyan());
+        println!("🕐 Time: {}", self.time.to_rfc3339());
+        println!("📅 Epoch: {} (slot {} of {}, {:.1}%)",
+                 self.epoch.to_string().cyan(), self.slot_in_epoch, self.epoch_length,
+                 self.slot_in_epoch as f64 / self.epoch_length as f64 * 100.0);
+
+        println!("\n{}", "📐 Epoch Boundaries".cyan().bold());
+        println!("  • Start: slot {} at {}", boundaries.start_slot, boundaries.start_time.to_rfc3339());
+        println!("  • End: slot {} at {}", boundaries.end_slot, boundaries.end_time.to_rfc3339());
+        if self.shelley {
+            println!("  • Stability window: slot {} at {}",
+                     boundaries.stability_window_slot, boundaries.stability_window_time.to_rfc3339());
+            println!("  • Reward calculation: slot {} at {}",
+                     boundaries.reward_calculation_slot, boundaries.reward_calculation_time.to_rfc3339());
+        }
+
+        if let Some(seconds) = self.next_epoch_in_seconds {
+            println!("\n⏳ Next epoch in {}", format_duration(Duration::seconds(seconds)));
+        }
+    }
+}
+
+pub async fn execute(
+    slot: Option<u64>,
+    epoch: Option<u64>,
+    at: Option<String>,
+    output: OutputFormat,
+    config: &Config,
+) -> Result<()> {
+    progress!("{}", "🕰️  Chain Time".blue().bold());
+    progress!("{}", "=".repeat(50).blue());
+
+    let chain_time = ChainTime::load(&config.cardano)?;
+    let now = Utc::now();
+
+    let (slot, slot_source) = match (slot, epoch, at) {
+        (Some(slot), _, _) => (slot, "argument"),
+        (None, Some(epoch), _) => (chain_time.epoch_start_slot(epoch), "argument"),
+        (None, None, Some(at)) => {
+            let time = DateTime::parse_from_rfc3339(&at)
+                .with_context(|| format!("Invalid time '{}', expected RFC 3339", at))?
+                .with_timezone(&Utc);
+            (chain_time.time_to_slot(time).context("Time is before the network's system start")?, "argument")
+        }
+        (None, None, None) => current_slot(&chain_time, config, now).await,
+    };
+
+    let (epoch, slot_in_epoch) = chain_time.epoch_of_slot(slot);
+    let boundaries = chain_time.epoch_boundaries(epoch);
+    let current = boundaries.start_time <= now && now < boundaries.end_time;
+
+    render(&ChainTimeReport {
+        slot,
+        time: chain_time.slot_to_time(slot),
+        epoch,
+        slot_in_epoch,
+        epoch_length: chain_time.epoch_length_of(epoch),
+        slot_source: slot_source.to_string(),
+        shelley: epoch >= chain_time.byron_epochs,
+        next_epoch_in_seconds: current.then(|| chain_time.time_until_next_epoch(now).num_seconds()),
+        boundaries,
+    }, output)
+}
+
+/// Prefer the node's tip, falling back to the wall clock.
+async fn current_slot(chain_time: &ChainTime, config: &Config, now: DateTime<Utc>) -> (u64, &'static str) {
+    let wall_clock_slot = chain_time.time_to_slot(now).unwrap_or(0);
+
+    match CardanoCli::new(config).query_tip().await.ok().and_then(|tip| tip["slot"].as_u64()) {
+        Some(tip_slot) => {
+            let lag = wall_clock_slot.saturating_sub(tip_slot);
+            progress!("📡 Using node tip ({} slots behind wall clock)", lag);
+            (tip_slot, "node")
+        }
+        None => {
+            progress!("⚠️  Node tip unavailable - using wall clock");
+            (wall_clock_slot, "wall_clock")
+        }
+    }
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    let seconds = duration.num_seconds().max(0);
+    format!("{}d {}h {}m", seconds / 86_400, (seconds % 86_400) / 3_600, (seconds % 3_600) / 60)
+}
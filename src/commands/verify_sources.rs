@@ -0,0 +1,397 @@
+use anyhow::Result;
+use colored::*;
+use serde::Serialize;
+use serde_json::Value;
+use tabled::{Table, Tabled};
+use crate::cardano::provider::{ChainDataProvider, ChainTip, ProviderChain};
+use crate::utils::config::{Config, VerificationConfig};
+use crate::utils::error::CliError;
+use crate::utils::output::{render, OutputFormat, Render};
+use crate::progress;
+use crate::cardano::pool_id::PoolId;
+
+/// What one provider reported.
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceSample {
+    pub source: String,
+    pub tip: Option<ChainTip>,
+    pub live_stake: Option<u64>,
+    pub blocks_epoch: Option<u64>,
+    pub errors: Vec<String>,
+}
+
+/// A disagreement beyond the configured tolerance.
+#[derive(Debug, Clone, Serialize)]
+pub struct Divergence {
+    pub check: String,
+    pub detail: String,
+    /// The source that is most likely wrong, when the others agree.
+    pub suspect: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConsistencyReport {
+    pub pool_id: Option<String>,
+    pub samples: Vec<SourceSample>,
+    pub divergences: Vec<Divergence>,
+}
+
+impl ConsistencyReport {
+    pub fn responding(&self) -> usize {
+        self.samples.iter().filter(|s| s.tip.is_some()).count()
+    }
+
+    pub fn is_consistent(&self) -> bool {
+        self.divergences.is_empty()
+    }
+}
+
+#[derive(Tabled)]
+struct SourceRow {
+    #[tabled(rename = "Source")]
+    source: String,
+    #[tabled(rename = "Epoch")]
+    epoch: String,
+    #[tabled(rename = "Slot")]
+    slot: String,
+    #[tabled(rename = "Block")]
+    block: String,
+    #[tabled(rename = "Live Stake")]
+    live_stake: String,
+    #[tabled(rename = "Blocks (epoch)")]
+    blocks: String,
+}
+
+impl Render for ConsistencyReport {
+    const SCHEMA: &'static str = "verify-sources";
+
+    fn render_table(&self) {
+        if let Some(pool_id) = &self.pool_id {
+            println!("🏊 Pool: {}", pool_id.dimmed());
+        }
+
+        let rows: Vec<SourceRow> = self.samples.iter().map(build_row).collect();
+        println!("{}", Table::new(rows));
+
+        for sample in &self.samples {
+            for error in &sample.errors {
+                println!("  ⚠️  {}: {}", sample.source, error.dimmed());
+            }
+        }
+
+        if self.responding() < 2 {
+            println!("\n{}", "⚠️  Fewer than two sources answered - nothing to compare".yellow());
+            return;
+        }
+
+        if self.is_consistent() {
+            println!("\n{}", format!("✅ All {} responding sources agree", self.responding()).green());
+            return;
+        }
+
+        println!("\n{}", "❌ Divergence detected".red().bold());
+        for divergence in &self.divergences {
+            println!("  • [{}] {}", divergence.check, divergence.detail);
+            match &divergence.suspect {
+                Some(suspect) => println!("    → likely wrong: {}", suspect.red()),
+                None => println!("    → {}", "no majority, cannot tell which source is wrong".yellow()),
+            }
+        }
+    }
+
+    fn records(&self) -> Result<Vec<Value>> {
+        self.samples.iter().map(|s| Ok(serde_json::to_value(s)?)).collect()
+    }
+}
+
+pub async fn execute(pool_id: Option<PoolId>, output: OutputFormat, config: &Config) -> Result<()> {
+    progress!("{}", "🔀 Data Source Consistency Check".blue().bold());
+    progress!("{}", "=".repeat(50).blue());
+
+    let pool_id = pool_id.or_else(|| config.pools.first().map(|p| p.pool_id.clone()));
+    let report = verify_sources(config, pool_id.as_ref().map(PoolId::as_str)).await?;
+
+    render(&report, output)?;
+
+    if report.responding() < 2 {
+        return Err(CliError::CheckFailed("Fewer than two sources answered - nothing to compare".to_string()).into());
+    }
+    if !report.is_consistent() {
+        return Err(CliError::CheckFailed(format!("{} divergence(s) between data sources", report.divergences.len())).into());
+    }
+    Ok(())
+}
+
+/// Query every configured provider and compare what they report.
+pub async fn verify_sources(config: &Config, pool_id: Option<&str>) -> Result<ConsistencyReport> {
+    let chain = ProviderChain::all_configured(config);
+
+    let mut samples = Vec::new();
+    for provider in chain.providers() {
+        samples.push(sample_provider(provider.as_ref(), pool_id).await);
+    }
+
+    let divergences = compare_samples(&samples, &config.verification);
+
+    Ok(ConsistencyReport {
+        pool_id: pool_id.map(String::from),
+        samples,
+        divergences,
+    })
+}
+
+async fn sample_provider(provider: &dyn ChainDataProvider, pool_id: Option<&str>) -> SourceSample {
+    let mut sample = SourceSample {
+        source: provider.name().to_string(),
+        tip: None,
+        live_stake: None,
+        blocks_epoch: None,
+        errors: Vec::new(),
+    };
+
+    match provider.tip().await {
+        Ok(tip) => sample.tip = Some(tip),
+        Err(e) => {
+            // A source without a tip has nothing comparable to offer
+            sample.errors.push(format!("tip: {}", e));
+            return sample;
+        }
+    }
+
+    if let Some(pool_id) = pool_id {
+        match provider.pool_stake(pool_id).await {
+            Ok(stake) => sample.live_stake = Some(stake.pool_stake),
+            Err(e) => sample.errors.push(format!("live stake: {}", e)),
+        }
+
+        let epoch = sample.tip.as_ref().map_or(0, |t| t.epoch);
+        match provider.pool_blocks(pool_id, epoch).await {
+            Ok(blocks) => sample.blocks_epoch = Some(blocks),
+            Err(e) => sample.errors.push(format!("blocks: {}", e)),
+        }
+    }
+
+    sample
+}
+
+fn compare_samples(samples: &[SourceSample], tolerances: &VerificationConfig) -> Vec<Divergence> {
+    let mut divergences = Vec::new();
+
+    let tips: Vec<(&str, &ChainTip)> = samples.iter()
+        .filter_map(|s| s.tip.as_ref().map(|t| (s.source.as_str(), t)))
+        .collect();
+
+    // A source can only fall behind, so the highest slot is the reference
+    if let Some((leader, leading)) = tips.iter().max_by_key(|(_, t)| t.slot) {
+        for (source, tip) in &tips {
+            let lag = leading.slot - tip.slot;
+            if lag > tolerances.max_slot_lag {
+                divergences.push(Divergence {
+                    check: "tip".to_string(),
+                    detail: format!("{} is {} slots behind {} (slot {} vs {})", source, lag, leader, tip.slot, leading.slot),
+                    suspect: Some(source.to_string()),
+                });
+            }
+        }
+    }
+
+    // A source one block behind may still be in the previous epoch
+    if !straddles_epoch_boundary(&tips) {
+        let epochs: Vec<(&str, f64)> = tips.iter().map(|(s, t)| (*s, t.epoch as f64)).collect();
+        divergences.extend(find_outliers("epoch", &epochs, |a, b| a == b));
+    }
+
+    // Sources at the same slot must report the same block
+    for (i, (source, tip)) in tips.iter().enumerate() {
+        for (other, other_tip) in &tips[i + 1..] {
+            if let (Some(hash), Some(other_hash)) = (&tip.hash, &other_tip.hash) {
+                if tip.slot == other_tip.slot && hash != other_hash {
+                    divergences.push(Divergence {
+                        check: "fork".to_string(),
+                        detail: format!("{} and {} report different blocks at slot {}", source, other, tip.slot),
+                        suspect: None,
+                    });
+                }
+            }
+        }
+    }
+
+    let stakes: Vec<(&str, f64)> = samples.iter()
+        .filter_map(|s| s.live_stake.map(|v| (s.source.as_str(), v as f64)))
+        .collect();
+    let max_delta = tolerances.max_stake_delta_percent / 100.0;
+    divergences.extend(find_outliers("live_stake", &stakes, |a, b| {
+        let base = a.max(b);
+        base == 0.0 || (a - b).abs() / base <= max_delta
+    }));
+
+    let blocks: Vec<(&str, f64)> = samples.iter()
+        .filter_map(|s| s.blocks_epoch.map(|v| (s.source.as_str(), v as f64)))
+        .collect();
+    let max_blocks = tolerances.max_block_delta as f64;
+    divergences.extend(find_outliers("blocks", &blocks, |a, b| (a - b).abs() <= max_blocks));
+
+    divergences
+}
+
+/// Whether the tips span two adjacent epochs only because they are a block
+/// apart, or because the boundary falls between their slots.
+fn straddles_epoch_boundary(tips: &[(&str, &ChainTip)]) -> bool {
+    let (Some(first), Some(last)) = (tips.iter().map(|(_, t)| t.epoch).min(), tips.iter().map(|(_, t)| t.epoch).max()) else {
+        return false;
+    };
+    if last != first + 1 {
+        return false;
+    }
+
+    // The later tips are the very next block after the earlier ones
+    let blocks = |epoch: u64| -> Option<Vec<u64>> {
+        tips.iter().filter(|(_, t)| t.epoch == epoch).map(|(_, t)| t.block).collect()
+    };
+    if let (Some(earlier), Some(later)) = (blocks(first), blocks(last)) {
+        let (earliest, latest) = (earlier.iter().min(), earlier.iter().max());
+        let next = |block: &u64| latest.is_some_and(|l| block > l) && earliest.is_some_and(|e| block - e <= 1);
+        if later.iter().all(next) {
+            return true;
+        }
+    }
+
+    let lowest_slot = tips.iter().map(|(_, t)| t.slot).min().unwrap_or(0);
+    tips.iter()
+        .filter(|(_, t)| t.epoch == last)
+        .all(|(_, t)| t.slot_in_epoch.is_some_and(|s| s <= t.slot - lowest_slot))
+}
+
+/// Flag values that agree with fewer sources than the best-supported value.
+fn find_outliers(check: &str, values: &[(&str, f64)], agree: impl Fn(f64, f64) -> bool) -> Vec<Divergence> {
+    let support: Vec<usize> = values.iter()
+        .map(|(_, a)| values.iter().filter(|(_, b)| agree(*a, *b)).count())
+        .collect();
+
+    let best = match support.iter().max() {
+        Some(&best) if best < values.len() => best,
+        _ => return Vec::new(),
+    };
+
+    // Without a strict majority there is no way to tell who is right
+    let has_majority = best * 2 > values.len();
+    let listing = values.iter()
+        .map(|(source, value)| format!("{}={}", source, value))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    if !has_majority {
+        return vec![Divergence {
+            check: check.to_string(),
+            detail: format!("sources disagree: {}", listing),
+            suspect: None,
+        }];
+    }
+
+    values.iter().zip(&support)
+        .filter(|(_, &count)| count < best)
+        .map(|((source, _), _)| Divergence {
+            check: check.to_string(),
+            detail: format!("{} disagrees with the other sources: {}", source, listing),
+            suspect: Some(source.to_string()),
+        })
+        .collect()
+}
+
+fn build_row(sample: &SourceSample) -> SourceRow {
+    let tip_field = |f: fn(&ChainTip) -> Option<u64>| {
+        sample.tip.as_ref().and_then(f).map_or("-".to_string(), |v| v.to_string())
+    };
+
+    SourceRow {
+        source: sample.source.clone(),
+        epoch: tip_field(|t| Some(t.epoch)),
+        slot: tip_field(|t| Some(t.slot)),
+        block: tip_field(|t| t.block),
+        live_stake: sample.live_stake.map_or("-".to_string(), |v| format!("{:.0} ADA", v as f64 / 1_000_000.0)),
+        blocks: sample.blocks_epoch.map_or("-".to_string(), |v| v.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(source: &str, epoch: u64, slot: u64, block: u64) -> SourceSample {
+        SourceSample {
+            source: source.to_string(),
+            tip: Some(ChainTip {
+                epoch,
+                slot,
+                slot_in_epoch: Some(slot % 432_000),
+                block: Some(block),
+                hash: Some(format!("hash-{}", slot)),
+            }),
+            live_stake: None,
+            blocks_epoch: None,
+            errors: Vec::new(),
+        }
+    }
+
+    fn checks(divergences: &[Divergence]) -> Vec<(&str, Option<&str>)> {
+        divergences.iter().map(|d| (d.check.as_str(), d.suspect.as_deref())).collect()
+    }
+
+    #[test]
+    fn matching_sources_agree() {
+        let samples = [sample("cardano-cli", 500, 864_100, 10_000), sample("blockfrost", 500, 864_080, 9_999)];
+        assert!(compare_samples(&samples, &VerificationConfig::default()).is_empty());
+    }
+
+    #[test]
+    fn lagging_source_is_the_suspect() {
+        let samples = [
+            sample("cardano-cli", 500, 865_000, 10_050),
+            sample("blockfrost", 500, 864_990, 10_049),
+            sample("koios", 500, 864_000, 10_000),
+        ];
+        assert_eq!(checks(&compare_samples(&samples, &VerificationConfig::default())), [("tip", Some("koios"))]);
+    }
+
+    #[test]
+    fn tips_a_block_apart_across_an_epoch_boundary_agree() {
+        // Epoch 501 starts at slot 864_000
+        let mut samples = [sample("cardano-cli", 501, 864_005, 10_001), sample("blockfrost", 500, 863_990, 10_000)];
+        assert!(compare_samples(&samples, &VerificationConfig::default()).is_empty());
+
+        // Block heights alone are enough when a provider has no slot in epoch
+        samples[0].tip.as_mut().unwrap().slot_in_epoch = None;
+        assert!(compare_samples(&samples, &VerificationConfig::default()).is_empty());
+    }
+
+    #[test]
+    fn mismatched_epochs_away_from_a_boundary_are_flagged() {
+        let samples = [
+            sample("cardano-cli", 500, 864_100, 10_000),
+            sample("blockfrost", 500, 864_100, 10_000),
+            sample("koios", 501, 864_100, 10_000),
+        ];
+        assert_eq!(checks(&compare_samples(&samples, &VerificationConfig::default())), [("epoch", Some("koios"))]);
+    }
+
+    #[test]
+    fn block_counts_within_tolerance_agree() {
+        let mut samples = [
+            sample("cardano-cli", 500, 864_100, 10_000),
+            sample("blockfrost", 500, 864_100, 10_000),
+            sample("koios", 500, 864_100, 10_000),
+        ];
+        for (sample, blocks) in samples.iter_mut().zip([12, 14, 20]) {
+            sample.blocks_epoch = Some(blocks);
+        }
+        assert_eq!(checks(&compare_samples(&samples, &VerificationConfig::default())), [("blocks", Some("koios"))]);
+    }
+
+    #[test]
+    fn stake_outlier_without_majority_has_no_suspect() {
+        let mut samples = [sample("cardano-cli", 500, 864_100, 10_000), sample("blockfrost", 500, 864_100, 10_000)];
+        samples[0].live_stake = Some(1_000_000_000);
+        samples[1].live_stake = Some(1_100_000_000);
+        assert_eq!(checks(&compare_samples(&samples, &VerificationConfig::default())), [("live_stake", None)]);
+    }
+}
@@ -1,7 +1,4 @@
 pub mod cardano;
 pub mod commands;
+pub mod storage;
 pub mod utils;
-
-pub use cardano::*;
-pub use commands::*;
-pub use utils::*;
\ No newline at end of file
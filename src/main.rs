@@ -1,25 +1,81 @@
 use anyhow::Result;
 use clap::{Parser, Subcommand};
+use colored::*;
+use std::path::{Path, PathBuf};
 
-mod cardano;
-mod commands;
-mod utils;
-
-use utils::config::Config;
+use cardano_validator_cli::cardano::address::Address;
+use cardano_validator_cli::cardano::pool_id::PoolId;
+use cardano_validator_cli::utils::config::Config;
+use cardano_validator_cli::utils::error::{classify, CliError};
+use cardano_validator_cli::utils::output::OutputFormat;
+use cardano_validator_cli::{commands, progress, utils};
 
 #[derive(Parser)]
 #[command(name = "cardano-validator-cli")]
 #[command(about = "A comprehensive CLI toolkit for Cardano stake pool operators")]
 #[command(version = "0.1.0")]
 struct Cli {
+    /// Output format for command results
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Table)]
+    output: OutputFormat,
+
+    /// Use this config file instead of discovering and merging system, user and project files
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
+    /// Apply the named [profiles.<name>] section (also CVC_PROFILE or default_profile)
+    #[arg(long, global = true, conflicts_with = "all_profiles")]
+    profile: Option<String>,
+
+    /// Run the command once for every configured profile
+    #[arg(long, global = true)]
+    all_profiles: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
 
-#[derive(Subcommand)]
+#[derive(Subcommand, Clone)]
 enum Commands {
-    /// Interactive setup wizard for first-time configuration
-    Setup,
+    /// Setup wizard for first-time configuration
+    ///
+    /// Values given as flags or in an answers file are not prompted for. With
+    /// --yes or --answers nothing is prompted and missing values are errors.
+    Setup {
+        /// Network: mainnet, preprod, preview or testnet
+        #[arg(long)]
+        network: Option<String>,
+
+        /// Blockfrost project ID (defaults to $BLOCKFROST_PROJECT_ID)
+        #[arg(long)]
+        blockfrost_key: Option<String>,
+
+        /// Pool to monitor as id:ticker:name (repeatable)
+        #[arg(long = "pool", value_name = "ID:TICKER:NAME")]
+        pools: Vec<String>,
+
+        /// Node socket path
+        #[arg(long)]
+        socket: Option<String>,
+
+        /// Path to the cardano-cli binary
+        #[arg(long)]
+        cli_path: Option<String>,
+
+        /// TOML file with setup answers; flags take precedence
+        #[arg(long, value_name = "FILE")]
+        answers: Option<PathBuf>,
+
+        /// Run unattended, answering yes to every confirmation
+        #[arg(long, short = 'y')]
+        yes: bool,
+    },
+
+    /// Create and inspect configuration files
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommands,
+    },
 
     /// Check health status of your stake pool
     HealthCheck {
@@ -40,11 +96,11 @@ enum Commands {
     Monitor {
         /// Pool ID to monitor
         #[arg(short, long)]
-        pool_id: Option<String>,
+        pool_id: Option<PoolId>,
 
-        /// Output format: table, json, csv
-        #[arg(long, default_value = "table")]
-        format: String,
+        /// Old spelling of the global --output flag
+        #[arg(long, value_enum, hide = true)]
+        format: Option<OutputFormat>,
 
         /// Run in continuous mode
         #[arg(long)]
@@ -57,13 +113,17 @@ enum Commands {
         /// Prometheus port
         #[arg(long, default_value = "9090")]
         port: u16,
+
+        /// Use only this chain data provider: cardano-cli, ogmios, blockfrost or koios
+        #[arg(long)]
+        provider: Option<String>,
     },
 
     /// Check detailed pool status and information
     PoolStatus {
         /// Pool ID to check
         #[arg(short, long)]
-        pool_id: Option<String>,
+        pool_id: Option<PoolId>,
 
         /// Show detailed information
         #[arg(short, long)]
@@ -72,6 +132,10 @@ enum Commands {
         /// Compare with other pools
         #[arg(long)]
         compare: bool,
+
+        /// Use only this chain data provider: cardano-cli, ogmios, blockfrost or koios
+        #[arg(long)]
+        provider: Option<String>,
     },
 
     /// Calculate and analyze rewards
@@ -83,44 +147,456 @@ enum Commands {
         /// Show detailed breakdown
         #[arg(short, long)]
         detailed: bool,
+
+        #[command(subcommand)]
+        action: Option<RewardsCommands>,
+    },
+
+    /// Analyze pool performance trends from stored history
+    Analytics {
+        /// Pool ID to analyze
+        #[arg(short, long)]
+        pool_id: Option<PoolId>,
+
+        /// Number of epochs to analyze
+        #[arg(long, default_value = "10")]
+        epochs: u64,
+
+        /// Show detailed report
+        #[arg(short, long)]
+        detailed: bool,
+
+        /// Export report to JSON
+        #[arg(long)]
+        export: bool,
     },
 
     /// Test API connection and configuration
     TestApi,
+
+    /// KES key expiry checks and rotation
+    Kes {
+        #[command(subcommand)]
+        action: KesCommands,
+    },
+
+    /// Cardano node status and chain following
+    Node {
+        #[command(subcommand)]
+        action: NodeCommands,
+    },
+
+    /// Check relay reachability, handshake and latency
+    Relays {
+        /// Pool ID to check
+        #[arg(short, long)]
+        pool_id: Option<PoolId>,
+    },
+
+    /// Check that the owners' live stake covers the declared pledge
+    Pledge {
+        /// Pool ID to check
+        #[arg(short, long)]
+        pool_id: Option<PoolId>,
+
+        /// Use only this chain data provider: cardano-cli, ogmios, blockfrost or koios
+        #[arg(long)]
+        provider: Option<String>,
+    },
+
+    /// Pool metadata checks
+    Metadata {
+        #[command(subcommand)]
+        action: MetadataCommands,
+    },
+
+    /// Decode addresses and show their stake accounts
+    Address {
+        #[command(subcommand)]
+        action: AddressCommands,
+    },
+
+    /// Compare tip, stake and block counts across every configured data source
+    VerifySources {
+        /// Pool ID to compare (defaults to the first configured pool)
+        #[arg(short, long)]
+        pool_id: Option<PoolId>,
+    },
+
+    /// Convert between slots, epochs and UTC time
+    Time {
+        /// Absolute slot number
+        #[arg(short, long)]
+        slot: Option<u64>,
+
+        /// Show the boundaries of this epoch
+        #[arg(short, long, conflicts_with = "slot")]
+        epoch: Option<u64>,
+
+        /// UTC time in RFC 3339 format
+        #[arg(long, conflicts_with_all = ["slot", "epoch"])]
+        at: Option<String>,
+    },
+
+    /// Run the background collector daemon
+    Daemon {
+        /// Show the status of a running daemon instead of starting one
+        #[arg(long)]
+        status: bool,
+    },
+}
+
+#[derive(Subcommand, Clone)]
+enum NodeCommands {
+    /// Show node status and metrics scraped from the node
+    Status,
+    /// Follow new blocks through Ogmios chain-sync
+    Follow {
+        /// Stop after this many blocks
+        #[arg(short, long)]
+        blocks: Option<u64>,
+    },
+}
+
+#[derive(Subcommand, Clone)]
+enum RewardsCommands {
+    /// Build an unsigned transaction withdrawing the reward balance, for offline signing
+    Withdraw {
+        /// Pool whose reward address to withdraw from
+        #[arg(short, long)]
+        pool_id: Option<PoolId>,
+
+        /// Address paying the fee and receiving the rewards (defaults to the pledge address)
+        #[arg(long)]
+        payment_address: Option<String>,
+
+        /// Where to write the unsigned transaction
+        #[arg(long)]
+        out_file: Option<String>,
+
+        /// Show what would be done without building the transaction
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Submit a transaction signed offline
+    Submit {
+        /// Signed transaction file
+        #[arg(long)]
+        tx_file: String,
+
+        /// Show the transaction ID without submitting
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand, Clone)]
+enum AddressCommands {
+    /// Decode addresses (defaults to the configured pledge and reward addresses)
+    /// and show balance, rewards and delegation of their stake keys
+    Inspect {
+        /// Shelley (addr1/stake1) or Byron addresses
+        addresses: Vec<Address>,
+
+        /// Only decode; do not query chain data providers
+        #[arg(long)]
+        offline: bool,
+
+        /// Use only this chain data provider: cardano-cli, ogmios, blockfrost or koios
+        #[arg(long)]
+        provider: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Clone)]
+enum ConfigCommands {
+    /// Write a starter config file (defaults to the user config directory)
+    Init {
+        /// Write to this path instead
+        #[arg(long)]
+        path: Option<PathBuf>,
+
+        /// Overwrite an existing file
+        #[arg(long)]
+        force: bool,
+    },
+    /// List the config files and environment overrides in effect
+    Paths,
+    /// Check the effective configuration and report problems with their location
+    Validate {
+        /// Treat warnings as errors
+        #[arg(long)]
+        strict: bool,
+    },
+}
+
+#[derive(Subcommand, Clone)]
+enum KesCommands {
+    /// Check KES key expiry and operational certificate counters
+    Status {
+        /// Pool ID to check
+        #[arg(short, long)]
+        pool_id: Option<PoolId>,
+    },
+
+    /// Generate new KES keys and an opcert issuance request for the cold machine
+    Rotate {
+        /// Pool ID to rotate
+        #[arg(short, long)]
+        pool_id: Option<PoolId>,
+
+        /// Directory where new keys and the issuance request are staged
+        #[arg(long, default_value = "./kes-rotation")]
+        staging_dir: String,
+
+        /// Show what would be done without writing any files
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Verify and install a signed operational certificate
+    Install {
+        /// Pool ID to install for
+        #[arg(short, long)]
+        pool_id: Option<PoolId>,
+
+        /// Signed operational certificate from the cold machine
+        #[arg(long)]
+        cert: String,
+
+        /// Directory where the rotation was staged
+        #[arg(long, default_value = "./kes-rotation")]
+        staging_dir: String,
+
+        /// Show what would be done without changing any files
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Restore the KES key and certificate replaced by the last install
+    Rollback {
+        /// Pool ID to roll back
+        #[arg(short, long)]
+        pool_id: Option<PoolId>,
+
+        /// Directory where the rotation was staged
+        #[arg(long, default_value = "./kes-rotation")]
+        staging_dir: String,
+
+        /// Show what would be done without changing any files
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand, Clone)]
+enum MetadataCommands {
+    /// Verify the registered metadata hash, CIP-6 schema and field limits
+    Verify {
+        /// Pool ID to verify
+        #[arg(short, long)]
+        pool_id: Option<PoolId>,
+
+        /// Read metadata from this local file or URL instead of the registered URL
+        #[arg(long)]
+        file: Option<String>,
+    },
 }
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() {
     env_logger::init();
 
-    let cli = Cli::parse();
+    let mut cli = Cli::parse();
+    if let Commands::Monitor { format: Some(format), .. } = cli.command {
+        cli.output = format;
+    }
+    utils::output::set_format(cli.output);
+
+    if let Err(e) = run(cli).await {
+        eprintln!("Error: {:?}", e);
+        std::process::exit(utils::error::exit_code(&e));
+    }
+}
 
-    match cli.command {
-        Commands::Setup => {
-            commands::setup::execute().await
+async fn run(cli: Cli) -> Result<()> {
+    let Cli { output, config: config_path, profile, all_profiles, command } = cli;
+    let config_path = config_path.as_deref();
+
+    if all_profiles && changes_state(&command) {
+        return Err(CliError::Config("--all-profiles cannot be used with commands that write files or submit transactions - run them per --profile".to_string()).into());
+    }
+
+    match command {
+        Commands::Setup { network, blockfrost_key, pools, socket, cli_path, answers, yes } => {
+            let mut flags = commands::setup::SetupAnswers {
+                network,
+                blockfrost_key,
+                socket,
+                cli_path,
+                pools: pools.iter().map(|p| commands::setup::PoolAnswer::parse(p)).collect::<Result<_>>()?,
+                ..Default::default()
+            };
+            let unattended = yes || answers.is_some();
+            if let Some(file) = &answers {
+                flags = flags.or(commands::setup::SetupAnswers::from_file(file)?);
+            }
+            commands::setup::execute(config_path, flags, unattended).await
+        }
+        Commands::Config { action } => match action {
+            ConfigCommands::Init { path, force } => commands::config::init(path.or(config_path.map(PathBuf::from)), force),
+            ConfigCommands::Paths => commands::config::show_paths(config_path),
+            ConfigCommands::Validate { strict } => {
+                let profiles = if all_profiles {
+                    Config::profile_names(config_path)?.into_iter().map(Some).collect()
+                } else {
+                    vec![profile]
+                };
+                commands::config::validate(config_path, &profiles, strict, output)
+            }
+        },
+        command if all_profiles => run_all_profiles(command, config_path, output).await,
+        command => {
+            let config = Config::load(config_path, profile.as_deref())?;
+            utils::output::set_profile(config.profile.as_deref(), &config.cardano.network);
+            execute(command, output, &config).await
+        }
+    }
+}
+
+/// Commands that write files, keys or transactions must never fan out.
+fn changes_state(command: &Commands) -> bool {
+    matches!(
+        command,
+        Commands::Setup { .. }
+            | Commands::Config { action: ConfigCommands::Init { .. } }
+            | Commands::Kes { action: KesCommands::Rotate { .. } | KesCommands::Install { .. } | KesCommands::Rollback { .. } }
+            | Commands::Rewards { action: Some(_), .. }
+    )
+}
+
+/// Run one command against every profile, reporting failures per profile.
+async fn run_all_profiles(command: Commands, config_path: Option<&Path>, output: OutputFormat) -> Result<()> {
+    let long_running = matches!(
+        command,
+        Commands::Daemon { status: false }
+            | Commands::Monitor { continuous: true, .. }
+            | Commands::HealthCheck { continuous: true, .. }
+            | Commands::Node { action: NodeCommands::Follow { .. } }
+    );
+    if long_running {
+        return Err(CliError::Config("--all-profiles cannot be used with long-running commands - start one per --profile".to_string()).into());
+    }
+
+    let names = Config::profile_names(config_path)?;
+    if names.is_empty() {
+        return Err(CliError::Config("No [profiles.<name>] sections configured".to_string()).into());
+    }
+
+    let mut failures = Vec::new();
+    for name in &names {
+        progress!("\n{}", format!("═══ Profile: {} ═══", name).magenta().bold());
+
+        let result = match Config::load(config_path, Some(name)) {
+            Ok(config) => {
+                utils::output::set_profile(Some(name), &config.cardano.network);
+                execute(command.clone(), output, &config).await
+            }
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            eprintln!("Error [{}]: {:#}", name, e);
+            failures.push(e);
         }
-        _ => {
-            // For all other commands, load config
-            let config = Config::load_or_create_default()?;
-
-            match cli.command {
-                Commands::Setup => unreachable!(),
-                Commands::HealthCheck { continuous, interval: _, export: _ } => {
-                    commands::health_check::execute(continuous, &config).await
-                }
-                Commands::Monitor { pool_id: _, format: _, continuous: _, prometheus, port } => {
-                    commands::monitoring::execute(prometheus, port, &config).await
-                }
-                Commands::PoolStatus { pool_id, detailed: _, compare: _ } => {
-                    commands::pool_status::execute(pool_id, &config).await
-                }
-                Commands::Rewards { epoch, detailed } => {
-                    commands::rewards::execute(epoch, detailed, &config).await
-                }
-                Commands::TestApi => {
-                    commands::test_api::execute(&config).await
-                }
+    }
+
+    // Exit with the category of the first failure
+    match failures.first() {
+        None => Ok(()),
+        Some(first) => {
+            let message = format!("{} of {} profiles failed", failures.len(), names.len());
+            match classify(first) {
+                Some(kind) => Err(kind.with_message(message).into()),
+                None => Err(anyhow::anyhow!(message)),
             }
         }
     }
-}
\ No newline at end of file
+}
+
+async fn execute(command: Commands, output: OutputFormat, config: &Config) -> Result<()> {
+    match command {
+        Commands::Setup { .. } | Commands::Config { .. } => unreachable!(),
+        Commands::HealthCheck { continuous, interval, export } => {
+            commands::health_check::execute(continuous, interval, export, output, config).await
+        }
+        Commands::Monitor { pool_id, format: _, continuous, prometheus, port, provider } => {
+            commands::monitoring::execute(pool_id, continuous, prometheus, port, provider, output, config).await
+        }
+        Commands::PoolStatus { pool_id, detailed: _, compare: _, provider } => {
+            commands::pool_status::execute(pool_id, provider, output, config).await
+        }
+        Commands::Rewards { epoch, detailed, action } => match action {
+            None => commands::rewards::execute(epoch, detailed, output, config).await,
+            Some(RewardsCommands::Withdraw { pool_id, payment_address, out_file, dry_run }) => {
+                commands::rewards::withdraw(pool_id, payment_address, out_file, dry_run, output, config).await
+            }
+            Some(RewardsCommands::Submit { tx_file, dry_run }) => {
+                commands::rewards::submit(&tx_file, dry_run, output, config).await
+            }
+        },
+        Commands::Analytics { pool_id, epochs, detailed, export } => {
+            commands::analytics::execute(pool_id, epochs, detailed, export, output, config).await
+        }
+        Commands::TestApi => {
+            commands::test_api::execute(output, config).await
+        }
+        Commands::Kes { action } => match action {
+            KesCommands::Status { pool_id } => {
+                commands::kes::execute(pool_id, output, config).await
+            }
+            KesCommands::Rotate { pool_id, staging_dir, dry_run } => {
+                commands::kes::rotate(pool_id, &staging_dir, dry_run, output, config).await
+            }
+            KesCommands::Install { pool_id, cert, staging_dir, dry_run } => {
+                commands::kes::install(pool_id, &cert, &staging_dir, dry_run, output, config).await
+            }
+            KesCommands::Rollback { pool_id, staging_dir, dry_run } => {
+                commands::kes::rollback(pool_id, &staging_dir, dry_run, output, config).await
+            }
+        },
+        Commands::Node { action } => match action {
+            NodeCommands::Status => commands::node::status(output, config).await,
+            NodeCommands::Follow { blocks } => commands::node::follow(blocks, output, config).await,
+        },
+        Commands::Relays { pool_id } => {
+            commands::relays::execute(pool_id, output, config).await
+        }
+        Commands::Pledge { pool_id, provider } => {
+            commands::pledge::execute(pool_id, provider, output, config).await
+        }
+        Commands::Metadata { action } => match action {
+            MetadataCommands::Verify { pool_id, file } => {
+                commands::metadata::verify(pool_id, file, output, config).await
+            }
+        },
+        Commands::Address { action } => match action {
+            AddressCommands::Inspect { addresses, offline, provider } => {
+                commands::address::inspect(addresses, offline, provider, output, config).await
+            }
+        },
+        Commands::VerifySources { pool_id } => {
+            commands::verify_sources::execute(pool_id, output, config).await
+        }
+        Commands::Time { slot, epoch, at } => {
+            commands::time::execute(slot, epoch, at, output, config).await
+        }
+        Commands::Daemon { status } => {
+            if status {
+                commands::daemon::show_status(output, config).await
+            } else {
+                commands::daemon::execute(config).await
+            }
+        }
+    }
+}
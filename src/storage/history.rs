@@ -0,0 +1,402 @@
+use anyhow::{Result, Context};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use crate::cardano::pool_id::PoolId;
+use crate::storage::migrations::{Migration, MIGRATIONS};
+use crate::utils::config::Config;
+
+/// File-backed store of per-epoch pool data and health samples.
+pub struct HistoryStore {
+    conn: Connection,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PoolSnapshot {
+    pub pool_id: String,
+    pub epoch: u64,
+    pub live_stake: Option<u64>,
+    pub active_stake: Option<u64>,
+    pub total_stake: Option<u64>,
+    pub saturation: Option<f64>,
+    pub delegator_count: Option<u64>,
+    pub source: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RewardsRecord {
+    pub pool_id: String,
+    pub epoch: u64,
+    pub total_rewards: f64,
+    pub pool_rewards: f64,
+    pub delegator_rewards: f64,
+    pub fees: f64,
+    pub delegator_count: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthSample {
+    pub recorded_at: String,
+    pub pool_id: Option<String>,
+    pub epoch: u64,
+    pub slot: u64,
+    pub node_connected: bool,
+    pub node_synced: bool,
+    pub sync_progress: f64,
+    pub blockfrost_connected: bool,
+    pub pool_active: bool,
+    pub kes_remaining_days: Option<f64>,
+    pub warning_count: u64,
+}
+
+impl HistoryStore {
+    /// Open the configured store, or `None` when history is disabled.
+    pub fn from_config(config: &Config) -> Result<Option<Self>> {
+        if !config.storage.enabled {
+            return Ok(None);
+        }
+        Self::open(&config.storage.database_path).map(Some)
+    }
+
+    /// Like `from_config`, but reports failures and carries on without history.
+    pub fn open_or_warn(config: &Config) -> Option<Self> {
+        match Self::from_config(config) {
+            Ok(store) => store,
+            Err(e) => {
+                eprintln!("Warning: history store unavailable: {:#}", e);
+                None
+            }
+        }
+    }
+
+    pub fn open(path: &str) -> Result<Self> {
+        if let Some(parent) = Path::new(path).parent() {
+            if !parent.as_os_str().is_empty() {
+                std::fs::create_dir_all(parent)?;
+            }
+        }
+
+        let conn = Connection::open(path)
+            .with_context(|| format!("Failed to open history database {}", path))?;
+        let store = Self { conn };
+        store.migrate()?;
+        Ok(store)
+    }
+
+    fn migrate(&self) -> Result<()> {
+        self.conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS schema_migrations (
+                version INTEGER PRIMARY KEY,
+                applied_at TEXT NOT NULL
+            );",
+        )?;
+
+        let current: u64 = self.conn.query_row(
+            "SELECT COALESCE(MAX(version), 0) FROM schema_migrations",
+            [],
+            |row| row.get(0),
+        )?;
+
+        for (index, migration) in MIGRATIONS.iter().enumerate() {
+            let version = index as u64 + 1;
+            if version <= current {
+                continue;
+            }
+
+            let tx = self.conn.unchecked_transaction()?;
+            match migration {
+                Migration::Sql(sql) => tx.execute_batch(sql).map_err(Into::into),
+                Migration::Code(step) => step(&tx),
+            }
+            .with_context(|| format!("History migration {} failed", version))?;
+            tx.execute(
+                "INSERT INTO schema_migrations (version, applied_at) VALUES (?1, ?2)",
+                params![version, now()],
+            )?;
+            tx.commit()?;
+        }
+
+        Ok(())
+    }
+
+    /// Insert or merge a snapshot; fields left empty keep their stored value.
+    pub fn record_pool_snapshot(&self, snapshot: &PoolSnapshot) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO pool_snapshots
+                (pool_id, epoch, live_stake, active_stake, total_stake, saturation, delegator_count, source, recorded_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+             ON CONFLICT (pool_id, epoch) DO UPDATE SET
+                live_stake = COALESCE(excluded.live_stake, live_stake),
+                active_stake = COALESCE(excluded.active_stake, active_stake),
+                total_stake = COALESCE(excluded.total_stake, total_stake),
+                saturation = COALESCE(excluded.saturation, saturation),
+                delegator_count = COALESCE(excluded.delegator_count, delegator_count),
+                source = excluded.source,
+                recorded_at = excluded.recorded_at",
+            params![
+                pool_key(&snapshot.pool_id),
+                snapshot.epoch,
+                snapshot.live_stake,
+                snapshot.active_stake,
+                snapshot.total_stake,
+                snapshot.saturation,
+                snapshot.delegator_count,
+                snapshot.source,
+                now(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn record_blocks(&self, pool_id: &str, epoch: u64, block_count: u64) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO pool_blocks (pool_id, epoch, block_count, recorded_at)
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT (pool_id, epoch) DO UPDATE SET
+                block_count = excluded.block_count,
+                recorded_at = excluded.recorded_at",
+            params![pool_key(pool_id), epoch, block_count, now()],
+        )?;
+        Ok(())
+    }
+
+    pub fn record_rewards(&self, record: &RewardsRecord) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO pool_rewards
+                (pool_id, epoch, total_rewards, pool_rewards, delegator_rewards, fees, delegator_count, recorded_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                pool_key(&record.pool_id),
+                record.epoch,
+                record.total_rewards,
+                record.pool_rewards,
+                record.delegator_rewards,
+                record.fees,
+                record.delegator_count,
+                now(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn record_health_sample(&self, sample: &HealthSample) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO health_samples
+                (recorded_at, pool_id, epoch, slot, node_connected, node_synced, sync_progress,
+                 blockfrost_connected, pool_active, kes_remaining_days, warning_count)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                sample.recorded_at,
+                sample.pool_id.as_deref().map(pool_key),
+                sample.epoch,
+                sample.slot,
+                sample.node_connected,
+                sample.node_synced,
+                sample.sync_progress,
+                sample.blockfrost_connected,
+                sample.pool_active,
+                sample.kes_remaining_days,
+                sample.warning_count,
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn pool_snapshots(&self, pool_id: &str, from_epoch: u64, to_epoch: u64) -> Result<Vec<PoolSnapshot>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT pool_id, epoch, live_stake, active_stake, total_stake, saturation, delegator_count, source
+             FROM pool_snapshots
+             WHERE pool_id = ?1 AND epoch BETWEEN ?2 AND ?3
+             ORDER BY epoch",
+        )?;
+
+        let rows = stmt.query_map(params![pool_key(pool_id), from_epoch, to_epoch], |row| {
+            Ok(PoolSnapshot {
+                pool_id: row.get(0)?,
+                epoch: row.get(1)?,
+                live_stake: row.get(2)?,
+                active_stake: row.get(3)?,
+                total_stake: row.get(4)?,
+                saturation: row.get(5)?,
+                delegator_count: row.get(6)?,
+                source: row.get(7)?,
+            })
+        })?;
+
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    /// (epoch, block count) pairs for the pool within the range.
+    pub fn pool_blocks(&self, pool_id: &str, from_epoch: u64, to_epoch: u64) -> Result<Vec<(u64, u64)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT epoch, block_count FROM pool_blocks
+             WHERE pool_id = ?1 AND epoch BETWEEN ?2 AND ?3
+             ORDER BY epoch",
+        )?;
+
+        let rows = stmt.query_map(params![pool_key(pool_id), from_epoch, to_epoch], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })?;
+
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    pub fn pool_rewards(&self, pool_id: &str, from_epoch: u64, to_epoch: u64) -> Result<Vec<RewardsRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT pool_id, epoch, total_rewards, pool_rewards, delegator_rewards, fees, delegator_count
+             FROM pool_rewards
+             WHERE pool_id = ?1 AND epoch BETWEEN ?2 AND ?3
+             ORDER BY epoch",
+        )?;
+
+        let rows = stmt.query_map(params![pool_key(pool_id), from_epoch, to_epoch], |row| {
+            Ok(RewardsRecord {
+                pool_id: row.get(0)?,
+                epoch: row.get(1)?,
+                total_rewards: row.get(2)?,
+                pool_rewards: row.get(3)?,
+                delegator_rewards: row.get(4)?,
+                fees: row.get(5)?,
+                delegator_count: row.get(6)?,
+            })
+        })?;
+
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    pub fn health_samples(&self, pool_id: &str, from_epoch: u64, to_epoch: u64) -> Result<Vec<HealthSample>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT recorded_at, pool_id, epoch, slot, node_connected, node_synced, sync_progress,
+                    blockfrost_connected, pool_active, kes_remaining_days, warning_count
+             FROM health_samples
+             WHERE (pool_id = ?1 OR pool_id IS NULL) AND epoch BETWEEN ?2 AND ?3
+             ORDER BY id",
+        )?;
+
+        let rows = stmt.query_map(params![pool_key(pool_id), from_epoch, to_epoch], |row| {
+            Ok(HealthSample {
+                recorded_at: row.get(0)?,
+                pool_id: row.get(1)?,
+                epoch: row.get(2)?,
+                slot: row.get(3)?,
+                node_connected: row.get(4)?,
+                node_synced: row.get(5)?,
+                sync_progress: row.get(6)?,
+                blockfrost_connected: row.get(7)?,
+                pool_active: row.get(8)?,
+                kes_remaining_days: row.get(9)?,
+                warning_count: row.get(10)?,
+            })
+        })?;
+
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    /// Epochs that already have a stored snapshot for the pool.
+    pub fn snapshot_epochs(&self, pool_id: &str) -> Result<Vec<u64>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT epoch FROM pool_snapshots WHERE pool_id = ?1 ORDER BY epoch",
+        )?;
+        let rows = stmt.query_map(params![pool_key(pool_id)], |row| row.get(0))?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+}
+
+fn now() -> String {
+    chrono::Utc::now().to_rfc3339()
+}
+
+/// Rewrite rows stored under hex pool IDs to the bech32 form every write
+/// now uses. Where both forms hold the same epoch the bech32 row wins.
+pub(crate) fn normalize_pool_ids(conn: &Connection) -> Result<()> {
+    for table in ["pool_snapshots", "pool_blocks", "pool_rewards", "health_samples"] {
+        let ids: Vec<String> = {
+            let mut stmt = conn.prepare(&format!("SELECT DISTINCT pool_id FROM {} WHERE pool_id IS NOT NULL", table))?;
+            let rows = stmt.query_map([], |row| row.get(0))?;
+            rows.collect::<rusqlite::Result<_>>()?
+        };
+        for id in ids {
+            let key = pool_key(&id);
+            if key != id {
+                conn.execute(&format!("UPDATE OR IGNORE {} SET pool_id = ?1 WHERE pool_id = ?2", table), params![key, id])?;
+                conn.execute(&format!("DELETE FROM {} WHERE pool_id = ?1", table), params![id])?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Pools are stored under their bech32 ID, however the caller spelled it.
+fn pool_key(pool_id: &str) -> String {
+    pool_id.parse::<PoolId>().map_or_else(|_| pool_id.to_string(), |id| id.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BECH32: &str = "pool1pu5jlj4q9w9jlxeu370a3c9myx47md5j5m2str0naunn2q3lkdy";
+    const HEX: &str = "0f292fcaa02b8b2f9b3c8f9fd8e0bb21abedb692a6d5058df3ef2735";
+
+    fn snapshot(pool_id: &str, epoch: u64) -> PoolSnapshot {
+        PoolSnapshot {
+            pool_id: pool_id.to_string(),
+            epoch,
+            live_stake: Some(1_000),
+            active_stake: None,
+            total_stake: None,
+            saturation: None,
+            delegator_count: None,
+            source: "test".to_string(),
+        }
+    }
+
+    #[test]
+    fn hex_and_bech32_ids_share_history() {
+        let store = HistoryStore::open(":memory:").unwrap();
+        store.record_pool_snapshot(&snapshot(HEX, 500)).unwrap();
+        store.record_pool_snapshot(&snapshot(BECH32, 501)).unwrap();
+        store.record_blocks(HEX, 500, 3).unwrap();
+
+        let snapshots = store.pool_snapshots(BECH32, 0, 1_000).unwrap();
+        assert_eq!(snapshots.iter().map(|s| s.epoch).collect::<Vec<_>>(), vec![500, 501]);
+        assert!(snapshots.iter().all(|s| s.pool_id == BECH32));
+        assert_eq!(store.pool_blocks(HEX, 0, 1_000).unwrap(), vec![(500, 3)]);
+        assert_eq!(store.snapshot_epochs(HEX).unwrap(), vec![500, 501]);
+    }
+
+    #[test]
+    fn rows_stored_under_hex_ids_are_migrated() {
+        let path = std::env::temp_dir().join(format!("cvc-history-{}.db", std::process::id()));
+        let path = path.to_str().unwrap();
+        {
+            let store = HistoryStore::open(path).unwrap();
+            // A version 1 database, as written before IDs were normalized
+            store.conn.execute("DELETE FROM schema_migrations WHERE version > 1", []).unwrap();
+            for (pool_id, epoch) in [(HEX, 500), (HEX, 501), (BECH32, 501)] {
+                store.conn.execute(
+                    "INSERT INTO pool_blocks (pool_id, epoch, block_count, recorded_at) VALUES (?1, ?2, ?3, ?4)",
+                    params![pool_id, epoch, if pool_id == HEX { 1 } else { 2 }, now()],
+                ).unwrap();
+            }
+        }
+
+        let hex_rows = |store: &HistoryStore| -> u64 {
+            store.conn.query_row("SELECT COUNT(*) FROM pool_blocks WHERE pool_id = ?1", params![HEX], |row| row.get(0)).unwrap()
+        };
+        {
+            let store = HistoryStore::open(path).unwrap();
+            assert_eq!(store.pool_blocks(BECH32, 0, 1_000).unwrap(), vec![(500, 1), (501, 2)]);
+            assert_eq!(hex_rows(&store), 0);
+            store.conn.execute(
+                "INSERT INTO pool_blocks (pool_id, epoch, block_count, recorded_at) VALUES (?1, 502, 3, ?2)",
+                params![HEX, now()],
+            ).unwrap();
+        }
+
+        // The rewrite is a migration, so later opens leave the tables alone
+        let store = HistoryStore::open(path).unwrap();
+        let _ = std::fs::remove_file(path);
+        assert_eq!(hex_rows(&store), 1);
+    }
+}
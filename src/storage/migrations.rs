@@ -0,0 +1,66 @@
+use anyhow::Result;
+use rusqlite::Connection;
+use crate::storage::history::normalize_pool_ids;
+
+/// One schema step: plain SQL, or code for rewrites SQL can't express.
+pub enum Migration {
+    Sql(&'static str),
+    Code(fn(&Connection) -> Result<()>),
+}
+
+// Schema migrations, applied in order. Never edit a released migration;
+// append a new one instead.
+pub const MIGRATIONS: &[Migration] = &[
+    // 1: initial schema
+    Migration::Sql("CREATE TABLE pool_snapshots (
+        pool_id TEXT NOT NULL,
+        epoch INTEGER NOT NULL,
+        live_stake INTEGER,
+        active_stake INTEGER,
+        total_stake INTEGER,
+        saturation REAL,
+        delegator_count INTEGER,
+        source TEXT NOT NULL,
+        recorded_at TEXT NOT NULL,
+        PRIMARY KEY (pool_id, epoch)
+    );
+
+    CREATE TABLE pool_blocks (
+        pool_id TEXT NOT NULL,
+        epoch INTEGER NOT NULL,
+        block_count INTEGER NOT NULL,
+        recorded_at TEXT NOT NULL,
+        PRIMARY KEY (pool_id, epoch)
+    );
+
+    CREATE TABLE pool_rewards (
+        pool_id TEXT NOT NULL,
+        epoch INTEGER NOT NULL,
+        total_rewards REAL NOT NULL,
+        pool_rewards REAL NOT NULL,
+        delegator_rewards REAL NOT NULL,
+        fees REAL NOT NULL,
+        delegator_count INTEGER NOT NULL,
+        recorded_at TEXT NOT NULL,
+        PRIMARY KEY (pool_id, epoch)
+    );
+
+    CREATE TABLE health_samples (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        recorded_at TEXT NOT NULL,
+        pool_id TEXT,
+        epoch INTEGER NOT NULL,
+        slot INTEGER NOT NULL,
+        node_connected INTEGER NOT NULL,
+        node_synced INTEGER NOT NULL,
+        sync_progress REAL NOT NULL,
+        blockfrost_connected INTEGER NOT NULL,
+        pool_active INTEGER NOT NULL,
+        kes_remaining_days REAL,
+        warning_count INTEGER NOT NULL
+    );
+
+    CREATE INDEX idx_health_samples_pool_epoch ON health_samples (pool_id, epoch);"),
+    // 2: rows written under hex pool IDs move to bech32
+    Migration::Code(normalize_pool_ids),
+];
@@ -0,0 +1,28 @@
+use anyhow::{Context, Result};
+use colored::*;
+use crate::cardano::pool_id::PoolId;
+use crate::utils::config::{Config, PoolConfig};
+use crate::progress;
+
+// Helpers for commands that act on a single pool and change files or chain
+// state, such as KES rotation and reward withdrawal.
+
+/// The pool to act on: the one selected, or the only one configured.
+pub fn find_pool<'a>(config: &'a Config, pool_id: Option<&PoolId>) -> Result<&'a PoolConfig> {
+    match pool_id {
+        Some(id) => config.pools.iter()
+            .find(|p| &p.pool_id == id)
+            .with_context(|| format!("Pool {} is not configured", id)),
+        None if config.pools.len() == 1 => Ok(&config.pools[0]),
+        None => anyhow::bail!("Several pools are configured - select one with --pool-id"),
+    }
+}
+
+/// Announce one step of a plan, marked as skipped under `--dry-run`.
+pub fn step(dry_run: bool, description: &str) {
+    if dry_run {
+        progress!("  {} {}", "[dry-run]".yellow(), description);
+    } else {
+        progress!("  {} {}", "→".cyan(), description);
+    }
+}
@@ -0,0 +1,50 @@
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::json;
+use std::time::Duration;
+use crate::utils::config::AlertsConfig;
+use crate::utils::error::CliError;
+
+/// A condition worth telling the operator about outside the terminal.
+#[derive(Debug, Clone, Serialize)]
+pub struct Alert {
+    pub kind: String,
+    pub pool_id: String,
+    pub ticker: String,
+    pub message: String,
+    pub raised_at: String,
+}
+
+impl Alert {
+    pub fn new(kind: &str, pool_id: &str, ticker: &str, message: String) -> Self {
+        Self {
+            kind: kind.to_string(),
+            pool_id: pool_id.to_string(),
+            ticker: ticker.to_string(),
+            message,
+            raised_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+/// Post an alert to `[monitoring.alerts] webhook_url`, if one is configured.
+/// `text` carries the message for Slack and Discord style hooks; the fields
+/// are under `alert`.
+pub async fn send_webhook(alerts: &AlertsConfig, alert: &Alert) -> Result<()> {
+    if alerts.webhook_url.is_empty() {
+        return Ok(());
+    }
+
+    let response = reqwest::Client::new()
+        .post(&alerts.webhook_url)
+        .timeout(Duration::from_secs(10))
+        .json(&json!({ "text": alert.message, "alert": alert }))
+        .send()
+        .await
+        .map_err(|e| CliError::Network(format!("Failed to send alert to webhook: {}", e)))?;
+
+    if !response.status().is_success() {
+        return Err(CliError::Network(format!("Alert webhook returned {}", response.status())).into());
+    }
+    Ok(())
+}
@@ -1,15 +1,36 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use crate::cardano::pool_id::PoolId;
+use crate::utils::error::CliError;
+use crate::utils::secret::{self, Secret};
+use crate::utils::validation::{self, Severity};
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Config {
     pub cardano: CardanoConfig,
     pub blockfrost: Option<BlockfrostConfig>,
+    pub koios: Option<KoiosConfig>,
+    pub ogmios: Option<OgmiosConfig>,
     pub pools: Vec<PoolConfig>,
     pub monitoring: MonitoringConfig,
     pub rewards: RewardsConfig,
+    #[serde(default)]
+    pub storage: StorageConfig,
+    #[serde(default)]
+    pub daemon: DaemonConfig,
+    #[serde(default)]
+    pub providers: ProvidersConfig,
+    #[serde(default)]
+    pub verification: VerificationConfig,
+    #[serde(default)]
+    pub cache: CacheConfig,
+    #[serde(default)]
+    pub secrets: SecretsConfig,
+    /// The `[profiles.<name>]` overlay this config was loaded with.
+    #[serde(skip)]
+    pub profile: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -18,22 +39,51 @@ pub struct CardanoConfig {
     pub node_socket_path: String,
     pub network: String,
     pub testnet_magic: Option<u32>,
+    pub byron_genesis_file: Option<String>,
+    pub shelley_genesis_file: Option<String>,
+    pub node_prometheus_url: Option<String>,
+    pub node_ekg_url: Option<String>,
 }
 
+/// The project ID comes from `api_key_env`, `api_key_file` or `api_key`,
+/// in that order of precedence.
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct BlockfrostConfig {
-    pub api_key: String,
+    #[serde(default, skip_serializing_if = "Secret::is_empty")]
+    pub api_key: Secret,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key_file: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key_env: Option<String>,
     pub base_url: String,
 }
 
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct KoiosConfig {
+    pub base_url: String,
+    pub api_token: Option<Secret>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_token_file: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_token_env: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct OgmiosConfig {
+    pub url: String,
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct PoolConfig {
-    pub pool_id: String,
+    pub pool_id: PoolId,
     pub name: String,
     pub ticker: String,
     pub vrf_key_file: String,
     pub pledge_address: String,
     pub reward_address: String,
+    pub opcert_file: Option<String>,
+    pub kes_skey_file: Option<String>,
+    pub kes_vkey_file: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -51,6 +101,20 @@ pub struct AlertsConfig {
     pub webhook_url: String,
     pub saturation_threshold: f64,
     pub missed_blocks_threshold: u64,
+    /// Leader checks the node may miss since it started before the health
+    /// check warns.
+    #[serde(default = "default_missed_slots_threshold")]
+    pub missed_slots_threshold: u64,
+    #[serde(default = "default_kes_warning_days")]
+    pub kes_warning_days: u64,
+}
+
+fn default_missed_slots_threshold() -> u64 {
+    20
+}
+
+fn default_kes_warning_days() -> u64 {
+    14
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -62,22 +126,471 @@ pub struct RewardsConfig {
     pub delegation_rewards_percentage: f64,
 }
 
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct StorageConfig {
+    pub enabled: bool,
+    pub database_path: String,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        StorageConfig {
+            enabled: true,
+            database_path: "./data/history.db".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct DaemonConfig {
+    pub tip_interval_seconds: u64,
+    pub backfill_epochs: u64,
+    pub status_file: String,
+}
+
+impl Default for DaemonConfig {
+    fn default() -> Self {
+        DaemonConfig {
+            tip_interval_seconds: 20,
+            backfill_epochs: 20,
+            status_file: "./data/daemon-status.json".to_string(),
+        }
+    }
+}
+
+/// Chain data sources, tried in order until one answers.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ProvidersConfig {
+    pub order: Vec<String>,
+}
+
+impl Default for ProvidersConfig {
+    fn default() -> Self {
+        ProvidersConfig {
+            order: vec!["cardano-cli".to_string(), "blockfrost".to_string(), "koios".to_string()],
+        }
+    }
+}
+
+/// How far providers may disagree before `verify-sources` flags them.
+/// Fields left out of the file keep their defaults.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct VerificationConfig {
+    pub max_slot_lag: u64,
+    pub max_stake_delta_percent: f64,
+    pub max_block_delta: u64,
+}
+
+impl Default for VerificationConfig {
+    fn default() -> Self {
+        VerificationConfig {
+            max_slot_lag: 120,
+            max_stake_delta_percent: 1.0,
+            max_block_delta: 3,
+        }
+    }
+}
+
+/// Query cache; epoch-scoped answers expire at the next epoch boundary.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct CacheConfig {
+    pub enabled: bool,
+    pub disk_enabled: bool,
+    pub directory: String,
+    pub tip_ttl_seconds: u64,
+    pub default_ttl_seconds: u64,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        CacheConfig {
+            enabled: true,
+            disk_enabled: false,
+            directory: "./data/cache".to_string(),
+            tip_ttl_seconds: 10,
+            default_ttl_seconds: 300,
+        }
+    }
+}
+
+/// An age-encrypted TOML file merged over the config files, e.g. holding
+/// `[blockfrost] api_key = "..."`. Created with `age -p -o secrets.toml.age secrets.toml`.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct SecretsConfig {
+    pub file: Option<String>,
+    pub identity_file: Option<String>,
+    pub age_path: String,
+}
+
+impl Default for SecretsConfig {
+    fn default() -> Self {
+        SecretsConfig {
+            file: None,
+            identity_file: None,
+            age_path: "age".to_string(),
+        }
+    }
+}
+
+impl CardanoConfig {
+    pub fn network_magic(&self) -> u32 {
+        if let Some(magic) = self.testnet_magic {
+            return magic;
+        }
+
+        match self.network.as_str() {
+            "preprod" => 1,
+            "preview" => 2,
+            "testnet" => 1097911063,
+            _ => 764824073,
+        }
+    }
+}
+
+/// Directory name used under `/etc` and `$XDG_CONFIG_HOME`.
+pub const APP_DIR: &str = "cardano-validator-cli";
+
+/// Environment variables starting with this prefix override config fields.
+/// Nested keys are separated by `__`, e.g. `CVC_BLOCKFROST__API_KEY`.
+pub const ENV_PREFIX: &str = "CVC_";
+
+/// Names under the prefix that select the config rather than override a field.
+pub const RESERVED_ENV: &[&str] = &["CVC_CONFIG", "CVC_PROFILE"];
+
+pub fn system_config_path() -> PathBuf {
+    Path::new("/etc").join(APP_DIR).join("config.toml")
+}
+
+pub fn user_config_path() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_CONFIG_HOME").filter(|v| !v.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+    Some(base.join(APP_DIR).join("config.toml"))
+}
+
+pub fn project_config_path() -> PathBuf {
+    PathBuf::from("config.toml")
+}
+
 impl Config {
-    pub fn load_or_create_default() -> Result<Self> {
-        let config_path = "config.toml";
+    /// Load the effective configuration. An explicit path (`--config` or
+    /// `CVC_CONFIG`) is used on its own; otherwise the system, user and
+    /// project files are merged in that order. The selected profile is merged
+    /// over the result and `CVC_*` variables are applied last.
+    pub fn load(explicit: Option<&Path>, profile: Option<&str>) -> Result<Self> {
+        let (config, layers) = Self::load_unvalidated(explicit, profile)?;
+
+        let report = validation::validate_layers(&config, &layers);
+        for diagnostic in report.diagnostics.iter().filter(|d| d.severity == Severity::Warning) {
+            log::warn!("{}: {}", diagnostic.field, diagnostic.message);
+        }
+        if report.errors() > 0 {
+            let details: String = report.diagnostics.iter()
+                .filter(|d| d.severity == Severity::Error)
+                .map(|d| validation::format_diagnostic(d) + "\n")
+                .collect();
+            return Err(CliError::Config(format!(
+                "Invalid configuration - {} error(s):\n\n{}Run `config validate` to see every problem", report.errors(), details
+            )).into());
+        }
+        Ok(config)
+    }
+
+    /// Merge the layers and apply overrides without semantic checks, so
+    /// `config validate` can report on a broken file.
+    pub fn load_unvalidated(explicit: Option<&Path>, profile: Option<&str>) -> Result<(Self, Vec<PathBuf>)> {
+        let layers = Self::layers(explicit)?;
+        let mut merged = merge_layers(&layers)?;
+
+        merge_secrets_file(&mut merged)?;
+
+        let profile = profile.map(String::from)
+            .or_else(|| std::env::var("CVC_PROFILE").ok().filter(|v| !v.is_empty()))
+            .or_else(|| merged.get("default_profile").and_then(|v| v.as_str()).map(String::from));
+        if let Some(name) = &profile {
+            let overlay = merged.get("profiles").and_then(|p| p.get(name)).cloned().ok_or_else(|| {
+                CliError::Config(format!("Profile '{}' is not defined - available: {}", name, profile_names(&merged).join(", ")))
+            })?;
+            merge_values(&mut merged, overlay);
+            log::debug!("Applied profile {}", name);
+        }
+
+        apply_env_overrides(&mut merged, std::env::vars())?;
 
-        if Path::new(config_path).exists() {
-            let content = fs::read_to_string(config_path)?;
-            let config: Config = toml::from_str(&content)?;
-            Ok(config)
-        } else {
-            let default_config = Self::default();
-            let toml_content = toml::to_string_pretty(&default_config)?;
-            fs::write(config_path, toml_content)?;
-            println!("Created default config file: {}", config_path);
-            Ok(default_config)
+        match merged.try_into::<Config>() {
+            Ok(mut config) => {
+                config.resolve_secrets()?;
+                config.profile = profile;
+                Ok((config, layers))
+            }
+            Err(e) => {
+                // A single file parsed on its own keeps line and column information
+                if let ([path], None) = (layers.as_slice(), &profile) {
+                    let content = fs::read_to_string(path)?;
+                    if let Err(e) = toml::from_str::<Config>(&content) {
+                        return Err(CliError::Config(format!("Invalid {}: {}", path.display(), e)).into());
+                    }
+                }
+                let scope = profile.map_or(String::new(), |name| format!(", profile {}", name));
+                Err(CliError::Config(format!("Invalid configuration ({}{}): {}", describe_layers(&layers), scope, e)).into())
+            }
         }
     }
+
+    /// Names of the `[profiles.<name>]` tables across all config files.
+    pub fn profile_names(explicit: Option<&Path>) -> Result<Vec<String>> {
+        let layers = Self::layers(explicit)?;
+        Ok(profile_names(&merge_layers(&layers)?))
+    }
+
+    /// The files that make up the configuration, lowest precedence first.
+    pub fn layers(explicit: Option<&Path>) -> Result<Vec<PathBuf>> {
+        let explicit = explicit.map(PathBuf::from)
+            .or_else(|| std::env::var_os("CVC_CONFIG").filter(|v| !v.is_empty()).map(PathBuf::from));
+
+        if let Some(path) = explicit {
+            if !path.exists() {
+                return Err(CliError::Config(format!("Config file {} does not exist", path.display())).into());
+            }
+            return Ok(vec![path]);
+        }
+
+        Ok(Self::search_paths().into_iter().filter(|p| p.exists()).collect())
+    }
+
+    fn resolve_secrets(&mut self) -> Result<()> {
+        if let Some(blockfrost) = &mut self.blockfrost {
+            blockfrost.api_key = secret::resolve(
+                "blockfrost.api_key",
+                &blockfrost.api_key,
+                blockfrost.api_key_file.as_deref(),
+                blockfrost.api_key_env.as_deref(),
+            )?;
+        }
+        if let Some(koios) = &mut self.koios {
+            let token = secret::resolve(
+                "koios.api_token",
+                &koios.api_token.clone().unwrap_or_default(),
+                koios.api_token_file.as_deref(),
+                koios.api_token_env.as_deref(),
+            )?;
+            koios.api_token = (!token.is_empty()).then_some(token);
+        }
+        Ok(())
+    }
+
+    fn search_paths() -> Vec<PathBuf> {
+        let mut paths = vec![system_config_path()];
+        paths.extend(user_config_path());
+        paths.push(project_config_path());
+        paths
+    }
+}
+
+fn merge_layers(layers: &[PathBuf]) -> Result<toml::Value> {
+    if layers.is_empty() {
+        return Err(CliError::Config(format!(
+            "No config file found (looked in {}) - run `config init` to create one",
+            Config::search_paths().iter().map(|p| p.display().to_string()).collect::<Vec<_>>().join(", ")
+        )).into());
+    }
+
+    let mut merged = toml::Value::Table(toml::map::Map::new());
+    for path in layers {
+        let content = fs::read_to_string(path)
+            .map_err(|e| CliError::Config(format!("Failed to read {}: {}", path.display(), e)))?;
+        let layer: toml::Value = toml::from_str(&content)
+            .map_err(|e| CliError::Config(format!("Invalid {}: {}", path.display(), e)))?;
+        log::debug!("Loaded config layer {}", path.display());
+        if has_inline_secrets(&layer) && secret::is_shared_readable(&path.to_string_lossy()) {
+            eprintln!("Warning: {} contains API keys and is readable by other users - run `chmod 600 {}`",
+                      path.display(), path.display());
+        }
+        merge_values(&mut merged, layer);
+    }
+    Ok(merged)
+}
+
+fn profile_names(merged: &toml::Value) -> Vec<String> {
+    merged.get("profiles")
+        .and_then(|p| p.as_table())
+        .map_or_else(Vec::new, |table| table.keys().cloned().collect())
+}
+
+fn describe_layers(layers: &[PathBuf]) -> String {
+    layers.iter().map(|p| p.display().to_string()).collect::<Vec<_>>().join(" + ")
+}
+
+fn has_inline_secrets(layer: &toml::Value) -> bool {
+    let inline = |table: &toml::Value| {
+        table.get("blockfrost").and_then(|b| b.get("api_key")).is_some()
+            || table.get("koios").and_then(|k| k.get("api_token")).is_some()
+    };
+    inline(layer) || layer.get("profiles")
+        .and_then(|p| p.as_table())
+        .is_some_and(|profiles| profiles.values().any(inline))
+}
+
+/// Decrypt `[secrets] file` and merge it over everything else, so secrets
+/// never have to be written to a config file in plain text.
+fn merge_secrets_file(merged: &mut toml::Value) -> Result<()> {
+    let secrets: SecretsConfig = match merged.get("secrets") {
+        Some(value) => value.clone().try_into()
+            .map_err(|e| CliError::Config(format!("Invalid [secrets] section: {}", e)))?,
+        None => return Ok(()),
+    };
+    let path = match &secrets.file {
+        Some(path) => path.clone(),
+        None => return Ok(()),
+    };
+
+    let content = secret::decrypt_secrets_file(&secrets, &path)?;
+    let overlay: toml::Value = toml::from_str(&content)
+        .map_err(|e| CliError::Config(format!("Invalid decrypted secrets file {}: {}", path, e)))?;
+    merge_values(merged, overlay);
+    log::debug!("Merged encrypted secrets from {}", path);
+    Ok(())
+}
+
+/// Merge `overlay` into `base`. Tables merge key by key; anything else,
+/// arrays included, is replaced wholesale.
+pub fn merge_values(base: &mut toml::Value, overlay: toml::Value) {
+    match (base, overlay) {
+        (toml::Value::Table(base), toml::Value::Table(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_values(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+fn apply_env_overrides(config: &mut toml::Value, vars: impl Iterator<Item = (String, String)>) -> Result<()> {
+    let mut overrides: Vec<(String, String)> = vars
+        .filter(|(name, _)| name.starts_with(ENV_PREFIX) && !RESERVED_ENV.contains(&name.as_str()))
+        .collect();
+    // Apply in a stable order so parent tables exist before their fields
+    overrides.sort();
+    let template = field_types();
+
+    for (name, raw) in overrides {
+        let path: Vec<String> = name[ENV_PREFIX.len()..]
+            .split("__")
+            .map(|segment| segment.to_lowercase())
+            .collect();
+        if path.iter().any(|segment| segment.is_empty()) {
+            return Err(CliError::Config(format!("Malformed override {} - separate nested keys with `__`", name)).into());
+        }
+
+        set_path(config, &path, &raw, field_type(&template, &path))
+            .map_err(|e| CliError::Config(format!("Cannot apply {}: {}", name, e)))?;
+        log::debug!("Applied config override {}", name);
+    }
+    Ok(())
+}
+
+/// A config with every optional field filled in, so an override of a field
+/// the files leave out is still parsed as the field's type: a numeric-looking
+/// API key stays a string.
+fn field_types() -> toml::Value {
+    let some = || Some(String::new());
+    let mut config = Config::default();
+    config.cardano.testnet_magic = Some(0);
+    config.cardano.byron_genesis_file = some();
+    config.cardano.shelley_genesis_file = some();
+    config.cardano.node_prometheus_url = some();
+    config.cardano.node_ekg_url = some();
+    config.blockfrost = Some(BlockfrostConfig {
+        api_key: Secret::new("-"),
+        api_key_file: some(),
+        api_key_env: some(),
+        base_url: String::new(),
+    });
+    config.koios = Some(KoiosConfig {
+        base_url: String::new(),
+        api_token: Some(Secret::new("-")),
+        api_token_file: some(),
+        api_token_env: some(),
+    });
+    config.ogmios = Some(OgmiosConfig { url: String::new() });
+    for pool in &mut config.pools {
+        pool.opcert_file = some();
+        pool.kes_skey_file = some();
+        pool.kes_vkey_file = some();
+    }
+    config.secrets.file = some();
+    config.secrets.identity_file = some();
+    toml::Value::try_from(&config).expect("the default config serializes")
+}
+
+/// The template value at an override path. Profiles take the fields of the
+/// top level and every array entry those of the first.
+fn field_type<'a>(template: &'a toml::Value, path: &[String]) -> Option<&'a toml::Value> {
+    let path = match path {
+        [profiles, _, rest @ ..] if profiles == "profiles" => rest,
+        path => path,
+    };
+    path.iter().try_fold(template, |value, key| match value {
+        toml::Value::Array(items) => key.parse::<usize>().ok().and_then(|_| items.first()),
+        value => value.get(key),
+    })
+}
+
+fn set_path(value: &mut toml::Value, path: &[String], raw: &str, field_type: Option<&toml::Value>) -> std::result::Result<(), String> {
+    let (key, rest) = path.split_first().expect("override path is never empty");
+
+    let slot = match value {
+        toml::Value::Table(table) => {
+            if rest.is_empty() {
+                let parsed = parse_override(table.get(key).or(field_type), raw)?;
+                table.insert(key.clone(), parsed);
+                return Ok(());
+            }
+            table.entry(key.clone()).or_insert_with(|| toml::Value::Table(toml::map::Map::new()))
+        }
+        toml::Value::Array(items) => {
+            let index: usize = key.parse().map_err(|_| format!("'{}' is not an array index", key))?;
+            let len = items.len();
+            let item = items.get_mut(index).ok_or_else(|| format!("index {} is out of range ({} entries)", index, len))?;
+            if rest.is_empty() {
+                *item = parse_override(Some(item), raw)?;
+                return Ok(());
+            }
+            item
+        }
+        _ => return Err(format!("'{}' is not a table", key)),
+    };
+    set_path(slot, rest, raw, field_type)
+}
+
+/// Parse an override using the type of the value it replaces, if any.
+fn parse_override(existing: Option<&toml::Value>, raw: &str) -> std::result::Result<toml::Value, String> {
+    match existing {
+        Some(toml::Value::String(_)) => Ok(toml::Value::String(raw.to_string())),
+        Some(toml::Value::Integer(_)) => raw.trim().parse().map(toml::Value::Integer)
+            .map_err(|_| format!("expected an integer, got '{}'", raw)),
+        Some(toml::Value::Float(_)) => raw.trim().parse().map(toml::Value::Float)
+            .map_err(|_| format!("expected a number, got '{}'", raw)),
+        Some(toml::Value::Boolean(_)) => raw.trim().parse().map(toml::Value::Boolean)
+            .map_err(|_| format!("expected true or false, got '{}'", raw)),
+        // Lists may be given as plain comma-separated strings
+        Some(toml::Value::Array(_)) if !raw.trim_start().starts_with('[') => Ok(toml::Value::Array(
+            raw.split(',').map(|item| toml::Value::String(item.trim().to_string())).collect()
+        )),
+        _ => Ok(parse_literal(raw).unwrap_or_else(|| toml::Value::String(raw.to_string()))),
+    }
+}
+
+fn parse_literal(raw: &str) -> Option<toml::Value> {
+    let mut table: toml::map::Map<String, toml::Value> = toml::from_str(&format!("value = {}", raw)).ok()?;
+    table.remove("value")
 }
 
 impl Default for Config {
@@ -88,19 +601,35 @@ impl Default for Config {
                 node_socket_path: "/opt/cardano/cnode/sockets/node0.socket".to_string(),
                 network: "mainnet".to_string(),
                 testnet_magic: None,
+                byron_genesis_file: None,
+                shelley_genesis_file: None,
+                node_prometheus_url: None,
+                node_ekg_url: None,
             },
             blockfrost: Some(BlockfrostConfig {
-                api_key: "mainnetguL8XQNJLM2CRQYmcWWsxK64OElqoKVb".to_string(),
+                api_key: Secret::default(),
+                api_key_file: None,
+                api_key_env: Some("BLOCKFROST_PROJECT_ID".to_string()),
                 base_url: "https://cardano-mainnet.blockfrost.io/api/v0".to_string(),
             }),
+            koios: Some(KoiosConfig {
+                base_url: "https://api.koios.rest/api/v1".to_string(),
+                api_token: None,
+                api_token_file: None,
+                api_token_env: None,
+            }),
+            ogmios: None,
             pools: vec![
                 PoolConfig {
-                    pool_id: "pool1pu5jlj4q9w9jlxeu370a3c9myx47md5j5m2str0naunn2q3lkdy".to_string(),
+                    pool_id: "pool1pu5jlj4q9w9jlxeu370a3c9myx47md5j5m2str0naunn2q3lkdy".parse().expect("valid pool ID"),
                     name: "ADALITE".to_string(),
                     ticker: "ADLT".to_string(),
                     vrf_key_file: "vrf.vkey".to_string(),
-                    pledge_address: "addr1...".to_string(),
-                    reward_address: "stake1...".to_string(),
+                    pledge_address: String::new(),
+                    reward_address: String::new(),
+                    opcert_file: None,
+                    kes_skey_file: None,
+                    kes_vkey_file: None,
                 },
             ],
             monitoring: MonitoringConfig {
@@ -113,6 +642,8 @@ impl Default for Config {
                     webhook_url: "".to_string(),
                     saturation_threshold: 0.8,
                     missed_blocks_threshold: 2,
+                    missed_slots_threshold: default_missed_slots_threshold(),
+                    kes_warning_days: default_kes_warning_days(),
                 },
             },
             rewards: RewardsConfig {
@@ -122,6 +653,171 @@ impl Default for Config {
                 include_fees: true,
                 delegation_rewards_percentage: 95.0,
             },
+            storage: StorageConfig::default(),
+            daemon: DaemonConfig::default(),
+            providers: ProvidersConfig::default(),
+            verification: VerificationConfig::default(),
+            cache: CacheConfig::default(),
+            secrets: SecretsConfig::default(),
+            profile: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::error::classify;
+
+    fn apply(config: &str, vars: &[(&str, &str)]) -> Result<toml::Value> {
+        let mut value: toml::Value = toml::from_str(config).unwrap();
+        apply_env_overrides(&mut value, vars.iter().map(|(k, v)| (k.to_string(), v.to_string())))?;
+        Ok(value)
+    }
+
+    fn defaults() -> String {
+        toml::to_string(&Config::default()).unwrap()
+    }
+
+    #[test]
+    fn overrides_take_the_type_of_the_field() {
+        let value = apply(&defaults(), &[
+            ("CVC_MONITORING__PROMETHEUS_PORT", "9100"),
+            ("CVC_MONITORING__ALERTS__SATURATION_THRESHOLD", "0.9"),
+            ("CVC_MONITORING__ENABLED", "false"),
+            ("CVC_CARDANO__NETWORK", "preprod"),
+            ("CVC_PROVIDERS__ORDER", "koios, blockfrost"),
+        ]).unwrap();
+        let config: Config = value.try_into().unwrap();
+
+        assert_eq!(config.monitoring.prometheus_port, 9100);
+        assert_eq!(config.monitoring.alerts.saturation_threshold, 0.9);
+        assert!(!config.monitoring.enabled);
+        assert_eq!(config.cardano.network, "preprod");
+        assert_eq!(config.providers.order, vec!["koios", "blockfrost"]);
+    }
+
+    #[test]
+    fn fields_missing_from_the_files_keep_their_type() {
+        // Neither key is in the config; the API key must not become an integer
+        let value = apply(&defaults(), &[
+            ("CVC_BLOCKFROST__API_KEY", "12345"),
+            ("CVC_CARDANO__TESTNET_MAGIC", "2"),
+            ("CVC_POOLS__0__OPCERT_FILE", "0001"),
+            ("CVC_PROFILES__TESTNET__BLOCKFROST__API_KEY", "007"),
+        ]).unwrap();
+
+        assert_eq!(value["blockfrost"]["api_key"].as_str(), Some("12345"));
+        assert_eq!(value["cardano"]["testnet_magic"].as_integer(), Some(2));
+        assert_eq!(value["pools"][0]["opcert_file"].as_str(), Some("0001"));
+        assert_eq!(value["profiles"]["testnet"]["blockfrost"]["api_key"].as_str(), Some("007"));
+
+        let config: Config = value.try_into().unwrap();
+        assert_eq!(config.blockfrost.unwrap().api_key.expose(), "12345");
+    }
+
+    #[test]
+    fn unknown_fields_are_parsed_as_literals() {
+        let value = apply(&defaults(), &[("CVC_EXTRA__COUNT", "3"), ("CVC_EXTRA__NAME", "relay-1")]).unwrap();
+        assert_eq!(value["extra"]["count"].as_integer(), Some(3));
+        assert_eq!(value["extra"]["name"].as_str(), Some("relay-1"));
+    }
+
+    #[test]
+    fn invalid_overrides_are_config_errors() {
+        let error = apply(&defaults(), &[("CVC_MONITORING__PROMETHEUS_PORT", "ninety")]).unwrap_err();
+        assert!(matches!(crate::utils::error::classify(&error), Some(CliError::Config(_))));
+        assert!(error.to_string().contains("expected an integer"), "{}", error);
+
+        let error = apply(&defaults(), &[("CVC_POOLS__5__NAME", "x")]).unwrap_err();
+        assert!(error.to_string().contains("out of range"), "{}", error);
+
+        assert!(apply(&defaults(), &[("CVC_CARDANO__", "preview")]).is_err());
+    }
+
+    #[test]
+    fn reserved_variables_are_not_overrides() {
+        let value = apply(&defaults(), &[("CVC_CONFIG", "/tmp/x.toml"), ("CVC_PROFILE", "testnet")]).unwrap();
+        assert!(value.get("config").is_none() && value.get("profile").is_none());
+    }
+
+    #[test]
+    fn layers_merge_tables_and_replace_arrays() {
+        let dir = std::env::temp_dir().join(format!("cvc-layers-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let system = dir.join("system.toml");
+        let project = dir.join("project.toml");
+        fs::write(&system, defaults()).unwrap();
+        fs::write(&project, "[cardano]\nnetwork = \"preview\"\n\n[providers]\norder = [\"koios\"]\n").unwrap();
+
+        let merged = merge_layers(&[system, project]);
+        let _ = fs::remove_dir_all(&dir);
+        let config: Config = merged.unwrap().try_into().unwrap();
+
+        // The later layer wins field by field and keeps the rest of the table
+        assert_eq!(config.cardano.network, "preview");
+        assert_eq!(config.cardano.cli_path, "cardano-cli");
+        assert_eq!(config.providers.order, vec!["koios"]);
+        assert_eq!(config.pools.len(), 1);
+    }
+
+    #[test]
+    fn merge_values_recurses_into_tables_only() {
+        let mut base: toml::Value = toml::from_str("a = 1\nlist = [1, 2]\n[t]\nx = 1\ny = 2\n").unwrap();
+        merge_values(&mut base, toml::from_str("list = [3]\n[t]\ny = 3\nz = 4\n").unwrap());
+
+        assert_eq!(base, toml::from_str("a = 1\nlist = [3]\n[t]\nx = 1\ny = 3\nz = 4\n").unwrap());
+    }
+
+    #[test]
+    fn secrets_prefer_env_then_file_then_inline() {
+        let dir = std::env::temp_dir().join(format!("cvc-secret-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("blockfrost.key");
+        fs::write(&file, "mainnetfromfile\n").unwrap();
+        let file = file.to_str().unwrap();
+        let inline = Secret::new("mainnetinline");
+        let env = "TEST_BLOCKFROST_PROJECT_ID";
+
+        std::env::set_var(env, " mainnetfromenv ");
+        let from_env = secret::resolve("blockfrost.api_key", &inline, Some(file), Some(env)).unwrap();
+        std::env::set_var(env, "");
+        let from_file = secret::resolve("blockfrost.api_key", &inline, Some(file), Some(env)).unwrap();
+        std::env::remove_var(env);
+        let from_inline = secret::resolve("blockfrost.api_key", &inline, None, Some(env)).unwrap();
+
+        fs::write(file, "  \n").unwrap();
+        let empty_file = secret::resolve("blockfrost.api_key", &inline, Some(file), None);
+        let _ = fs::remove_dir_all(&dir);
+        let missing_file = secret::resolve("blockfrost.api_key", &inline, Some(file), None);
+
+        assert_eq!(from_env.expose(), "mainnetfromenv");
+        assert_eq!(from_file.expose(), "mainnetfromfile");
+        assert_eq!(from_inline.expose(), "mainnetinline");
+        for result in [empty_file, missing_file] {
+            let error = result.unwrap_err();
+            assert!(matches!(classify(&error), Some(CliError::Config(_))), "{:#}", error);
         }
     }
-}
\ No newline at end of file
+
+    #[cfg(unix)]
+    #[test]
+    fn group_or_world_readable_files_are_shared() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = std::env::temp_dir().join(format!("cvc-shared-{}.key", std::process::id()));
+        fs::write(&path, "secret").unwrap();
+        let mode = |mode: u32| {
+            fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+            secret::is_shared_readable(path.to_str().unwrap())
+        };
+
+        let owner_only = [mode(0o600), mode(0o400), mode(0o700)];
+        let shared = [mode(0o640), mode(0o604), mode(0o644)];
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(owner_only, [false; 3]);
+        assert_eq!(shared, [true; 3]);
+        assert!(!secret::is_shared_readable(path.to_str().unwrap()), "a missing file is not shared");
+    }
+}
@@ -0,0 +1,78 @@
+use std::fmt;
+
+/// Failure categories that scripts can tell apart by exit code.
+#[derive(Debug, Clone)]
+pub enum CliError {
+    Config(String),
+    Network(String),
+    ProviderAuth(String),
+    RateLimited(String),
+    NodeUnreachable(String),
+    NotFound(String),
+    /// The command ran but what it checked is unhealthy or degraded.
+    CheckFailed(String),
+}
+
+impl CliError {
+    /// 1 is left for uncategorised errors and 2 for clap usage errors.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CliError::Config(_) => 3,
+            CliError::Network(_) => 4,
+            CliError::ProviderAuth(_) => 5,
+            CliError::RateLimited(_) => 6,
+            CliError::NodeUnreachable(_) => 7,
+            CliError::NotFound(_) => 8,
+            CliError::CheckFailed(_) => 9,
+        }
+    }
+
+    /// The same category with a different message.
+    pub fn with_message(&self, message: String) -> Self {
+        match self {
+            CliError::Config(_) => CliError::Config(message),
+            CliError::Network(_) => CliError::Network(message),
+            CliError::ProviderAuth(_) => CliError::ProviderAuth(message),
+            CliError::RateLimited(_) => CliError::RateLimited(message),
+            CliError::NodeUnreachable(_) => CliError::NodeUnreachable(message),
+            CliError::NotFound(_) => CliError::NotFound(message),
+            CliError::CheckFailed(_) => CliError::CheckFailed(message),
+        }
+    }
+
+    /// Classify a non-success HTTP status from a provider API.
+    pub fn from_status(service: &str, status: reqwest::StatusCode) -> Self {
+        let message = format!("{} API returned status: {}", service, status);
+        match status.as_u16() {
+            401 | 403 => CliError::ProviderAuth(message),
+            404 => CliError::NotFound(message),
+            429 => CliError::RateLimited(message),
+            _ => CliError::Network(message),
+        }
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::Config(message)
+            | CliError::Network(message)
+            | CliError::ProviderAuth(message)
+            | CliError::RateLimited(message)
+            | CliError::NodeUnreachable(message)
+            | CliError::NotFound(message)
+            | CliError::CheckFailed(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for CliError {}
+
+/// The categorised error anywhere in the chain, if there is one.
+pub fn classify(error: &anyhow::Error) -> Option<&CliError> {
+    error.chain().find_map(|cause| cause.downcast_ref::<CliError>())
+}
+
+pub fn exit_code(error: &anyhow::Error) -> i32 {
+    classify(error).map_or(1, CliError::exit_code)
+}
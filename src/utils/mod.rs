@@ -1,2 +1,8 @@
+pub mod actions;
+pub mod alerts;
 pub mod config;
-pub mod logger;
\ No newline at end of file
+pub mod error;
+pub mod logger;
+pub mod output;
+pub mod secret;
+pub mod validation;
@@ -0,0 +1,193 @@
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::{Mutex, OnceLock};
+
+/// Bumped whenever a field is removed or changes meaning in any schema.
+pub const SCHEMA_VERSION: u32 = 1;
+
+static FORMAT: OnceLock<OutputFormat> = OnceLock::new();
+static PROFILE: Mutex<Option<ProfileTag>> = Mutex::new(None);
+
+/// Identifies which profile a result belongs to when several are configured.
+#[derive(Debug, Clone, Serialize)]
+struct ProfileTag {
+    profile: String,
+    network: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Table,
+    Json,
+    Yaml,
+    Csv,
+    Ndjson,
+}
+
+/// Remember the format chosen on the command line for `progress!`.
+pub fn set_format(format: OutputFormat) {
+    let _ = FORMAT.set(format);
+}
+
+/// Whether human-readable progress should be printed to stdout.
+pub fn is_table() -> bool {
+    FORMAT.get().copied().unwrap_or_default() == OutputFormat::Table
+}
+
+/// Tag structured output with the active profile; `None` when profiles are not in use.
+pub fn set_profile(profile: Option<&str>, network: &str) {
+    let tag = profile.map(|profile| ProfileTag {
+        profile: profile.to_string(),
+        network: network.to_string(),
+    });
+    *PROFILE.lock().unwrap_or_else(|e| e.into_inner()) = tag;
+}
+
+fn current_profile() -> Option<ProfileTag> {
+    PROFILE.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// `println!` that stays quiet when stdout carries structured output.
+#[macro_export]
+macro_rules! progress {
+    ($($arg:tt)*) => {
+        if $crate::utils::output::is_table() {
+            println!($($arg)*);
+        }
+    };
+}
+
+/// A command result that can be printed for humans or serialized for scripts.
+pub trait Render: Serialize {
+    /// Name of the schema in the JSON/YAML envelope, e.g. `pool-status`.
+    const SCHEMA: &'static str;
+
+    fn render_table(&self);
+
+    /// Flat rows for csv and ndjson. Defaults to the whole result as one row.
+    fn records(&self) -> Result<Vec<Value>> {
+        Ok(vec![serde_json::to_value(self)?])
+    }
+}
+
+#[derive(Serialize)]
+struct Envelope<'a, T: Serialize> {
+    schema: &'static str,
+    version: u32,
+    generated_at: String,
+    #[serde(flatten)]
+    profile: Option<ProfileTag>,
+    data: &'a T,
+}
+
+fn envelope<T: Render>(result: &T) -> Envelope<'_, T> {
+    Envelope {
+        schema: T::SCHEMA,
+        version: SCHEMA_VERSION,
+        generated_at: chrono::Utc::now().to_rfc3339(),
+        profile: current_profile(),
+        data: result,
+    }
+}
+
+pub fn render<T: Render>(result: &T, format: OutputFormat) -> Result<()> {
+    match format {
+        OutputFormat::Table => result.render_table(),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&envelope(result))?),
+        OutputFormat::Yaml => print!("{}", serde_yaml::to_string(&envelope(result))?),
+        OutputFormat::Ndjson => {
+            for record in tagged_records(result)? {
+                println!("{}", serde_json::to_string(&record)?);
+            }
+        }
+        OutputFormat::Csv => print_csv(&tagged_records(result)?)?,
+    }
+
+    Ok(())
+}
+
+/// Render one result of a continuous command. A reader cannot split a stream
+/// of pretty-printed envelopes, so `json` prints each envelope on one line.
+pub fn render_stream<T: Render>(result: &T, format: OutputFormat) -> Result<()> {
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string(&envelope(result))?),
+        format => render(result, format)?,
+    }
+    Ok(())
+}
+
+/// Lovelace as ADA to the last lovelace, for balances and pledge.
+pub fn format_ada(lovelace: u64) -> String {
+    format!("{:.6} ₳", lovelace as f64 / 1_000_000.0)
+}
+
+/// Lovelace as rounded ADA (`1.2M ₳`), for stake figures in tables.
+pub fn format_ada_short(lovelace: u64) -> String {
+    let ada = lovelace as f64 / 1_000_000.0;
+    if ada >= 1_000_000.0 {
+        format!("{:.1}M ₳", ada / 1_000_000.0)
+    } else if ada >= 1_000.0 {
+        format!("{:.1}K ₳", ada / 1_000.0)
+    } else {
+        format!("{:.1} ₳", ada)
+    }
+}
+
+/// Records with `profile` and `network` columns added when a profile is active.
+fn tagged_records<T: Render>(result: &T) -> Result<Vec<Value>> {
+    let mut records = result.records()?;
+    if let Some(tag) = current_profile() {
+        for record in records.iter_mut() {
+            if let Value::Object(map) = record {
+                map.insert("profile".to_string(), Value::String(tag.profile.clone()));
+                map.insert("network".to_string(), Value::String(tag.network.clone()));
+            }
+        }
+    }
+    Ok(records)
+}
+
+fn print_csv(records: &[Value]) -> Result<()> {
+    let rows: Vec<BTreeMap<String, String>> = records.iter().map(|record| {
+        let mut row = BTreeMap::new();
+        flatten("", record, &mut row);
+        row
+    }).collect();
+
+    let mut columns: Vec<&String> = rows.iter().flat_map(|row| row.keys()).collect();
+    columns.sort();
+    columns.dedup();
+
+    let mut writer = csv::Writer::from_writer(std::io::stdout());
+    writer.write_record(&columns)?;
+    for row in &rows {
+        writer.write_record(columns.iter().map(|c| row.get(*c).map_or("", |v| v.as_str())))?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Nested objects become dotted columns; arrays stay as embedded JSON.
+fn flatten(prefix: &str, value: &Value, row: &mut BTreeMap<String, String>) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, value) in map {
+                let column = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+                flatten(&column, value, row);
+            }
+        }
+        Value::Object(_) | Value::Null => {
+            row.insert(prefix.to_string(), String::new());
+        }
+        Value::String(s) => {
+            row.insert(prefix.to_string(), s.clone());
+        }
+        other => {
+            row.insert(prefix.to_string(), other.to_string());
+        }
+    }
+}
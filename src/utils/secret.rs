@@ -0,0 +1,104 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::process::{Command, Stdio};
+use crate::utils::config::SecretsConfig;
+use crate::utils::error::CliError;
+
+/// A credential that never shows up in `Debug` output or logs. Use
+/// `expose()` only where the value is handed to the service it is for.
+#[derive(Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn new(value: impl Into<String>) -> Self {
+        Secret(value.into())
+    }
+
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            write!(f, "Secret(<empty>)")
+        } else {
+            write!(f, "Secret(<redacted>)")
+        }
+    }
+}
+
+/// Pick a secret from, in order of precedence, the named environment
+/// variable, a file holding only the secret, or the inline value.
+pub fn resolve(field: &str, inline: &Secret, file: Option<&str>, env: Option<&str>) -> Result<Secret> {
+    if let Some(name) = env {
+        match std::env::var(name) {
+            Ok(value) if !value.trim().is_empty() => return Ok(Secret::new(value.trim())),
+            _ if file.is_none() && inline.is_empty() => {
+                log::warn!("{}: environment variable {} is not set", field, name);
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(path) = file {
+        warn_if_readable(path, field);
+        let value = std::fs::read_to_string(path)
+            .map_err(|e| CliError::Config(format!("{}: cannot read secret file {}: {}", field, path, e)))?;
+        let value = value.trim();
+        if value.is_empty() {
+            return Err(CliError::Config(format!("{}: secret file {} is empty", field, path)).into());
+        }
+        return Ok(Secret::new(value));
+    }
+
+    Ok(inline.clone())
+}
+
+/// Decrypt the `[secrets]` file with the `age` tool. Without an identity file
+/// age asks for the passphrase on the terminal.
+pub fn decrypt_secrets_file(secrets: &SecretsConfig, path: &str) -> Result<String> {
+    let mut command = Command::new(&secrets.age_path);
+    command.arg("--decrypt");
+    if let Some(identity) = &secrets.identity_file {
+        warn_if_readable(identity, "secrets.identity_file");
+        command.arg("--identity").arg(identity);
+    }
+    command.arg(path)
+        .stdin(Stdio::inherit())
+        .stderr(Stdio::inherit());
+
+    let output = command.output()
+        .map_err(|e| CliError::Config(format!("Failed to run {} to decrypt {}: {}", secrets.age_path, path, e)))?;
+    if !output.status.success() {
+        return Err(CliError::Config(format!("Failed to decrypt secrets file {}", path)).into());
+    }
+
+    String::from_utf8(output.stdout)
+        .map_err(|_| CliError::Config(format!("Decrypted secrets file {} is not valid UTF-8", path)).into())
+}
+
+/// Whether a file can be read by its group or by everyone.
+#[cfg(unix)]
+pub fn is_shared_readable(path: &str) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path).is_ok_and(|m| m.permissions().mode() & 0o044 != 0)
+}
+
+#[cfg(not(unix))]
+pub fn is_shared_readable(_path: &str) -> bool {
+    false
+}
+
+fn warn_if_readable(path: &str, field: &str) {
+    if is_shared_readable(path) {
+        eprintln!("Warning: {} ({}) is readable by other users - run `chmod 600 {}`", path, field, path);
+    }
+}
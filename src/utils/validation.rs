@@ -0,0 +1,557 @@
+use anyhow::Result;
+use colored::*;
+use serde::Serialize;
+use serde_json::Value;
+use std::path::PathBuf;
+use crate::cardano::bech32;
+use crate::cardano::provider::PROVIDER_NAMES;
+use crate::utils::config::{Config, ENV_PREFIX};
+use crate::utils::output::Render;
+use crate::utils::secret;
+
+const KNOWN_NETWORKS: &[&str] = &["mainnet", "preprod", "preview", "testnet"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// Where a value was set: a file position or an environment variable.
+#[derive(Debug, Clone, Serialize)]
+pub struct Location {
+    pub source: String,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    #[serde(skip)]
+    snippet: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// Dotted field path, e.g. `pools[0].pledge_address`.
+    pub field: String,
+    pub message: String,
+    pub location: Option<Location>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ValidationReport {
+    pub profile: Option<String>,
+    pub files: Vec<String>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl ValidationReport {
+    pub fn errors(&self) -> usize {
+        self.diagnostics.iter().filter(|d| d.severity == Severity::Error).count()
+    }
+
+    pub fn warnings(&self) -> usize {
+        self.diagnostics.len() - self.errors()
+    }
+}
+
+impl Render for ValidationReport {
+    const SCHEMA: &'static str = "config-validate";
+
+    fn render_table(&self) {
+        match &self.profile {
+            Some(profile) => println!("{}", format!("🔎 Config Validation (profile {})", profile).blue().bold()),
+            None => println!("{}", "🔎 Config Validation".blue().bold()),
+        }
+        println!("{}", "=".repeat(50).blue());
+        for file in &self.files {
+            println!("  • {}", file.dimmed());
+        }
+        println!();
+
+        for diagnostic in &self.diagnostics {
+            println!("{}", format_diagnostic(diagnostic));
+        }
+
+        if self.diagnostics.is_empty() {
+            println!("{}", "✅ Configuration is valid".green());
+        } else {
+            println!("{} error(s), {} warning(s)", self.errors(), self.warnings());
+        }
+    }
+
+    fn records(&self) -> Result<Vec<Value>> {
+        self.diagnostics.iter().map(|d| Ok(serde_json::to_value(d)?)).collect()
+    }
+}
+
+/// Compiler-style rendering with the offending line and a caret under the value.
+pub fn format_diagnostic(diagnostic: &Diagnostic) -> String {
+    let label = match diagnostic.severity {
+        Severity::Error => "error".red().bold(),
+        Severity::Warning => "warning".yellow().bold(),
+    };
+    let mut text = format!("{}: {}: {}", label, diagnostic.field.bold(), diagnostic.message);
+
+    if let Some(location) = &diagnostic.location {
+        match (location.line, location.column) {
+            (Some(line), Some(column)) => {
+                let gutter = " ".repeat(line.to_string().len());
+                text.push_str(&format!("\n{}{} {}:{}:{}", gutter, "-->".blue(), location.source, line, column));
+                if let Some(snippet) = &location.snippet {
+                    text.push_str(&format!("\n {} {}", gutter, "|".blue()));
+                    text.push_str(&format!("\n {} {} {}", line.to_string().blue(), "|".blue(), snippet));
+                    text.push_str(&format!("\n {} {} {}{}", gutter, "|".blue(), " ".repeat(column - 1), "^".red()));
+                }
+            }
+            _ => text.push_str(&format!("\n {} {}", "-->".blue(), location.source)),
+        }
+    }
+    text.push('\n');
+    text
+}
+
+struct Checker<'a> {
+    config: &'a Config,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Checker<'_> {
+    fn push(&mut self, severity: Severity, field: impl Into<String>, message: impl Into<String>) {
+        self.diagnostics.push(Diagnostic {
+            severity,
+            field: field.into(),
+            message: message.into(),
+            location: None,
+        });
+    }
+
+    fn error(&mut self, field: impl Into<String>, message: impl Into<String>) {
+        self.push(Severity::Error, field, message);
+    }
+
+    fn warn(&mut self, field: impl Into<String>, message: impl Into<String>) {
+        self.push(Severity::Warning, field, message);
+    }
+
+    fn is_mainnet(&self) -> bool {
+        self.config.cardano.network == "mainnet"
+    }
+
+    fn range(&mut self, field: &str, value: f64, min: f64, max: f64) {
+        if !(min..=max).contains(&value) {
+            self.error(field, format!("{} is out of range - expected {} to {}", value, min, max));
+        }
+    }
+
+    fn positive(&mut self, field: &str, value: u64) {
+        if value == 0 {
+            self.error(field, "must be greater than 0");
+        }
+    }
+
+    fn url(&mut self, field: &str, url: &str, schemes: &[&str]) {
+        if !schemes.iter().any(|scheme| url.starts_with(&format!("{}://", scheme))) {
+            self.error(field, format!("'{}' is not a {} URL", url, schemes.join("/")));
+        }
+    }
+
+    fn file(&mut self, field: &str, path: &str) {
+        let metadata = match std::fs::metadata(path) {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                self.warn(field, format!("cannot access {}: {}", path, e));
+                return;
+            }
+        };
+        if !metadata.is_file() {
+            self.error(field, format!("{} is not a file", path));
+            return;
+        }
+
+        // Signing keys must not be readable by anyone but the owner
+        #[cfg(unix)]
+        if path.ends_with(".skey") {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = metadata.permissions().mode() & 0o777;
+            if mode & 0o077 != 0 {
+                self.error(field, format!("{} has mode {:o} - signing keys should be 600 or stricter", path, mode));
+            }
+        }
+    }
+
+    fn secret_file(&mut self, field: &str, path: &str) {
+        if secret::is_shared_readable(path) {
+            self.warn(field, format!("{} is readable by other users - run `chmod 600 {}`", path, path));
+        }
+    }
+
+    /// Check a bech32 address: prefix, network tag in the header byte and,
+    /// when `stake_only`, that it is a reward address.
+    fn address(&mut self, field: &str, text: &str, stake_only: bool) {
+        if text.is_empty() {
+            self.warn(field, "not set");
+            return;
+        }
+
+        let (hrp, payload) = match bech32::decode(text) {
+            Ok(decoded) => decoded,
+            Err(e) => {
+                self.error(field, format!("'{}' is not a valid bech32 address ({})", text, e));
+                return;
+            }
+        };
+
+        let suffix = if self.is_mainnet() { "" } else { "_test" };
+        let expected: Vec<String> = if stake_only {
+            vec![format!("stake{}", suffix)]
+        } else {
+            vec![format!("addr{}", suffix), format!("stake{}", suffix)]
+        };
+        if !expected.contains(&hrp) {
+            self.error(field, format!(
+                "prefix '{}' does not match network {} - expected {}",
+                hrp, self.config.cardano.network, expected.join(" or ")
+            ));
+            return;
+        }
+
+        let header = match payload.first() {
+            Some(&header) => header,
+            None => {
+                self.error(field, "address has no payload");
+                return;
+            }
+        };
+        let network_id = header & 0x0f;
+        if network_id != u8::from(self.is_mainnet()) {
+            self.error(field, format!("header network id {} does not match network {}", network_id, self.config.cardano.network));
+        }
+        if hrp.starts_with("stake") && header >> 4 != 0x0e && header >> 4 != 0x0f {
+            self.error(field, "stake prefix on an address that is not a reward address");
+        }
+    }
+}
+
+/// Check every field that can be checked without touching the network.
+pub fn validate(config: &Config) -> Vec<Diagnostic> {
+    let mut check = Checker { config, diagnostics: Vec::new() };
+
+    let cardano = &config.cardano;
+    if !KNOWN_NETWORKS.contains(&cardano.network.as_str()) && cardano.testnet_magic.is_none() {
+        check.error("cardano.network", format!(
+            "unknown network '{}' - expected one of {} or set testnet_magic",
+            cardano.network, KNOWN_NETWORKS.join(", ")
+        ));
+    }
+    match std::fs::metadata(&cardano.node_socket_path) {
+        Err(_) => check.warn("cardano.node_socket_path", format!("{} does not exist - cardano-cli queries will fail", cardano.node_socket_path)),
+        #[cfg(unix)]
+        Ok(metadata) if !std::os::unix::fs::FileTypeExt::is_socket(&metadata.file_type()) => {
+            check.error("cardano.node_socket_path", format!("{} is not a socket", cardano.node_socket_path))
+        }
+        Ok(_) => {}
+    }
+    for (field, path) in [
+        ("cardano.byron_genesis_file", &cardano.byron_genesis_file),
+        ("cardano.shelley_genesis_file", &cardano.shelley_genesis_file),
+    ] {
+        if let Some(path) = path {
+            check.file(field, path);
+        }
+    }
+    for (field, url) in [("cardano.node_prometheus_url", &cardano.node_prometheus_url), ("cardano.node_ekg_url", &cardano.node_ekg_url)] {
+        if let Some(url) = url {
+            check.url(field, url, &["http", "https"]);
+        }
+    }
+
+    if let Some(blockfrost) = &config.blockfrost {
+        check.url("blockfrost.base_url", &blockfrost.base_url, &["https", "http"]);
+        if blockfrost.api_key.is_empty() {
+            let source = match &blockfrost.api_key_env {
+                Some(name) => format!("set ${} or api_key_file", name),
+                None => "set api_key_env, api_key_file or api_key".to_string(),
+            };
+            check.warn("blockfrost.api_key", format!("no project ID - Blockfrost is disabled until you {}", source));
+        } else if !blockfrost.api_key.expose().starts_with(&cardano.network) && KNOWN_NETWORKS.contains(&cardano.network.as_str()) {
+            check.warn("blockfrost.api_key", format!("project ID is not for network {}", cardano.network));
+        }
+    }
+    if let Some(koios) = &config.koios {
+        check.url("koios.base_url", &koios.base_url, &["https", "http"]);
+    }
+    for (field, path) in [
+        ("blockfrost.api_key_file", config.blockfrost.as_ref().and_then(|b| b.api_key_file.as_ref())),
+        ("koios.api_token_file", config.koios.as_ref().and_then(|k| k.api_token_file.as_ref())),
+        ("secrets.identity_file", config.secrets.identity_file.as_ref()),
+    ] {
+        if let Some(path) = path {
+            check.secret_file(field, path);
+        }
+    }
+    if let Some(ogmios) = &config.ogmios {
+        check.url("ogmios.url", &ogmios.url, &["ws", "wss"]);
+    }
+
+    if config.pools.is_empty() {
+        check.warn("pools", "no pools configured");
+    }
+    for (i, pool) in config.pools.iter().enumerate() {
+        let field = |name: &str| format!("pools[{}].{}", i, name);
+
+        if config.pools[..i].iter().any(|p| p.pool_id == pool.pool_id) {
+            check.error(field("pool_id"), format!("{} is configured more than once", pool.pool_id));
+        }
+        if pool.ticker.is_empty() || pool.ticker.len() > 5 {
+            check.error(field("ticker"), format!("'{}' must be 1 to 5 characters", pool.ticker));
+        }
+
+        check.address(&field("pledge_address"), &pool.pledge_address, false);
+        check.address(&field("reward_address"), &pool.reward_address, true);
+
+        check.file(&field("vrf_key_file"), &pool.vrf_key_file);
+        for (name, path) in [("opcert_file", &pool.opcert_file), ("kes_skey_file", &pool.kes_skey_file), ("kes_vkey_file", &pool.kes_vkey_file)] {
+            if let Some(path) = path {
+                check.file(&field(name), path);
+            }
+        }
+    }
+
+    let monitoring = &config.monitoring;
+    check.positive("monitoring.prometheus_port", monitoring.prometheus_port as u64);
+    check.positive("monitoring.metrics_interval", monitoring.metrics_interval);
+    check.positive("monitoring.check_interval_seconds", monitoring.check_interval_seconds);
+    check.range("monitoring.alerts.saturation_threshold", monitoring.alerts.saturation_threshold, 0.0, 1.0);
+    if !monitoring.alerts.webhook_url.is_empty() {
+        check.url("monitoring.alerts.webhook_url", &monitoring.alerts.webhook_url, &["https", "http"]);
+    }
+
+    check.range("rewards.delegation_rewards_percentage", config.rewards.delegation_rewards_percentage, 0.0, 100.0);
+    if !["json", "csv"].contains(&config.rewards.output_format.as_str()) {
+        check.error("rewards.output_format", format!("'{}' - expected json or csv", config.rewards.output_format));
+    }
+
+    check.positive("daemon.tip_interval_seconds", config.daemon.tip_interval_seconds);
+
+    if config.providers.order.is_empty() {
+        check.error("providers.order", "no providers listed");
+    }
+    for name in &config.providers.order {
+        if !PROVIDER_NAMES.contains(&name.as_str()) {
+            check.error("providers.order", format!("unknown provider '{}' - expected one of {}", name, PROVIDER_NAMES.join(", ")));
+        }
+    }
+
+    check.range("verification.max_stake_delta_percent", config.verification.max_stake_delta_percent, 0.0, 100.0);
+    if config.cache.enabled {
+        check.positive("cache.default_ttl_seconds", config.cache.default_ttl_seconds);
+    }
+
+    check.diagnostics
+}
+
+/// Attach the file, line and column where each diagnostic's field was set.
+/// `layers` are lowest precedence first, as returned by `Config::layers`;
+/// fields of the active profile are looked up under `[profiles.<name>]` first.
+pub fn locate(diagnostics: &mut [Diagnostic], layers: &[PathBuf], profile: Option<&str>) {
+    let sources: Vec<(&PathBuf, String)> = layers.iter()
+        .filter_map(|path| std::fs::read_to_string(path).ok().map(|content| (path, content)))
+        .collect();
+
+    for diagnostic in diagnostics.iter_mut() {
+        let env_name = env_var_name(&diagnostic.field);
+        if std::env::var_os(&env_name).is_some() {
+            diagnostic.location = Some(Location { source: format!("${}", env_name), line: None, column: None, snippet: None });
+            continue;
+        }
+
+        let profile_field = profile.map(|name| format!("profiles.{}.{}", name, diagnostic.field));
+        let candidates: Vec<&str> = profile_field.iter().map(String::as_str)
+            .chain(std::iter::once(diagnostic.field.as_str()))
+            .collect();
+
+        diagnostic.location = candidates.iter().find_map(|field| sources.iter().rev().find_map(|(path, content)| {
+            find_field(content, field).map(|(line, column, snippet)| Location {
+                source: path.display().to_string(),
+                line: Some(line),
+                column: Some(column),
+                snippet: Some(snippet),
+            })
+        }));
+    }
+}
+
+fn env_var_name(field: &str) -> String {
+    let path = field.replace('[', ".").replace(']', "");
+    format!("{}{}", ENV_PREFIX, path.split('.').collect::<Vec<_>>().join("__").to_uppercase())
+}
+
+/// Find a dotted field path in TOML source, returning the 1-based line and
+/// column of its value. Handles `[table]` and `[[array]]` headers, which is
+/// all the config format uses.
+fn find_field(source: &str, field: &str) -> Option<(usize, usize, String)> {
+    let (table, key) = match field.rsplit_once('.') {
+        Some((table, key)) => (table, key),
+        None => ("", field),
+    };
+    let (table, index) = match table.split_once('[') {
+        Some((name, rest)) => (name, rest.trim_end_matches(']').parse::<usize>().ok()),
+        None => (table, None),
+    };
+
+    let mut current = String::new();
+    let mut array_counts: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
+    let mut current_index: Option<usize> = None;
+
+    for (number, line) in source.lines().enumerate() {
+        let trimmed = line.trim_start();
+
+        if let Some(header) = trimmed.strip_prefix("[[") {
+            current = header.split("]]").next().unwrap_or("").trim().to_string();
+            let count = array_counts.entry(current.clone()).or_insert(0);
+            current_index = Some(*count);
+            *count += 1;
+            continue;
+        }
+        if let Some(header) = trimmed.strip_prefix('[') {
+            current = header.split(']').next().unwrap_or("").trim().to_string();
+            current_index = None;
+            continue;
+        }
+
+        if current != table || (index.is_some() && current_index != index) {
+            continue;
+        }
+
+        let (name, _) = match trimmed.split_once('=') {
+            Some(pair) => pair,
+            None => continue,
+        };
+        if name.trim().trim_matches('"') != key {
+            continue;
+        }
+
+        let equals = line.find('=')?;
+        let value_offset = line[equals + 1..].len() - line[equals + 1..].trim_start().len();
+        let column = line[..equals + 1 + value_offset].chars().count() + 1;
+        return Some((number + 1, column, line.to_string()));
+    }
+
+    // Whole-table fields such as `pools` point at their first header
+    if key == field {
+        return source.lines().enumerate()
+            .find(|(_, line)| line.trim_start().trim_start_matches('[').starts_with(&format!("{}]", key)))
+            .map(|(number, line)| (number + 1, 1, line.to_string()));
+    }
+    None
+}
+
+/// Everything `config validate` reports, with locations.
+pub fn validate_layers(config: &Config, layers: &[PathBuf]) -> ValidationReport {
+    let mut diagnostics = validate(config);
+    locate(&mut diagnostics, layers, config.profile.as_deref());
+
+    for path in layers {
+        let source = path.display().to_string();
+        if secret::is_shared_readable(&source) {
+            diagnostics.push(Diagnostic {
+                severity: Severity::Warning,
+                field: "config file".to_string(),
+                message: format!("{} is readable by other users - run `chmod 600 {}` if it holds API keys", source, source),
+                location: Some(Location { source, line: None, column: None, snippet: None }),
+            });
+        }
+    }
+    ValidationReport {
+        profile: config.profile.clone(),
+        files: layers.iter().map(|p| p.display().to_string()).collect(),
+        diagnostics,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"[cardano]
+network = "mainnet"
+node_socket_path = "/ipc/node.socket"
+
+[[pools]]
+pool_id = "pool1aaa"
+ticker = "AAA"
+
+[[pools]]
+pool_id = "pool1bbb"
+  ticker =   "TOOLONG"
+
+[monitoring.alerts]
+saturation_threshold = 1.5
+
+[profiles.test.cardano]
+network = "preprod"
+"#;
+
+    fn diagnostic(field: &str) -> Diagnostic {
+        Diagnostic { severity: Severity::Error, field: field.to_string(), message: String::new(), location: None }
+    }
+
+    #[test]
+    fn fields_point_at_their_value() {
+        let (line, column, snippet) = find_field(CONFIG, "cardano.network").unwrap();
+        assert_eq!((line, column), (2, 11));
+        assert_eq!(snippet, "network = \"mainnet\"");
+
+        assert_eq!(find_field(CONFIG, "monitoring.alerts.saturation_threshold").map(|f| (f.0, f.1)), Some((14, 24)));
+        assert_eq!(find_field(CONFIG, "cardano.testnet_magic"), None);
+    }
+
+    #[test]
+    fn array_tables_are_counted() {
+        assert_eq!(find_field(CONFIG, "pools[0].ticker").map(|f| (f.0, f.1)), Some((7, 10)));
+        // Indented key and padded value
+        assert_eq!(find_field(CONFIG, "pools[1].ticker").map(|f| (f.0, f.1)), Some((11, 14)));
+        assert_eq!(find_field(CONFIG, "pools[2].ticker"), None);
+    }
+
+    #[test]
+    fn whole_tables_point_at_their_first_header() {
+        assert_eq!(find_field(CONFIG, "pools").map(|f| (f.0, f.1)), Some((5, 1)));
+        assert_eq!(find_field(CONFIG, "daemon"), None);
+    }
+
+    #[test]
+    fn profile_fields_are_found_before_base_fields() {
+        let path = std::env::temp_dir().join(format!("cvc-validation-{}.toml", std::process::id()));
+        std::fs::write(&path, CONFIG).unwrap();
+
+        let mut diagnostics = [diagnostic("cardano.network"), diagnostic("cardano.node_socket_path")];
+        locate(&mut diagnostics, std::slice::from_ref(&path), Some("test"));
+        let mut unprofiled = [diagnostic("cardano.network")];
+        locate(&mut unprofiled, std::slice::from_ref(&path), None);
+        let _ = std::fs::remove_file(&path);
+
+        let line = |d: &Diagnostic| d.location.as_ref().and_then(|l| l.line);
+        assert_eq!(line(&diagnostics[0]), Some(17));
+        // Not set by the profile, so the base value is blamed
+        assert_eq!(line(&diagnostics[1]), Some(3));
+        assert_eq!(line(&unprofiled[0]), Some(2));
+        assert_eq!(diagnostics[0].location.as_ref().unwrap().source, path.display().to_string());
+    }
+
+    #[test]
+    fn env_overrides_are_blamed_on_the_variable() {
+        assert_eq!(env_var_name("cardano.network"), "CVC_CARDANO__NETWORK");
+        assert_eq!(env_var_name("monitoring.alerts.webhook_url"), "CVC_MONITORING__ALERTS__WEBHOOK_URL");
+        assert_eq!(env_var_name("pools[1].ticker"), "CVC_POOLS__1__TICKER");
+
+        std::env::set_var("CVC_POOLS__1__TICKER", "BBB");
+        let mut diagnostics = [diagnostic("pools[1].ticker")];
+        locate(&mut diagnostics, &[], None);
+        std::env::remove_var("CVC_POOLS__1__TICKER");
+
+        let location = diagnostics[0].location.as_ref().unwrap();
+        assert_eq!(location.source, "$CVC_POOLS__1__TICKER");
+        assert_eq!(location.line, None);
+    }
+}